@@ -16,6 +16,9 @@ fn impl_downcast_macro(ast: &syn::DeriveInput) -> TokenStream {
             fn as_any(&self) -> &dyn Any {
                 self
             }
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
         }
     };
     gen.into()
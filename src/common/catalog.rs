@@ -0,0 +1,282 @@
+use super::error::{DbError, DbResult};
+use super::ty::Type;
+use crate::storage::db_file::DbFile;
+use crate::storage::heap_file::HeapFile;
+use crate::table::tuple_desc::TupleDesc;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+struct Table {
+    file: Arc<dyn DbFile>,
+    name: String,
+    pkey: Option<String>,
+}
+
+#[derive(Default)]
+struct Tables {
+    by_id: HashMap<u64, Table>,
+    by_name: HashMap<String, u64>,
+}
+
+/**
+ * The Catalog keeps track of all available tables in the database and their
+ * associated schemas.
+ */
+#[derive(Default)]
+pub struct Catalog {
+    tables: RwLock<Tables>,
+}
+
+impl Catalog {
+    /// Constructor. Creates a new, empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Add a new table to the catalog.
+     * This table's contents are stored in the specified DbFile.
+     * If a name conflict exists, use the last table to be added as the table
+     * for a given name. If a table with the same id exists, it is replaced.
+     */
+    pub fn add_table(&self, file: Arc<dyn DbFile>, name: &str, pkey: Option<&str>) {
+        let id = file.get_id();
+        let mut tables = self.tables.write().unwrap();
+        if let Some(old_id) = tables.by_name.remove(name) {
+            tables.by_id.remove(&old_id);
+        }
+        if let Some(old) = tables.by_id.remove(&id) {
+            tables.by_name.remove(&old.name);
+        }
+        tables.by_name.insert(name.to_string(), id);
+        tables.by_id.insert(
+            id,
+            Table {
+                file,
+                name: name.to_string(),
+                pkey: pkey.map(|k| k.to_string()),
+            },
+        );
+    }
+
+    /// Return the id of the table with a specified name.
+    pub fn get_table_id(&self, name: &str) -> DbResult<u64> {
+        self.tables
+            .read()
+            .unwrap()
+            .by_name
+            .get(name)
+            .copied()
+            .ok_or_else(|| DbError::NoSuchElement(format!("table {}", name)))
+    }
+
+    /// Returns the tuple descriptor (schema) of the specified table.
+    pub fn get_tuple_desc(&self, table_id: u64) -> DbResult<TupleDesc> {
+        self.with_table(table_id, |t| t.file.get_tuple_desc().clone())
+    }
+
+    /// Returns the DbFile that can be used to read the contents of the specified table.
+    pub fn get_database_file(&self, table_id: u64) -> DbResult<Arc<dyn DbFile>> {
+        self.with_table(table_id, |t| t.file.clone())
+    }
+
+    /// Returns the name of the primary key field of the specified table, if any.
+    pub fn get_primary_key(&self, table_id: u64) -> DbResult<Option<String>> {
+        self.with_table(table_id, |t| t.pkey.clone())
+    }
+
+    pub fn get_table_name(&self, table_id: u64) -> DbResult<String> {
+        self.with_table(table_id, |t| t.name.clone())
+    }
+
+    /// Returns the ids of all tables in the catalog, ordered by table name.
+    pub fn table_ids(&self) -> Vec<u64> {
+        let tables = self.tables.read().unwrap();
+        let mut names: Vec<(&String, &u64)> = tables.by_name.iter().collect();
+        names.sort();
+        names.into_iter().map(|(_, id)| *id).collect()
+    }
+
+    /// Removes the table with the specified name from the catalog.
+    pub fn drop_table(&self, name: &str) -> DbResult<()> {
+        let mut tables = self.tables.write().unwrap();
+        let id = tables
+            .by_name
+            .remove(name)
+            .ok_or_else(|| DbError::NoSuchElement(format!("table {}", name)))?;
+        tables.by_id.remove(&id);
+        Ok(())
+    }
+
+    /// Delete all tables from the catalog.
+    pub fn clear(&self) {
+        *self.tables.write().unwrap() = Tables::default();
+    }
+
+    /**
+     * Reads the schema from a file and creates the appropriate tables in the
+     * database. Each line describes one table:
+     *
+     * `name (field type [pk], field type [pk], ...)`
+     *
+     * where type is `int` or `string` and `pk` marks the primary key. The
+     * contents of each table are stored in `name.dat`, next to the schema
+     * file. Blank lines and lines starting with `#` are ignored.
+     */
+    pub fn load_schema<P: AsRef<Path>>(&self, catalog_file: P) -> DbResult<()> {
+        let catalog_file = catalog_file.as_ref();
+        let base = catalog_file.parent().unwrap_or_else(|| Path::new("."));
+        let contents = fs::read_to_string(catalog_file)?;
+        for (lineno, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, td, pkey) = Self::parse_schema_line(line)
+                .map_err(|e| DbError::Db(format!("line {}: {}", lineno + 1, e)))?;
+            let file = HeapFile::new(base.join(format!("{}.dat", name)), td)?;
+            self.add_table(Arc::new(file), &name, pkey.as_deref());
+        }
+        Ok(())
+    }
+
+    fn parse_schema_line(line: &str) -> DbResult<(String, TupleDesc, Option<String>)> {
+        let malformed = || DbError::Db(format!("malformed schema {}", line));
+        let open = line.find('(').ok_or_else(malformed)?;
+        let close = line.rfind(')').ok_or_else(malformed)?;
+        let name = line[..open].trim();
+        if name.is_empty() || close < open {
+            return Err(malformed());
+        }
+
+        let mut types = Vec::new();
+        let mut names = Vec::new();
+        let mut pkey = None;
+        for column in line[open + 1..close].split(',') {
+            let parts: Vec<&str> = column.split_whitespace().collect();
+            match parts.as_slice() {
+                [field, ty] => {
+                    names.push(*field);
+                    types.push(ty.parse::<Type>()?);
+                }
+                [field, ty, annotation] if annotation.eq_ignore_ascii_case("pk") => {
+                    names.push(*field);
+                    types.push(ty.parse::<Type>()?);
+                    pkey = Some(field.to_string());
+                }
+                _ => return Err(malformed()),
+            }
+        }
+        Ok((name.to_string(), TupleDesc::new(types, names), pkey))
+    }
+
+    fn with_table<T, F>(&self, table_id: u64, f: F) -> DbResult<T>
+    where
+        F: FnOnce(&Table) -> T,
+    {
+        self.tables
+            .read()
+            .unwrap()
+            .by_id
+            .get(&table_id)
+            .map(f)
+            .ok_or_else(|| DbError::NoSuchElement(format!("table id {}", table_id)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{int_tuple_desc, TempDir};
+
+    fn add_heap_file(catalog: &Catalog, dir: &TempDir, name: &str, pkey: Option<&str>) -> u64 {
+        let file = HeapFile::new(dir.path().join(name), int_tuple_desc(2)).unwrap();
+        let id = file.get_id();
+        catalog.add_table(Arc::new(file), name, pkey);
+        id
+    }
+
+    #[test]
+    fn test_lookup() {
+        let dir = TempDir::new("catalog_lookup");
+        let catalog = Catalog::new();
+        let id1 = add_heap_file(&catalog, &dir, "t1", Some("field0"));
+        let id2 = add_heap_file(&catalog, &dir, "t2", None);
+
+        assert_eq!(id1, catalog.get_table_id("t1").unwrap());
+        assert_eq!(id2, catalog.get_table_id("t2").unwrap());
+        assert!(catalog.get_table_id("t3").is_err());
+        assert_eq!("t1", catalog.get_table_name(id1).unwrap());
+        assert_eq!(int_tuple_desc(2), catalog.get_tuple_desc(id2).unwrap());
+        assert_eq!(
+            Some("field0".to_string()),
+            catalog.get_primary_key(id1).unwrap()
+        );
+        assert_eq!(None, catalog.get_primary_key(id2).unwrap());
+        assert_eq!(id1, catalog.get_database_file(id1).unwrap().get_id());
+        assert_eq!(vec![id1, id2], catalog.table_ids());
+    }
+
+    #[test]
+    fn test_name_conflict() {
+        let dir = TempDir::new("catalog_conflict");
+        let catalog = Catalog::new();
+        let id1 = add_heap_file(&catalog, &dir, "t1", None);
+        let file = HeapFile::new(dir.path().join("other"), int_tuple_desc(1)).unwrap();
+        let id2 = file.get_id();
+        catalog.add_table(Arc::new(file), "t1", None);
+
+        assert_eq!(id2, catalog.get_table_id("t1").unwrap());
+        assert!(catalog.get_tuple_desc(id1).is_err());
+        assert_eq!(vec![id2], catalog.table_ids());
+    }
+
+    #[test]
+    fn test_drop_table() {
+        let dir = TempDir::new("catalog_drop");
+        let catalog = Catalog::new();
+        let id = add_heap_file(&catalog, &dir, "t1", None);
+        catalog.drop_table("t1").unwrap();
+        assert!(catalog.get_table_id("t1").is_err());
+        assert!(catalog.get_database_file(id).is_err());
+        assert!(catalog.drop_table("t1").is_err());
+    }
+
+    #[test]
+    fn test_load_schema() {
+        let dir = TempDir::new("catalog_schema");
+        let schema = dir.path().join("catalog.txt");
+        fs::write(
+            &schema,
+            "# test schema\nusers (id int pk, name string)\n\norders (id int, user_id int, amount int)\n",
+        )
+        .unwrap();
+        let catalog = Catalog::new();
+        catalog.load_schema(&schema).unwrap();
+
+        let users = catalog.get_table_id("users").unwrap();
+        assert_eq!(
+            TupleDesc::new(vec![Type::Int, Type::Str], vec!["id", "name"]),
+            catalog.get_tuple_desc(users).unwrap()
+        );
+        assert_eq!(
+            Some("id".to_string()),
+            catalog.get_primary_key(users).unwrap()
+        );
+        let orders = catalog.get_table_id("orders").unwrap();
+        assert_eq!(3, catalog.get_tuple_desc(orders).unwrap().num_fields());
+        assert_eq!(None, catalog.get_primary_key(orders).unwrap());
+        assert!(dir.path().join("users.dat").exists());
+    }
+
+    #[test]
+    fn test_load_malformed_schema() {
+        let dir = TempDir::new("catalog_malformed");
+        let schema = dir.path().join("catalog.txt");
+        fs::write(&schema, "users (id int)\nbroken (id float)\n").unwrap();
+        let err = Catalog::new().load_schema(&schema).unwrap_err();
+        assert!(err.to_string().starts_with("line 2"));
+    }
+}
@@ -0,0 +1,161 @@
+use super::catalog::Catalog;
+use super::error::DbResult;
+use crate::storage::buffer_pool::{BufferPool, DEFAULT_PAGES};
+use crate::storage::log_file::LogFile;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Name of the schema file read when opening a database directory.
+pub const CATALOG_FILE: &str = "catalog.txt";
+/// Name of the write-ahead log kept in a database directory.
+pub const LOG_FILE: &str = "log";
+
+/**
+ * Database holds the state of a running database: its Catalog of tables and
+ * the BufferPool caching their pages.
+ */
+pub struct Database {
+    catalog: Catalog,
+    buffer_pool: BufferPool,
+}
+
+impl Database {
+    /// Creates a database with no tables and a pool of `num_pages` pages.
+    pub fn new(num_pages: usize) -> Self {
+        Self {
+            catalog: Catalog::new(),
+            buffer_pool: BufferPool::new(num_pages),
+        }
+    }
+
+    /**
+     * Opens the database stored in `dir`. The tables are described by the
+     * `catalog.txt` schema file in that directory. The write-ahead log in
+     * that directory is used to recover the tables from a crash.
+     */
+    pub fn open<P: AsRef<Path>>(dir: P) -> DbResult<Self> {
+        let db = Self::default();
+        db.catalog.load_schema(dir.as_ref().join(CATALOG_FILE))?;
+        let log = Arc::new(LogFile::new(dir.as_ref().join(LOG_FILE))?);
+        log.recover(&mut |image| {
+            let file = db.catalog.get_database_file(image.table_id)?;
+            let page = file.page_from_image(&image.pid, &image.data)?;
+            file.write_page(page.as_ref())
+        })?;
+        db.buffer_pool.set_log_file(log);
+        Ok(db)
+    }
+
+    pub fn get_catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    pub fn get_buffer_pool(&self) -> &BufferPool {
+        &self.buffer_pool
+    }
+
+    /**
+     * Writes all dirty pages to disk and records a checkpoint in the log,
+     * so that recovery does not have to redo older transactions.
+     */
+    pub fn checkpoint(&self) -> DbResult<()> {
+        self.buffer_pool.flush_all_pages()?;
+        if let Some(log) = self.buffer_pool.get_log_file() {
+            log.log_checkpoint()?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAGES)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execution::op_iterator::OpIterator;
+    use crate::execution::seq_scan::SeqScan;
+    use crate::test_util::{int_tuple, read_ints, TempDir};
+    use crate::transaction::transaction_id::TransactionId;
+    use std::fs;
+
+    fn open(dir: &TempDir) -> (Arc<Database>, u64) {
+        let db = Arc::new(Database::open(dir.path()).unwrap());
+        let id = db.get_catalog().get_table_id("t").unwrap();
+        (db, id)
+    }
+
+    fn insert(db: &Database, tid: TransactionId, id: u64, values: &[i32]) {
+        let file = db.get_catalog().get_database_file(id).unwrap();
+        db.get_buffer_pool()
+            .insert_tuple(tid, &file, int_tuple(values))
+            .unwrap();
+    }
+
+    fn read_table(db: &Arc<Database>, id: u64) -> Vec<Vec<i32>> {
+        let tid = TransactionId::new();
+        let mut scan = SeqScan::new(db.clone(), tid, id, "t").unwrap();
+        scan.open().unwrap();
+        let rows = read_ints(&mut scan);
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+        rows
+    }
+
+    #[test]
+    fn test_abort_after_steal() {
+        let dir = TempDir::new("database_abort");
+        fs::write(
+            dir.path().join(CATALOG_FILE),
+            "t (field0 int, field1 int)\n",
+        )
+        .unwrap();
+        let (db, id) = open(&dir);
+        let pool = db.get_buffer_pool();
+
+        let t1 = TransactionId::new();
+        insert(&db, t1, id, &[1, 1]);
+        pool.transaction_complete(t1, true).unwrap();
+
+        // the page holding the insert of t2 is written to disk before t2 aborts
+        let t2 = TransactionId::new();
+        insert(&db, t2, id, &[2, 2]);
+        pool.flush_all_pages().unwrap();
+        pool.transaction_complete(t2, false).unwrap();
+        assert_eq!(vec![vec![1, 1]], read_table(&db, id));
+    }
+
+    #[test]
+    fn test_recover() {
+        let dir = TempDir::new("database_recover");
+        fs::write(
+            dir.path().join(CATALOG_FILE),
+            "t (field0 int, field1 int)\n",
+        )
+        .unwrap();
+        {
+            let (db, id) = open(&dir);
+            let pool = db.get_buffer_pool();
+            let t1 = TransactionId::new();
+            insert(&db, t1, id, &[1, 1]);
+            pool.transaction_complete(t1, true).unwrap();
+            db.checkpoint().unwrap();
+
+            let t2 = TransactionId::new();
+            insert(&db, t2, id, &[2, 2]);
+            pool.transaction_complete(t2, true).unwrap();
+
+            // the insert of t3 reaches the disk, but t3 never completes
+            let t3 = TransactionId::new();
+            insert(&db, t3, id, &[3, 3]);
+            pool.flush_all_pages().unwrap();
+        }
+
+        let (db, id) = open(&dir);
+        assert_eq!(vec![vec![1, 1], vec![2, 2]], read_table(&db, id));
+    }
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/**
+ * Errors raised by SimpleDB.
+ */
+#[derive(Debug)]
+pub enum DbError {
+    /// An I/O failure of the underlying file system.
+    Io(io::Error),
+    /// A generic database failure, e.g. a full page or a schema mismatch.
+    Db(String),
+    /// A lookup of something that does not exist, e.g. an unknown table.
+    NoSuchElement(String),
+    /// The transaction must abort, e.g. it was chosen as a deadlock victim.
+    TransactionAborted(String),
+    /// Malformed or invalid input, e.g. a query naming an unknown column.
+    Parse(String),
+}
+
+pub type DbResult<T> = Result<T, DbError>;
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Db(msg) => write!(f, "{}", msg),
+            Self::NoSuchElement(msg) => write!(f, "no such element: {}", msg),
+            Self::TransactionAborted(msg) => write!(f, "transaction aborted: {}", msg),
+            Self::Parse(msg) => write!(f, "parse error: {}", msg),
+        }
+    }
+}
+
+impl Error for DbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DbError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
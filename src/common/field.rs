@@ -1,21 +1,38 @@
 use super::predicate::Op;
 use super::ty::Type;
 use crate::downcast::Downcast;
-use std::fs::File;
-use std::io;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
 
 /**
  * Interface for values of fields in tuples in SimpleDB.
  */
-pub trait Field: Downcast {
-    /// Write the bytes representing this field to the specified file.
-    fn serialize(&self, output: &mut File) -> io::Result<()>;
+pub trait Field: Downcast + Send + Sync {
+    /**
+     * Write the bytes representing this field to the specified output.
+     * Exactly `self.get_type().len()` bytes are written.
+     */
+    fn serialize(&self, output: &mut dyn Write) -> io::Result<()>;
 
     /// Compare the value of this field object to the passed in value.
     fn compare(&self, op: Op, other: &Self) -> bool
     where
         Self: Sized;
 
+    /**
+     * Compare the value of this field object to a field of any type.
+     * Returns None if the other field is of a different type.
+     */
+    fn compare_field(&self, op: Op, other: &dyn Field) -> Option<bool>;
+
+    /**
+     * Orders this field against a field of any type.
+     * Returns None if the other field is of a different type.
+     */
+    fn cmp_field(&self, other: &dyn Field) -> Option<Ordering>;
+
     /// Returns the type of this field
     fn get_type(&self) -> Type;
 
@@ -29,4 +46,114 @@ pub trait Field: Downcast {
     where
         Self: Sized;
     fn to_string(&self) -> String;
+
+    /// Returns a boxed copy of this field.
+    fn clone_box(&self) -> Box<dyn Field>;
+}
+
+impl Clone for Box<dyn Field> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/**
+ * Boxed fields are equal if they have the same type and value. They are
+ * totally ordered by type first, then by value, so they can be sorted and
+ * used as keys of maps.
+ */
+impl<'a> PartialEq for dyn Field + 'a {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp_field(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<'a> Eq for dyn Field + 'a {}
+
+impl<'a> PartialOrd for dyn Field + 'a {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for dyn Field + 'a {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_field(other)
+            .unwrap_or_else(|| self.get_type().cmp(&other.get_type()))
+    }
+}
+
+impl<'a> Hash for dyn Field + 'a {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash_code());
+    }
+}
+
+impl<'a> fmt::Debug for dyn Field + 'a {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.get_type(), self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::string_field::StringField;
+    use crate::common::ty::STRING_LEN;
+    use std::collections::HashSet;
+
+    fn int(v: i32) -> Box<dyn Field> {
+        Box::new(IntField::new(v))
+    }
+
+    fn string(v: &str) -> Box<dyn Field> {
+        Box::new(StringField::new(v.to_string(), STRING_LEN as usize))
+    }
+
+    #[test]
+    fn test_compare_field() {
+        assert_eq!(
+            Some(true),
+            int(1).compare_field(Op::LessThan, int(2).as_ref())
+        );
+        assert_eq!(
+            Some(false),
+            int(1).compare_field(Op::Equals, int(2).as_ref())
+        );
+        assert_eq!(
+            Some(true),
+            string("abc").compare_field(Op::Like, string("b").as_ref())
+        );
+        assert_eq!(None, int(1).compare_field(Op::Equals, string("1").as_ref()));
+        assert_eq!(None, string("1").compare_field(Op::Equals, int(1).as_ref()));
+    }
+
+    #[test]
+    fn test_cmp_field() {
+        assert_eq!(Some(Ordering::Greater), int(3).cmp_field(int(2).as_ref()));
+        assert_eq!(
+            Some(Ordering::Less),
+            string("a").cmp_field(string("b").as_ref())
+        );
+        assert_eq!(None, int(3).cmp_field(string("a").as_ref()));
+    }
+
+    #[test]
+    fn test_total_order() {
+        let mut fields = vec![string("b"), int(2), string("a"), int(-1)];
+        fields.sort();
+        assert_eq!(vec![int(-1), int(2), string("a"), string("b")], fields);
+        assert!(int(1).as_ref() != string("1").as_ref());
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut set: HashSet<Box<dyn Field>> = HashSet::new();
+        set.insert(int(1));
+        set.insert(int(1));
+        set.insert(string("1"));
+        assert_eq!(2, set.len());
+        assert!(set.contains(&int(1)));
+    }
 }
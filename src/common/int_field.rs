@@ -4,15 +4,15 @@ use super::ty::Type;
 use crate::downcast::Downcast;
 use downcast_macro_derive::Downcast;
 use std::any::Any;
+use std::cmp::Ordering;
 use std::collections::hash_map::DefaultHasher;
-use std::fs::File;
 use std::hash::{Hash, Hasher};
 use std::io::{self, Write};
 
 /**
  * Instance of Field that stores a single integer.
  */
-#[derive(PartialEq, Debug, Downcast)]
+#[derive(PartialEq, Clone, Debug, Downcast)]
 pub struct IntField {
     value: i32,
 }
@@ -28,9 +28,9 @@ impl IntField {
 }
 
 impl Field for IntField {
-    fn serialize(&self, output: &mut File) -> io::Result<()> {
-        output.write_all(self.to_string().as_bytes())?;
-        Ok(())
+    /// Writes the value as a big-endian 4-byte integer.
+    fn serialize(&self, output: &mut dyn Write) -> io::Result<()> {
+        output.write_all(&self.value.to_be_bytes())
     }
 
     fn compare(&self, op: Op, other: &Self) -> bool {
@@ -45,6 +45,20 @@ impl Field for IntField {
         }
     }
 
+    fn compare_field(&self, op: Op, other: &dyn Field) -> Option<bool> {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map(|other| self.compare(op, other))
+    }
+
+    fn cmp_field(&self, other: &dyn Field) -> Option<Ordering> {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map(|other| self.value.cmp(&other.value))
+    }
+
     fn equals(&self, other: &Self) -> bool {
         self.value == other.value
     }
@@ -62,4 +76,8 @@ impl Field for IntField {
         self.value.hash(&mut s);
         s.finish()
     }
+
+    fn clone_box(&self) -> Box<dyn Field> {
+        Box::new(self.clone())
+    }
 }
@@ -0,0 +1,53 @@
+use super::predicate::Op;
+use crate::table::tuple::Tuple;
+use std::fmt;
+
+/**
+ * JoinPredicate compares fields of two tuples using a predicate. JoinPredicate
+ * is most likely used by the Join operator.
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct JoinPredicate {
+    field1: usize,
+    op: Op,
+    field2: usize,
+}
+
+impl JoinPredicate {
+    /**
+     * Constructor -- create a new predicate over two fields of two tuples.
+     * field1 is the field of the left tuple, field2 the field of the right one.
+     */
+    pub fn new(field1: usize, op: Op, field2: usize) -> Self {
+        Self { field1, op, field2 }
+    }
+
+    /**
+     * Apply the predicate to the two specified tuples. Tuples whose join
+     * fields are missing or of different types do not match.
+     */
+    pub fn filter(&self, t1: &Tuple, t2: &Tuple) -> bool {
+        match (t1.get_field(self.field1), t2.get_field(self.field2)) {
+            (Some(f1), Some(f2)) => f1.compare_field(self.op, f2).unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    pub fn get_field1(&self) -> usize {
+        self.field1
+    }
+
+    pub fn get_field2(&self) -> usize {
+        self.field2
+    }
+
+    pub fn get_operator(&self) -> Op {
+        self.op
+    }
+}
+
+impl fmt::Display for JoinPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.field1, self.op, self.field2)
+    }
+}
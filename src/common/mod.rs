@@ -1,6 +1,11 @@
+pub mod catalog;
+pub mod database;
+pub mod error;
 pub mod field;
 pub mod int_field;
+pub mod join_predicate;
 pub mod page_id;
+pub mod permissions;
 pub mod predicate;
 pub mod string_field;
 pub mod ty;
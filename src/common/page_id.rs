@@ -1,7 +1,10 @@
+/// A (table id, page number) pair uniquely identifying a page.
+pub type PageKey = (u64, i32);
+
 /**
  * PageId is an interface to a specific page of a specific table.
  */
-pub trait PageId {
+pub trait PageId: Send + Sync {
     /** Return a representation of this page id object as a collection of
         integers (used for logging).
         This class MUST have a constructor that accepts n integer parameters,
@@ -17,4 +20,18 @@ pub trait PageId {
     where
         Self: Sized;
     fn get_page_number(&self) -> i32;
+
+    /// Returns a boxed copy of this page id.
+    fn clone_box(&self) -> Box<dyn PageId>;
+
+    /// Returns the key of this page, used to index maps of pages.
+    fn key(&self) -> PageKey {
+        (self.get_table_id(), self.get_page_number())
+    }
+}
+
+impl Clone for Box<dyn PageId> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
@@ -0,0 +1,11 @@
+/**
+ * Permissions requested when fetching a page, which decide the kind of lock
+ * the transaction takes on it.
+ */
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Permissions {
+    /// Takes a shared lock.
+    ReadOnly,
+    /// Takes an exclusive lock.
+    ReadWrite,
+}
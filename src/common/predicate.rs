@@ -1,77 +1,56 @@
 use super::field::Field;
-use super::int_field::IntField;
-use super::string_field::StringField;
 use crate::table::tuple::Tuple;
 use std::fmt;
 
 /**
  * Predicate compares tuples to a specified Field value.
  */
-struct Predicate<T: Field> {
+pub struct Predicate {
     /// field number of passed in tuples to compare against
-    field: i32,
+    field: usize,
     /// operation to use for comparison
     op: Op,
     /// field value to compare passed in tuples to
-    operand: T,
+    operand: Box<dyn Field>,
 }
 
-impl<T> Predicate<T>
-where
-    T: Field,
-{
-    pub fn new(field: i32, op: Op, operand: T) -> Self {
+impl Predicate {
+    pub fn new(field: usize, op: Op, operand: Box<dyn Field>) -> Self {
         Self { field, op, operand }
     }
 
-    pub fn get_operand(&self) -> Option<T> {
-        // some code goes here
-        None
+    pub fn get_operand(&self) -> &dyn Field {
+        self.operand.as_ref()
     }
 
-    pub fn get_field(&self) -> i32 {
-        // some code goes here
-        -1
+    pub fn get_field(&self) -> usize {
+        self.field
     }
 
-    pub fn get_op(&self) -> Option<Op> {
-        // some code goes here
-        None
+    pub fn get_op(&self) -> Op {
+        self.op
     }
 
-    pub fn filter(&self, t: Tuple) -> bool {
-        // some code goes here
-        false
+    /**
+     * Compares the field number of t specified in the constructor to the
+     * operand field specified in the constructor using the operator specific
+     * in the constructor. Tuples without such a field, or whose field has a
+     * different type than the operand, do not pass.
+     */
+    pub fn filter(&self, t: &Tuple) -> bool {
+        t.get_field(self.field)
+            .and_then(|field| field.compare_field(self.op, self.operand.as_ref()))
+            .unwrap_or(false)
     }
+}
 
-    pub fn to_string(&self) -> String {
-        // some code goes here
-        String::from("")
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.op, self.operand.to_string())
     }
 }
 
-// impl Predicate<IntField> {
-//     pub fn new(field: i32, op: Op, operand: IntField) -> Self {
-//         Self { field, op, operand }
-//     }
-
-//     pub fn get_operand(&self) -> Option<IntField> {
-//         // some code goes here
-//         None
-//     }
-// }
-
-// impl Predicate<StringField> {
-//     pub fn new(field: i32, op: Op, operand: StringField) -> Self {
-//         Self { field, op, operand }
-//     }
-
-//     pub fn get_operand(&self) -> Option<StringField> {
-//         // some code goes here
-//         None
-//     }
-// }
-
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum Op {
     Equals,
     GreaterThan,
@@ -111,3 +90,73 @@ impl fmt::Display for Op {
         write!(f, "{}", output)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::string_field::StringField;
+    use crate::common::ty::{Type, STRING_LEN};
+    use crate::table::tuple_desc::TupleDesc;
+
+    fn get_tuple(id: i32, name: &str) -> Tuple {
+        let td = TupleDesc::new(vec![Type::Int, Type::Str], vec!["id", "name"]);
+        let mut t = Tuple::new(td);
+        t.set_field(0, Box::new(IntField::new(id)));
+        t.set_field(
+            1,
+            Box::new(StringField::new(name.to_string(), STRING_LEN as usize)),
+        );
+        t
+    }
+
+    fn int_predicate(op: Op, value: i32) -> Predicate {
+        Predicate::new(0, op, Box::new(IntField::new(value)))
+    }
+
+    #[test]
+    fn test_filter_int() {
+        let t = get_tuple(1, "a");
+        assert!(int_predicate(Op::Equals, 1).filter(&t));
+        assert!(!int_predicate(Op::Equals, 2).filter(&t));
+        assert!(int_predicate(Op::GreaterThan, 0).filter(&t));
+        assert!(!int_predicate(Op::GreaterThan, 1).filter(&t));
+        assert!(int_predicate(Op::GreaterThanOrEq, 1).filter(&t));
+        assert!(int_predicate(Op::LessThan, 2).filter(&t));
+        assert!(!int_predicate(Op::LessThan, 1).filter(&t));
+        assert!(int_predicate(Op::LessThanOrEq, 1).filter(&t));
+        assert!(int_predicate(Op::NotEquals, 2).filter(&t));
+        assert!(!int_predicate(Op::NotEquals, 1).filter(&t));
+    }
+
+    #[test]
+    fn test_filter_string() {
+        let t = get_tuple(1, "hello world");
+        let like = |s: &str| {
+            Predicate::new(
+                1,
+                Op::Like,
+                Box::new(StringField::new(s.to_string(), STRING_LEN as usize)),
+            )
+        };
+        assert!(like("world").filter(&t));
+        assert!(!like("bye").filter(&t));
+    }
+
+    #[test]
+    fn test_filter_mismatch() {
+        let t = get_tuple(1, "a");
+        // type mismatch and missing field never pass
+        assert!(!Predicate::new(1, Op::NotEquals, Box::new(IntField::new(1))).filter(&t));
+        assert!(!Predicate::new(2, Op::NotEquals, Box::new(IntField::new(1))).filter(&t));
+    }
+
+    #[test]
+    fn test_to_string() {
+        let p = int_predicate(Op::LessThanOrEq, 3);
+        assert_eq!("0 <= 3", p.to_string());
+        assert_eq!(0, p.get_field());
+        assert_eq!(Op::LessThanOrEq, p.get_op());
+        assert_eq!("3", p.get_operand().to_string());
+    }
+}
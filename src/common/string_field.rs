@@ -1,30 +1,28 @@
 use super::field::Field;
 use super::predicate::Op;
-use super::ty::Type;
+use super::ty::{Type, STRING_LEN};
 use crate::downcast::Downcast;
 use downcast_macro_derive::Downcast;
 use std::any::Any;
+use std::cmp::Ordering;
 use std::collections::hash_map::DefaultHasher;
-use std::fs::File;
 use std::hash::{Hash, Hasher};
 use std::io::{self, Write};
 
 /**
  * Instance of Field that stores a single String of a fixed length.
  */
-#[derive(Downcast)]
+#[derive(Clone, Downcast)]
 pub struct StringField {
     value: String,
     max_size: usize,
 }
 
 impl StringField {
-    pub fn new(value: String, max_size: usize) -> Self {
-        let value = if value.len() < max_size {
-            value
-        } else {
-            value[0..max_size].to_string()
-        };
+    /// Creates a field holding `value` cut down to at most `max_size` bytes.
+    pub fn new(mut value: String, max_size: usize) -> Self {
+        let len = truncated_len(&value, max_size);
+        value.truncate(len);
         Self { value, max_size }
     }
 
@@ -33,22 +31,30 @@ impl StringField {
     }
 }
 
+/// The length of the longest prefix of `s` of at most `len` bytes that does
+/// not split a character.
+fn truncated_len(s: &str, len: usize) -> usize {
+    if s.len() <= len {
+        return s.len();
+    }
+    let mut end = len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    end
+}
+
 impl Field for StringField {
-    fn serialize(&self, output: &mut File) -> io::Result<()> {
-        let overflow = self.max_size as i32 - self.value.len() as i32;
-        let s = if overflow < 0 {
-            self.value[0..self.max_size].to_string()
-        } else {
-            self.value.clone()
-        };
-        let l = s.len() as i32;
-        output.write_all(l.to_string().as_bytes())?;
+    /**
+     * Writes the length of the string as a big-endian 4-byte integer,
+     * followed by the string bytes zero-padded to STRING_LEN bytes.
+     */
+    fn serialize(&self, output: &mut dyn Write) -> io::Result<()> {
+        let max_size = self.max_size.min(STRING_LEN as usize);
+        let s = &self.value[..truncated_len(&self.value, max_size)];
+        output.write_all(&(s.len() as i32).to_be_bytes())?;
         output.write_all(s.as_bytes())?;
-        if overflow > 0 {
-            let zeros: Vec<u8> = vec![0; overflow as usize];
-            output.write_all(&zeros)?;
-        }
-        Ok(())
+        output.write_all(&vec![0; STRING_LEN as usize - s.len()])
     }
 
     fn compare(&self, op: Op, other: &Self) -> bool {
@@ -63,6 +69,20 @@ impl Field for StringField {
         }
     }
 
+    fn compare_field(&self, op: Op, other: &dyn Field) -> Option<bool> {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map(|other| self.compare(op, other))
+    }
+
+    fn cmp_field(&self, other: &dyn Field) -> Option<Ordering> {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map(|other| self.value.cmp(&other.value))
+    }
+
     fn equals(&self, other: &Self) -> bool {
         self.value == other.value
     }
@@ -80,4 +100,8 @@ impl Field for StringField {
         self.value.hash(&mut s);
         s.finish()
     }
+
+    fn clone_box(&self) -> Box<dyn Field> {
+        Box::new(self.clone())
+    }
 }
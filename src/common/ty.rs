@@ -1,19 +1,24 @@
+use super::error::DbError;
+use super::field::Field;
 use super::int_field::IntField;
 use super::string_field::StringField;
 use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
 
-const STRING_LEN: i32 = 128;
+pub const STRING_LEN: i32 = 128;
 
 /**
  * Enum representing a type in SimpleDB.
  */
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
 pub enum Type {
     Int,
     Str,
 }
 
 impl Type {
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> i32 {
         match self {
             Self::Int => 4,
@@ -21,12 +26,36 @@ impl Type {
         }
     }
 
-    pub fn parse_int() -> Result<IntField, &'static str> {
-        Err("not implemented error")
+    /// Parse a field of this type, as written by Field::serialize, from the input.
+    pub fn parse(&self, input: &mut dyn Read) -> io::Result<Box<dyn Field>> {
+        Ok(match self {
+            Self::Int => Box::new(Self::parse_int(input)?),
+            Self::Str => Box::new(Self::parse_str(input)?),
+        })
     }
 
-    pub fn parse_str() -> Result<StringField, &'static str> {
-        Err("not implemented error")
+    /// Reads a big-endian 4-byte integer.
+    pub fn parse_int(input: &mut dyn Read) -> io::Result<IntField> {
+        let mut buf = [0; 4];
+        input.read_exact(&mut buf)?;
+        Ok(IntField::new(i32::from_be_bytes(buf)))
+    }
+
+    /// Reads a length-prefixed string followed by its padding.
+    pub fn parse_str(input: &mut dyn Read) -> io::Result<StringField> {
+        let len = Self::parse_int(input)?.get_value();
+        if !(0..=STRING_LEN).contains(&len) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid string length {}", len),
+            ));
+        }
+        let mut buf = vec![0; STRING_LEN as usize];
+        input.read_exact(&mut buf)?;
+        buf.truncate(len as usize);
+        let value =
+            String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(StringField::new(value, STRING_LEN as usize))
     }
 }
 
@@ -40,6 +69,19 @@ impl fmt::Display for Type {
     }
 }
 
+impl FromStr for Type {
+    type Err = DbError;
+
+    /// Parses a type name as written in schema files, e.g. `int` or `string`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "int" => Ok(Self::Int),
+            "string" | "str" => Ok(Self::Str),
+            _ => Err(DbError::Db(format!("unknown type {}", s))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -48,4 +90,58 @@ mod test {
         assert_eq!(Type::Int.len(), 4);
         assert_eq!(Type::Str.len(), 128 + 4);
     }
+
+    #[test]
+    fn test_int_round_trip() {
+        let mut buf = Vec::new();
+        IntField::new(-42).serialize(&mut buf).unwrap();
+        assert_eq!(Type::Int.len() as usize, buf.len());
+        assert_eq!(vec![0xff, 0xff, 0xff, 0xd6], buf);
+        assert_eq!(IntField::new(-42), Type::parse_int(&mut &buf[..]).unwrap());
+    }
+
+    #[test]
+    fn test_str_round_trip() {
+        let mut buf = Vec::new();
+        let field = StringField::new("hello".to_string(), STRING_LEN as usize);
+        field.serialize(&mut buf).unwrap();
+        assert_eq!(Type::Str.len() as usize, buf.len());
+        assert_eq!(vec![0, 0, 0, 5], buf[..4].to_vec());
+        assert!(buf[9..].iter().all(|b| *b == 0));
+
+        let parsed = Type::Str.parse(&mut &buf[..]).unwrap();
+        assert_eq!(Type::Str, parsed.get_type());
+        assert_eq!("hello", parsed.to_string());
+    }
+
+    #[test]
+    fn test_multibyte_str_round_trip() {
+        // the first STRING_LEN bytes end inside an 'é', which is left out
+        let value = format!("a{}", "é".repeat(100));
+        for max_size in [STRING_LEN as usize, 1000].iter() {
+            let field = StringField::new(value.clone(), *max_size);
+            let mut buf = Vec::new();
+            field.serialize(&mut buf).unwrap();
+            assert_eq!(Type::Str.len() as usize, buf.len());
+            assert_eq!(vec![0, 0, 0, 127], buf[..4].to_vec());
+
+            let parsed = Type::Str.parse(&mut &buf[..]).unwrap();
+            assert_eq!(value[..127], parsed.to_string());
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Type::parse_int(&mut &[0, 1][..]).is_err());
+        let mut buf = vec![0; Type::Str.len() as usize];
+        buf[..4].copy_from_slice(&(STRING_LEN + 1).to_be_bytes());
+        assert!(Type::parse_str(&mut &buf[..]).is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Type::Int, "int".parse().unwrap());
+        assert_eq!(Type::Str, "String".parse().unwrap());
+        assert!("float".parse::<Type>().is_err());
+    }
 }
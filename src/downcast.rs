@@ -2,6 +2,7 @@ use std::any::Any;
 
 pub trait Downcast {
     fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
 #[macro_export]
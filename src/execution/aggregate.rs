@@ -0,0 +1,295 @@
+use super::aggregator::{AggOp, Aggregator};
+use super::integer_aggregator::IntegerAggregator;
+use super::op_iterator::OpIterator;
+use super::string_aggregator::StringAggregator;
+use crate::common::error::{DbError, DbResult};
+use crate::common::ty::Type;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+
+/**
+ * The Aggregation operator that computes an aggregate (e.g., sum, avg, max,
+ * min). Note that we only support aggregates over a single column, grouped
+ * by a single column.
+ */
+pub struct Aggregate {
+    child: Box<dyn OpIterator>,
+    afield: usize,
+    gfield: Option<usize>,
+    aop: AggOp,
+    td: TupleDesc,
+    results: Option<Vec<Tuple>>,
+    pos: usize,
+}
+
+impl Aggregate {
+    /**
+     * Constructor.
+     *
+     * afield: the column over which we are computing an aggregate. gfield:
+     * the column over which we are grouping the result, or None if there is
+     * no grouping. aop: the aggregation operator to use.
+     *
+     * The output has the group field (if any) followed by the aggregate
+     * value, named like `SUM(price)`. Without grouping, a COUNT or SUM over
+     * a child without tuples is one tuple holding 0, while a MIN, MAX or
+     * AVG has no tuples, as there is no NULL to return.
+     */
+    pub fn new(
+        child: Box<dyn OpIterator>,
+        afield: usize,
+        gfield: Option<usize>,
+        aop: AggOp,
+    ) -> DbResult<Self> {
+        let child_td = child.get_tuple_desc();
+        let field = |i: usize| {
+            child_td
+                .get_field_type(i)
+                .map(|ty| (ty, child_td.get_field_name(i).unwrap().to_string()))
+                .ok_or_else(|| DbError::NoSuchElement(format!("field {}", i)))
+        };
+        let (atype, aname) = field(afield)?;
+        if atype == Type::Str && (aop == AggOp::Sum || aop == AggOp::Avg) {
+            return Err(DbError::Db(format!("{} is not supported on strings", aop)));
+        }
+
+        let mut types = Vec::new();
+        let mut names = Vec::new();
+        if let Some(g) = gfield {
+            let (gtype, gname) = field(g)?;
+            types.push(gtype);
+            names.push(gname);
+        }
+        types.push(match (atype, aop) {
+            (Type::Str, AggOp::Min) | (Type::Str, AggOp::Max) => Type::Str,
+            _ => Type::Int,
+        });
+        names.push(format!("{}({})", aop, aname));
+        let td = TupleDesc::new(types, names.iter().map(|n| n.as_str()).collect());
+
+        Ok(Self {
+            child,
+            afield,
+            gfield,
+            aop,
+            td,
+            results: None,
+            pos: 0,
+        })
+    }
+
+    /// Returns the group by field index in the input tuples, if grouping.
+    pub fn group_field(&self) -> Option<usize> {
+        self.gfield
+    }
+
+    /// Returns the aggregate field index in the input tuples.
+    pub fn aggregate_field(&self) -> usize {
+        self.afield
+    }
+
+    pub fn aggregate_op(&self) -> AggOp {
+        self.aop
+    }
+
+    fn compute(&mut self) -> DbResult<Vec<Tuple>> {
+        let child_type = self.child.get_tuple_desc().get_field_type(self.afield);
+        let mut aggregator: Box<dyn Aggregator> = match child_type {
+            Some(Type::Str) => Box::new(StringAggregator::new(self.gfield, self.afield, self.aop)?),
+            _ => Box::new(IntegerAggregator::new(self.gfield, self.afield, self.aop)),
+        };
+        while self.child.has_next()? {
+            aggregator.merge_tuple_into_group(&self.child.next()?);
+        }
+
+        let mut tuples = Vec::new();
+        for (group, value) in aggregator.results()? {
+            let mut t = Tuple::new(self.td.clone());
+            let mut i = 0;
+            if let Some(group) = group {
+                t.set_field(i, group);
+                i += 1;
+            }
+            t.set_field(i, value);
+            tuples.push(t);
+        }
+        Ok(tuples)
+    }
+}
+
+impl OpIterator for Aggregate {
+    fn open(&mut self) -> DbResult<()> {
+        self.child.open()?;
+        self.results = Some(self.compute()?);
+        self.pos = 0;
+        Ok(())
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        let results = self
+            .results
+            .as_ref()
+            .ok_or_else(|| DbError::Db("operator is not open".to_string()))?;
+        Ok(self.pos < results.len())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        self.pos += 1;
+        Ok(self.results.as_ref().unwrap()[self.pos - 1].clone())
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.has_next()?;
+        self.pos = 0;
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.results = None;
+        self.child.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::string_field::StringField;
+    use crate::common::ty::STRING_LEN;
+    use crate::test_util::{read_ints, scan_ints, scan_tuples};
+
+    fn get_scan() -> Box<dyn OpIterator> {
+        let rows = vec![
+            vec![1, 2],
+            vec![1, 4],
+            vec![1, 6],
+            vec![3, 2],
+            vec![3, 3],
+            vec![5, 7],
+        ];
+        scan_ints("t", 2, &rows)
+    }
+
+    fn aggregate(gfield: Option<usize>, aop: AggOp) -> Vec<Vec<i32>> {
+        let mut agg = Aggregate::new(get_scan(), 1, gfield, aop).unwrap();
+        agg.open().unwrap();
+        let rows = read_ints(&mut agg);
+        agg.rewind().unwrap();
+        assert_eq!(rows, read_ints(&mut agg));
+        rows
+    }
+
+    #[test]
+    fn test_grouped_int() {
+        assert_eq!(
+            vec![vec![1, 12], vec![3, 5], vec![5, 7]],
+            aggregate(Some(0), AggOp::Sum)
+        );
+        assert_eq!(
+            vec![vec![1, 2], vec![3, 2], vec![5, 7]],
+            aggregate(Some(0), AggOp::Min)
+        );
+        assert_eq!(
+            vec![vec![1, 6], vec![3, 3], vec![5, 7]],
+            aggregate(Some(0), AggOp::Max)
+        );
+        assert_eq!(
+            vec![vec![1, 4], vec![3, 2], vec![5, 7]],
+            aggregate(Some(0), AggOp::Avg)
+        );
+        assert_eq!(
+            vec![vec![1, 3], vec![3, 2], vec![5, 1]],
+            aggregate(Some(0), AggOp::Count)
+        );
+    }
+
+    #[test]
+    fn test_ungrouped_int() {
+        assert_eq!(vec![vec![24]], aggregate(None, AggOp::Sum));
+        assert_eq!(vec![vec![4]], aggregate(None, AggOp::Avg));
+        assert_eq!(vec![vec![6]], aggregate(None, AggOp::Count));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let run = |gfield, aop| {
+            let mut agg = Aggregate::new(scan_ints("t", 2, &[]), 1, gfield, aop).unwrap();
+            agg.open().map(|_| read_ints(&mut agg))
+        };
+        assert_eq!(vec![vec![0]], run(None, AggOp::Count).unwrap());
+        assert_eq!(vec![vec![0]], run(None, AggOp::Sum).unwrap());
+        assert!(run(None, AggOp::Min).unwrap().is_empty());
+        assert!(run(None, AggOp::Max).unwrap().is_empty());
+        assert!(run(None, AggOp::Avg).unwrap().is_empty());
+        assert!(run(Some(0), AggOp::Count).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sum_overflow() {
+        let rows = vec![vec![0, i32::MAX], vec![0, 1], vec![1, i32::MIN], vec![1, 1]];
+        let run = |gfield| {
+            let scan = scan_ints("t", 2, &rows);
+            let mut agg = Aggregate::new(scan, 1, gfield, AggOp::Sum).unwrap();
+            agg.open().map(|_| read_ints(&mut agg))
+        };
+        assert!(run(Some(0)).is_err());
+        // the overflows cancel out
+        assert_eq!(vec![vec![1]], run(None).unwrap());
+    }
+
+    #[test]
+    fn test_tuple_desc() {
+        let agg = Aggregate::new(get_scan(), 1, Some(0), AggOp::Sum).unwrap();
+        assert_eq!(
+            TupleDesc::new(
+                vec![Type::Int, Type::Int],
+                vec!["t.field0", "SUM(t.field1)"]
+            ),
+            *agg.get_tuple_desc()
+        );
+    }
+
+    #[test]
+    fn test_string() {
+        let td = TupleDesc::new(vec![Type::Int, Type::Str], vec!["g", "name"]);
+        let tuples: Vec<Tuple> = [(1, "b"), (1, "a"), (2, "c")]
+            .iter()
+            .map(|(g, name)| {
+                let mut t = Tuple::new(td.clone());
+                t.set_field(0, Box::new(IntField::new(*g)));
+                t.set_field(
+                    1,
+                    Box::new(StringField::new(name.to_string(), STRING_LEN as usize)),
+                );
+                t
+            })
+            .collect();
+        let scan = || scan_tuples("s", td.clone(), tuples.clone());
+
+        let run = |aop| {
+            let mut agg = Aggregate::new(scan(), 1, Some(0), aop).unwrap();
+            agg.open().unwrap();
+            let mut rows = Vec::new();
+            while agg.has_next().unwrap() {
+                let t = agg.next().unwrap();
+                rows.push(format!(
+                    "{}:{}",
+                    t.get_field(0).unwrap().to_string(),
+                    t.get_field(1).unwrap().to_string()
+                ));
+            }
+            rows
+        };
+        assert_eq!(vec!["1:a", "2:c"], run(AggOp::Min));
+        assert_eq!(vec!["1:b", "2:c"], run(AggOp::Max));
+        assert_eq!(vec!["1:2", "2:1"], run(AggOp::Count));
+        assert!(Aggregate::new(scan(), 1, Some(0), AggOp::Sum).is_err());
+    }
+}
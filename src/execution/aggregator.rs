@@ -0,0 +1,60 @@
+use crate::common::error::DbResult;
+use crate::common::field::Field;
+use crate::common::int_field::IntField;
+use crate::table::tuple::Tuple;
+use std::fmt;
+
+/**
+ * The aggregation operators supported by Aggregate.
+ */
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum AggOp {
+    Min,
+    Max,
+    Sum,
+    Avg,
+    Count,
+}
+
+impl fmt::Display for AggOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match self {
+            Self::Min => "MIN",
+            Self::Max => "MAX",
+            Self::Sum => "SUM",
+            Self::Avg => "AVG",
+            Self::Count => "COUNT",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// The value of a group (None when not grouping) and its aggregate value.
+pub type AggregateResult = (Option<Box<dyn Field>>, Box<dyn Field>);
+
+/**
+ * The common interface for any class that can compute an aggregate over a
+ * list of Tuples.
+ */
+pub trait Aggregator {
+    /// Merge a new tuple into the aggregate, grouping as indicated in the constructor.
+    fn merge_tuple_into_group(&mut self, t: &Tuple);
+
+    /**
+     * Returns the aggregate value of every group, ordered by group value.
+     * Without grouping and without tuples, see empty_result.
+     */
+    fn results(&self) -> DbResult<Vec<AggregateResult>>;
+}
+
+/**
+ * Returns the result of an aggregate without grouping over no tuples: a
+ * COUNT or SUM of 0. There is no NULL to stand for the MIN, MAX or AVG of
+ * no values, so those have no result at all.
+ */
+pub fn empty_result(what: AggOp) -> Vec<AggregateResult> {
+    match what {
+        AggOp::Count | AggOp::Sum => vec![(None, Box::new(IntField::new(0)))],
+        _ => vec![],
+    }
+}
@@ -0,0 +1,170 @@
+use super::op_iterator::OpIterator;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::int_field::IntField;
+use crate::common::ty::Type;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::sync::Arc;
+
+/**
+ * The delete operator. Delete reads tuples from its child operator and
+ * removes them from the table they belong to, as named by their RecordId.
+ * Returns a single tuple with one Int field holding the number of deleted
+ * records.
+ */
+pub struct Delete {
+    db: Arc<Database>,
+    tid: TransactionId,
+    child: Box<dyn OpIterator>,
+    table_id: u64,
+    td: TupleDesc,
+    result: Option<Tuple>,
+    fetched: bool,
+}
+
+impl Delete {
+    /**
+     * Constructor, as a part of transaction `tid`. Fails if the tuples of
+     * the child do not have the same field types as the table
+     * from which we are to delete.
+     */
+    pub fn new(
+        db: Arc<Database>,
+        tid: TransactionId,
+        child: Box<dyn OpIterator>,
+        table_id: u64,
+    ) -> DbResult<Self> {
+        let table_td = db.get_catalog().get_tuple_desc(table_id)?;
+        if !child.get_tuple_desc().types_match(&table_td) {
+            return Err(DbError::Db(format!(
+                "cannot delete tuples of ({}) from table {}",
+                child.get_tuple_desc(),
+                db.get_catalog().get_table_name(table_id)?
+            )));
+        }
+        Ok(Self {
+            db,
+            tid,
+            child,
+            table_id,
+            td: TupleDesc::new(vec![Type::Int], vec!["count"]),
+            result: None,
+            fetched: false,
+        })
+    }
+
+    pub fn get_table_id(&self) -> u64 {
+        self.table_id
+    }
+
+    fn delete_all(&mut self) -> DbResult<Tuple> {
+        let file = self.db.get_catalog().get_database_file(self.table_id)?;
+        let mut count = 0;
+        while self.child.has_next()? {
+            let t = self.child.next()?;
+            self.db
+                .get_buffer_pool()
+                .delete_tuple(self.tid, &file, &t)?;
+            count += 1;
+        }
+        let mut t = Tuple::new(self.td.clone());
+        t.set_field(0, Box::new(IntField::new(count)));
+        Ok(t)
+    }
+}
+
+impl OpIterator for Delete {
+    /// The tuples are deleted when the operator is opened.
+    fn open(&mut self) -> DbResult<()> {
+        self.child.open()?;
+        self.result = Some(self.delete_all()?);
+        self.fetched = false;
+        Ok(())
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.result.is_none() {
+            return Err(DbError::Db("operator is not open".to_string()));
+        }
+        Ok(!self.fetched)
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        self.fetched = true;
+        Ok(self.result.clone().unwrap())
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.has_next()?;
+        self.fetched = false;
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.result = None;
+        self.child.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::predicate::{Op, Predicate};
+    use crate::execution::filter::Filter;
+    use crate::execution::seq_scan::SeqScan;
+    use crate::test_util::{create_table, read_ints, scan_ints, TempDir};
+    use crate::transaction::transaction_id::TransactionId;
+
+    #[test]
+    fn test_delete() {
+        let dir = TempDir::new("delete");
+        let db = Arc::new(Database::new(10));
+        let tid = TransactionId::new();
+        let rows: Vec<Vec<i32>> = (0..1000).map(|i| vec![i, i % 3]).collect();
+        let id = create_table(&db, &dir, "t", 2, &rows);
+
+        let scan = Box::new(SeqScan::new(db.clone(), tid, id, "t").unwrap());
+        let pred = Predicate::new(1, Op::Equals, Box::new(IntField::new(0)));
+        let filter = Box::new(Filter::new(pred, scan));
+        let mut delete = Delete::new(db.clone(), tid, filter, id).unwrap();
+        delete.open().unwrap();
+        assert_eq!(vec![vec![334]], read_ints(&mut delete));
+        delete.close();
+
+        let expected: Vec<Vec<i32>> = rows.into_iter().filter(|r| r[1] != 0).collect();
+        let mut scan = SeqScan::new(db.clone(), tid, id, "t").unwrap();
+        scan.open().unwrap();
+        assert_eq!(expected, read_ints(&mut scan));
+
+        // the deletes reached the disk once committed
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+        let fresh = Arc::new(Database::new(10));
+        fresh
+            .get_catalog()
+            .add_table(db.get_catalog().get_database_file(id).unwrap(), "t", None);
+        let mut scan = SeqScan::new(fresh, TransactionId::new(), id, "t").unwrap();
+        scan.open().unwrap();
+        assert_eq!(expected.len(), read_ints(&mut scan).len());
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let dir = TempDir::new("delete_mismatch");
+        let db = Arc::new(Database::new(10));
+        let t2 = create_table(&db, &dir, "t2", 2, &[]);
+        let scan = scan_ints("t", 1, &[]);
+        assert!(Delete::new(db, TransactionId::new(), scan, t2).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::common::predicate::Predicate;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+
+/**
+ * Filter is an operator that implements a relational select.
+ */
+pub struct Filter {
+    pred: Predicate,
+    child: Box<dyn OpIterator>,
+    next: Option<Tuple>,
+}
+
+impl Filter {
+    /// Constructor accepts a predicate to apply and a child operator to read
+    /// tuples to filter from.
+    pub fn new(pred: Predicate, child: Box<dyn OpIterator>) -> Self {
+        Self {
+            pred,
+            child,
+            next: None,
+        }
+    }
+
+    pub fn get_predicate(&self) -> &Predicate {
+        &self.pred
+    }
+
+    /// Iterates over tuples from the child operator, returning the first one
+    /// that passes the predicate.
+    fn fetch_next(&mut self) -> DbResult<Option<Tuple>> {
+        while self.child.has_next()? {
+            let t = self.child.next()?;
+            if self.pred.filter(&t) {
+                return Ok(Some(t));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl OpIterator for Filter {
+    fn open(&mut self) -> DbResult<()> {
+        self.next = None;
+        self.child.open()
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.next.is_none() {
+            self.next = self.fetch_next()?;
+        }
+        Ok(self.next.is_some())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        Ok(self.next.take().unwrap())
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.next = None;
+        self.child.rewind()
+    }
+
+    fn close(&mut self) {
+        self.next = None;
+        self.child.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        self.child.get_tuple_desc()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::predicate::Op;
+    use crate::test_util::{read_ints, scan_ints};
+
+    #[test]
+    fn test_filter() {
+        let rows: Vec<Vec<i32>> = (0..100).map(|i| vec![i, i % 7]).collect();
+        let pred = Predicate::new(1, Op::Equals, Box::new(IntField::new(3)));
+
+        let mut filter = Filter::new(pred, scan_ints("t", 2, &rows));
+        assert_eq!(Some("t.field1"), filter.get_tuple_desc().get_field_name(1));
+        filter.open().unwrap();
+        let expected: Vec<Vec<i32>> = rows.into_iter().filter(|r| r[1] == 3).collect();
+        assert_eq!(expected, read_ints(&mut filter));
+
+        filter.rewind().unwrap();
+        assert_eq!(expected, read_ints(&mut filter));
+        filter.close();
+    }
+}
@@ -0,0 +1,169 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::common::join_predicate::JoinPredicate;
+use crate::common::predicate::Op;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use std::collections::{HashMap, VecDeque};
+
+/**
+ * HashEquiJoin implements an equality join by building an in-memory hash
+ * table over the left child, keyed on Field::hash_code of the join field,
+ * and probing it with each tuple of the right child.
+ */
+pub struct HashEquiJoin {
+    pred: JoinPredicate,
+    child1: Box<dyn OpIterator>,
+    child2: Box<dyn OpIterator>,
+    td: TupleDesc,
+    table: HashMap<u64, Vec<Tuple>>,
+    pending: VecDeque<Tuple>,
+}
+
+impl HashEquiJoin {
+    /// Constructor. Fails if the predicate does not test for equality.
+    pub fn new(
+        pred: JoinPredicate,
+        child1: Box<dyn OpIterator>,
+        child2: Box<dyn OpIterator>,
+    ) -> DbResult<Self> {
+        if pred.get_operator() != Op::Equals {
+            return Err(DbError::Db(format!(
+                "hash join requires an equality predicate, got {}",
+                pred.get_operator()
+            )));
+        }
+        let td = TupleDesc::merge(
+            child1.get_tuple_desc().clone(),
+            child2.get_tuple_desc().clone(),
+        );
+        Ok(Self {
+            pred,
+            child1,
+            child2,
+            td,
+            table: HashMap::new(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    pub fn get_join_predicate(&self) -> &JoinPredicate {
+        &self.pred
+    }
+
+    fn build(&mut self) -> DbResult<()> {
+        self.table.clear();
+        while self.child1.has_next()? {
+            let t = self.child1.next()?;
+            if let Some(field) = t.get_field(self.pred.get_field1()) {
+                self.table.entry(field.hash_code()).or_default().push(t);
+            }
+        }
+        Ok(())
+    }
+
+    fn fetch_next(&mut self) -> DbResult<Option<Tuple>> {
+        loop {
+            if let Some(t) = self.pending.pop_front() {
+                return Ok(Some(t));
+            }
+            if !self.child2.has_next()? {
+                return Ok(None);
+            }
+            let right = self.child2.next()?;
+            let key = match right.get_field(self.pred.get_field2()) {
+                Some(field) => field.hash_code(),
+                None => continue,
+            };
+            if let Some(bucket) = self.table.get(&key) {
+                for left in bucket.iter() {
+                    // Different values may share a hash code.
+                    if self.pred.filter(left, &right) {
+                        self.pending
+                            .push_back(Tuple::merge(self.td.clone(), left, &right));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl OpIterator for HashEquiJoin {
+    fn open(&mut self) -> DbResult<()> {
+        self.pending.clear();
+        self.child1.open()?;
+        self.child2.open()?;
+        self.build()
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.pending.is_empty() {
+            if let Some(t) = self.fetch_next()? {
+                self.pending.push_front(t);
+            }
+        }
+        Ok(!self.pending.is_empty())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        Ok(self.pending.pop_front().unwrap())
+    }
+
+    /// Only the right child is rescanned; the hash table is kept.
+    fn rewind(&mut self) -> DbResult<()> {
+        self.pending.clear();
+        self.child2.rewind()
+    }
+
+    fn close(&mut self) {
+        self.table.clear();
+        self.pending.clear();
+        self.child1.close();
+        self.child2.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{read_ints, scan_ints};
+
+    #[test]
+    fn test_hash_join() {
+        let left_rows: Vec<Vec<i32>> = (0..1000).map(|i| vec![i % 100, i]).collect();
+        let right_rows: Vec<Vec<i32>> = (0..50).map(|i| vec![i * 3, i]).collect();
+        let left = scan_ints("l", 2, &left_rows);
+        let right = scan_ints("r", 2, &right_rows);
+
+        let pred = JoinPredicate::new(0, Op::Equals, 0);
+        let mut join = HashEquiJoin::new(pred, left, right).unwrap();
+        join.open().unwrap();
+        let mut actual = read_ints(&mut join);
+        actual.sort();
+        let mut expected = Vec::new();
+        for l in left_rows.iter() {
+            for r in right_rows.iter().filter(|r| r[0] == l[0]) {
+                expected.push(vec![l[0], l[1], r[0], r[1]]);
+            }
+        }
+        expected.sort();
+        assert_eq!(expected, actual);
+
+        join.rewind().unwrap();
+        assert_eq!(expected.len(), read_ints(&mut join).len());
+    }
+
+    #[test]
+    fn test_requires_equality() {
+        let pred = JoinPredicate::new(0, Op::LessThan, 0);
+        let (left, right) = (scan_ints("a", 1, &[]), scan_ints("b", 1, &[]));
+        assert!(HashEquiJoin::new(pred, left, right).is_err());
+    }
+}
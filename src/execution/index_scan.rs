@@ -0,0 +1,244 @@
+use super::op_iterator::OpIterator;
+use super::seq_scan::alias_tuple_desc;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::predicate::Predicate;
+use crate::index::index_predicate::IndexPredicate;
+use crate::storage::db_file::DbFileIterator;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::sync::Arc;
+
+/**
+ * IndexScan is an access method that reads the tuples of an indexed table
+ * satisfying a predicate on the key field, in key order. Instead of
+ * reading the whole table like a SeqScan followed by a Filter, it starts
+ * at the first leaf that may hold a match.
+ */
+pub struct IndexScan {
+    db: Arc<Database>,
+    tid: TransactionId,
+    table_id: u64,
+    table_alias: String,
+    pred: IndexPredicate,
+    td: TupleDesc,
+    iter: Option<DbFileIterator>,
+    next: Option<Tuple>,
+}
+
+impl IndexScan {
+    /**
+     * Creates a scan of the tuples of the specified table satisfying
+     * `pred`, as a part of the specified transaction. The table must be
+     * indexed on the field `pred` is about.
+     *
+     * table_alias: the alias of this table (needed by the parser); the
+     * returned tuple_desc should have fields with name table_alias.field_name.
+     */
+    pub fn new(
+        db: Arc<Database>,
+        tid: TransactionId,
+        table_id: u64,
+        table_alias: &str,
+        pred: &Predicate,
+    ) -> DbResult<Self> {
+        if !Self::can_scan(&db, table_id, pred) {
+            return Err(DbError::Db(format!(
+                "table {} has no index on field {}",
+                db.get_catalog().get_table_name(table_id)?,
+                pred.get_field()
+            )));
+        }
+        let td = db.get_catalog().get_tuple_desc(table_id)?;
+        Ok(Self {
+            db,
+            tid,
+            table_id,
+            table_alias: table_alias.to_string(),
+            pred: IndexPredicate::new(pred.get_op(), pred.get_operand().clone_box()),
+            td: alias_tuple_desc(&td, table_alias),
+            iter: None,
+            next: None,
+        })
+    }
+
+    /// Returns true if the table is indexed on the field `pred` is about.
+    pub fn can_scan(db: &Database, table_id: u64, pred: &Predicate) -> bool {
+        db.get_catalog()
+            .get_database_file(table_id)
+            .map(|file| file.get_key_field() == Some(pred.get_field()))
+            .unwrap_or(false)
+    }
+
+    /// Returns the actual name of the table in the catalog of the database.
+    pub fn get_table_name(&self) -> DbResult<String> {
+        self.db.get_catalog().get_table_name(self.table_id)
+    }
+
+    /// Returns the alias of the table this operator scans.
+    pub fn get_alias(&self) -> &str {
+        &self.table_alias
+    }
+
+    pub fn get_predicate(&self) -> &IndexPredicate {
+        &self.pred
+    }
+
+    fn fetch_next(&mut self) -> DbResult<Option<Tuple>> {
+        let iter = self
+            .iter
+            .as_mut()
+            .ok_or_else(|| DbError::Db("operator is not open".to_string()))?;
+        match iter.next() {
+            Some(t) => {
+                let mut t = t?;
+                t.set_tuple_desc(self.td.clone());
+                Ok(Some(t))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl OpIterator for IndexScan {
+    fn open(&mut self) -> DbResult<()> {
+        let file = self.db.get_catalog().get_database_file(self.table_id)?;
+        self.iter = Some(file.index_iterator(self.db.clone(), self.tid, self.pred.clone())?);
+        self.next = None;
+        Ok(())
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.next.is_none() {
+            self.next = self.fetch_next()?;
+        }
+        Ok(self.next.is_some())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        Ok(self.next.take().unwrap())
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        if self.iter.is_none() {
+            return Err(DbError::Db("operator is not open".to_string()));
+        }
+        self.open()
+    }
+
+    fn close(&mut self) {
+        self.iter = None;
+        self.next = None;
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::predicate::Op;
+    use crate::index::btree_file::BTreeFile;
+    use crate::storage::db_file::DbFile;
+    use crate::test_util::{create_table, int_tuple, int_tuple_desc, read_ints, TempDir};
+
+    /// Creates a table indexed on field 1, holding `(i, (i * 37) % 100)` for i in 0..1000.
+    fn create_index(db: &Database, dir: &TempDir) -> u64 {
+        let f = BTreeFile::new(dir.path().join("t.dat"), int_tuple_desc(2), 1).unwrap();
+        let file: Arc<dyn DbFile> = Arc::new(f);
+        db.get_catalog().add_table(file.clone(), "t", None);
+        let tid = TransactionId::new();
+        for i in 0..1000 {
+            db.get_buffer_pool()
+                .insert_tuple(tid, &file, int_tuple(&[i, (i * 37) % 100]))
+                .unwrap();
+        }
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+        file.get_id()
+    }
+
+    fn scan(db: &Arc<Database>, table_id: u64, op: Op, value: i32) -> Vec<Vec<i32>> {
+        let pred = Predicate::new(1, op, Box::new(IntField::new(value)));
+        let mut scan =
+            IndexScan::new(db.clone(), TransactionId::new(), table_id, "t", &pred).unwrap();
+        scan.open().unwrap();
+        read_ints(&mut scan)
+    }
+
+    /// The rows matching the predicate, sorted on the key and then on field 0.
+    fn expected(op: Op, value: i32) -> Vec<Vec<i32>> {
+        let pred = Predicate::new(1, op, Box::new(IntField::new(value)));
+        let mut rows: Vec<Vec<i32>> = (0..1000)
+            .map(|i| vec![i, (i * 37) % 100])
+            .filter(|row| pred.filter(&int_tuple(row)))
+            .collect();
+        rows.sort_by_key(|row| (row[1], row[0]));
+        rows
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir = TempDir::new("index_scan_scan");
+        let db = Arc::new(Database::new(50));
+        let id = create_index(&db, &dir);
+        for &op in [
+            Op::Equals,
+            Op::LessThan,
+            Op::LessThanOrEq,
+            Op::GreaterThan,
+            Op::GreaterThanOrEq,
+            Op::NotEquals,
+        ]
+        .iter()
+        {
+            let mut rows = scan(&db, id, op, 42);
+            // the scan yields keys in order, but equal keys in no particular order
+            assert!(rows.windows(2).all(|w| w[0][1] <= w[1][1]), "{}", op);
+            rows.sort_by_key(|row| (row[1], row[0]));
+            assert_eq!(expected(op, 42), rows, "{}", op);
+        }
+        assert_eq!(10, scan(&db, id, Op::Equals, 42).len());
+        assert!(scan(&db, id, Op::Equals, 100).is_empty());
+    }
+
+    #[test]
+    fn test_rewind() {
+        let dir = TempDir::new("index_scan_rewind");
+        let db = Arc::new(Database::new(50));
+        let id = create_index(&db, &dir);
+        let pred = Predicate::new(1, Op::LessThan, Box::new(IntField::new(3)));
+        let mut scan = IndexScan::new(db, TransactionId::new(), id, "x", &pred).unwrap();
+        assert_eq!(Some("x.field1"), scan.get_tuple_desc().get_field_name(1));
+        assert!(scan.has_next().is_err());
+        scan.open().unwrap();
+        let rows = read_ints(&mut scan);
+        assert_eq!(30, rows.len());
+        scan.rewind().unwrap();
+        assert_eq!(rows, read_ints(&mut scan));
+        scan.close();
+        assert!(scan.next().is_err());
+    }
+
+    #[test]
+    fn test_not_indexed() {
+        let dir = TempDir::new("index_scan_not_indexed");
+        let db = Arc::new(Database::new(50));
+        let id = create_index(&db, &dir);
+        let heap_id = create_table(&db, &dir, "h", 2, &[]);
+        let pred = Predicate::new(0, Op::Equals, Box::new(IntField::new(3)));
+        assert!(!IndexScan::can_scan(&db, id, &pred));
+        assert!(IndexScan::new(db.clone(), TransactionId::new(), id, "t", &pred).is_err());
+        let pred = Predicate::new(1, Op::Equals, Box::new(IntField::new(3)));
+        assert!(IndexScan::can_scan(&db, id, &pred));
+        assert!(!IndexScan::can_scan(&db, heap_id, &pred));
+    }
+}
@@ -0,0 +1,197 @@
+use super::op_iterator::OpIterator;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::int_field::IntField;
+use crate::common::ty::Type;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::sync::Arc;
+
+/**
+ * Inserts tuples read from the child operator into the table specified in
+ * the constructor. Returns a single tuple with one Int field holding the
+ * number of inserted records.
+ */
+pub struct Insert {
+    db: Arc<Database>,
+    tid: TransactionId,
+    child: Box<dyn OpIterator>,
+    table_id: u64,
+    td: TupleDesc,
+    result: Option<Tuple>,
+    fetched: bool,
+}
+
+impl Insert {
+    /**
+     * Constructor, as a part of transaction `tid`. Fails if the tuples of
+     * the child do not have the same field types as the table
+     * into which we are to insert.
+     */
+    pub fn new(
+        db: Arc<Database>,
+        tid: TransactionId,
+        child: Box<dyn OpIterator>,
+        table_id: u64,
+    ) -> DbResult<Self> {
+        let table_td = db.get_catalog().get_tuple_desc(table_id)?;
+        if !child.get_tuple_desc().types_match(&table_td) {
+            return Err(DbError::Db(format!(
+                "cannot insert tuples of ({}) into table {}",
+                child.get_tuple_desc(),
+                db.get_catalog().get_table_name(table_id)?
+            )));
+        }
+        Ok(Self {
+            db,
+            tid,
+            child,
+            table_id,
+            td: TupleDesc::new(vec![Type::Int], vec!["count"]),
+            result: None,
+            fetched: false,
+        })
+    }
+
+    pub fn get_table_id(&self) -> u64 {
+        self.table_id
+    }
+
+    /**
+     * Reads every tuple of the child before inserting the first one, so that
+     * a child scanning the table itself does not see the inserted tuples.
+     */
+    fn insert_all(&mut self) -> DbResult<Tuple> {
+        let file = self.db.get_catalog().get_database_file(self.table_id)?;
+        let table_td = file.get_tuple_desc().clone();
+        let mut tuples = Vec::new();
+        while self.child.has_next()? {
+            tuples.push(self.child.next()?);
+        }
+        let count = tuples.len() as i32;
+        for mut t in tuples {
+            t.set_tuple_desc(table_td.clone());
+            self.db.get_buffer_pool().insert_tuple(self.tid, &file, t)?;
+        }
+        let mut t = Tuple::new(self.td.clone());
+        t.set_field(0, Box::new(IntField::new(count)));
+        Ok(t)
+    }
+}
+
+impl OpIterator for Insert {
+    /// The tuples are inserted when the operator is opened.
+    fn open(&mut self) -> DbResult<()> {
+        self.child.open()?;
+        self.result = Some(self.insert_all()?);
+        self.fetched = false;
+        Ok(())
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.result.is_none() {
+            return Err(DbError::Db("operator is not open".to_string()));
+        }
+        Ok(!self.fetched)
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        self.fetched = true;
+        Ok(self.result.clone().unwrap())
+    }
+
+    /// Rewinding returns the count again; it does not insert the tuples twice.
+    fn rewind(&mut self) -> DbResult<()> {
+        self.has_next()?;
+        self.fetched = false;
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.result = None;
+        self.child.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execution::seq_scan::SeqScan;
+    use crate::test_util::{create_table, read_ints, scan_ints, TempDir};
+    use crate::transaction::transaction_id::TransactionId;
+
+    #[test]
+    fn test_insert() {
+        let dir = TempDir::new("insert");
+        let db = Arc::new(Database::new(10));
+        let tid = TransactionId::new();
+        let src_rows: Vec<Vec<i32>> = (0..1500).map(|i| vec![i, -i]).collect();
+        let dst = create_table(&db, &dir, "dst", 2, &[vec![-1, 1]]);
+
+        let scan = scan_ints("s", 2, &src_rows);
+        let mut insert = Insert::new(db.clone(), tid, scan, dst).unwrap();
+        insert.open().unwrap();
+        assert_eq!(vec![vec![1500]], read_ints(&mut insert));
+        insert.rewind().unwrap();
+        assert_eq!(vec![vec![1500]], read_ints(&mut insert));
+        insert.close();
+
+        let mut expected = vec![vec![-1, 1]];
+        expected.extend(src_rows);
+        let mut scan = SeqScan::new(db.clone(), tid, dst, "d").unwrap();
+        scan.open().unwrap();
+        assert_eq!(expected, read_ints(&mut scan));
+
+        // the new pages were written to disk once committed
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+        let file = db.get_catalog().get_database_file(dst).unwrap();
+        assert_eq!(3, file.num_pages());
+    }
+
+    #[test]
+    fn test_insert_into_itself() {
+        let dir = TempDir::new("insert_itself");
+        let db = Arc::new(Database::new(50));
+        let tid = TransactionId::new();
+        let rows: Vec<Vec<i32>> = (0..1500).map(|i| vec![i, -i]).collect();
+        let table = create_table(&db, &dir, "t", 2, &rows);
+        let file = db.get_catalog().get_database_file(table).unwrap();
+        assert_eq!(3, file.num_pages());
+
+        // the scan must not see the tuples inserted behind it
+        let scan = Box::new(SeqScan::new(db.clone(), tid, table, "t").unwrap());
+        let mut insert = Insert::new(db.clone(), tid, scan, table).unwrap();
+        insert.open().unwrap();
+        assert_eq!(vec![vec![1500]], read_ints(&mut insert));
+        insert.close();
+
+        let mut expected = rows.clone();
+        expected.extend(rows);
+        let mut scan = SeqScan::new(db.clone(), tid, table, "t").unwrap();
+        scan.open().unwrap();
+        assert_eq!(expected, read_ints(&mut scan));
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+        assert_eq!(6, file.num_pages());
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let dir = TempDir::new("insert_mismatch");
+        let db = Arc::new(Database::new(10));
+        let dst = create_table(&db, &dir, "dst", 2, &[]);
+        let scan = scan_ints("s", 3, &[]);
+        assert!(Insert::new(db, TransactionId::new(), scan, dst).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use super::aggregator::{empty_result, AggOp, AggregateResult, Aggregator};
+use crate::common::error::{DbError, DbResult};
+use crate::common::field::Field;
+use crate::common::int_field::IntField;
+use crate::downcast;
+use crate::table::tuple::Tuple;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+struct IntState {
+    count: i32,
+    sum: i64,
+    min: i32,
+    max: i32,
+}
+
+/**
+ * Knows how to compute some aggregate over a set of IntFields.
+ */
+pub struct IntegerAggregator {
+    gfield: Option<usize>,
+    afield: usize,
+    what: AggOp,
+    groups: BTreeMap<Option<Box<dyn Field>>, IntState>,
+}
+
+impl IntegerAggregator {
+    /**
+     * gfield: the index of the group-by field in the tuple, or None if
+     * there is no grouping. afield: the index of the aggregate field in the
+     * tuple. what: the aggregation operator.
+     */
+    pub fn new(gfield: Option<usize>, afield: usize, what: AggOp) -> Self {
+        Self {
+            gfield,
+            afield,
+            what,
+            groups: BTreeMap::new(),
+        }
+    }
+}
+
+impl Aggregator for IntegerAggregator {
+    fn merge_tuple_into_group(&mut self, t: &Tuple) {
+        let key = self.gfield.map(|g| t.get_field(g).unwrap().clone_box());
+        let value = downcast!(t.get_field(self.afield).unwrap(), IntField).get_value();
+        let state = self.groups.entry(key).or_insert(IntState {
+            count: 0,
+            sum: 0,
+            min: value,
+            max: value,
+        });
+        state.count += 1;
+        state.sum += value as i64;
+        state.min = state.min.min(value);
+        state.max = state.max.max(value);
+    }
+
+    /// AVG uses integer division. Fails if a SUM does not fit in an Int.
+    fn results(&self) -> DbResult<Vec<AggregateResult>> {
+        if self.gfield.is_none() && self.groups.is_empty() {
+            return Ok(empty_result(self.what));
+        }
+        self.groups
+            .iter()
+            .map(|(key, state)| {
+                let value = match self.what {
+                    AggOp::Min => state.min,
+                    AggOp::Max => state.max,
+                    AggOp::Sum => i32::try_from(state.sum).map_err(|_| {
+                        DbError::Db(format!("SUM {} does not fit in an Int", state.sum))
+                    })?,
+                    AggOp::Avg => (state.sum / state.count as i64) as i32,
+                    AggOp::Count => state.count,
+                };
+                let value: Box<dyn Field> = Box::new(IntField::new(value));
+                Ok((key.clone(), value))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,163 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::common::join_predicate::JoinPredicate;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+
+/**
+ * The Join operator implements the relational join operation with a simple
+ * nested loop: every tuple of the left child is compared against every tuple
+ * of the right child.
+ */
+pub struct Join {
+    pred: JoinPredicate,
+    child1: Box<dyn OpIterator>,
+    child2: Box<dyn OpIterator>,
+    td: TupleDesc,
+    left: Option<Tuple>,
+    next: Option<Tuple>,
+}
+
+impl Join {
+    /**
+     * Constructor. Accepts two children to join and the predicate to join
+     * them on. The output schema is the left schema followed by the right one.
+     */
+    pub fn new(
+        pred: JoinPredicate,
+        child1: Box<dyn OpIterator>,
+        child2: Box<dyn OpIterator>,
+    ) -> Self {
+        let td = TupleDesc::merge(
+            child1.get_tuple_desc().clone(),
+            child2.get_tuple_desc().clone(),
+        );
+        Self {
+            pred,
+            child1,
+            child2,
+            td,
+            left: None,
+            next: None,
+        }
+    }
+
+    pub fn get_join_predicate(&self) -> &JoinPredicate {
+        &self.pred
+    }
+
+    /// Returns the field name of join field1, qualified by the table alias.
+    pub fn get_join_field1_name(&self) -> Option<&str> {
+        self.child1
+            .get_tuple_desc()
+            .get_field_name(self.pred.get_field1())
+    }
+
+    /// Returns the field name of join field2, qualified by the table alias.
+    pub fn get_join_field2_name(&self) -> Option<&str> {
+        self.child2
+            .get_tuple_desc()
+            .get_field_name(self.pred.get_field2())
+    }
+
+    /**
+     * Returns the next tuple generated by the join. For each tuple of the left
+     * child, the right child is rewound and scanned for matching tuples.
+     */
+    fn fetch_next(&mut self) -> DbResult<Option<Tuple>> {
+        loop {
+            if self.left.is_none() {
+                if !self.child1.has_next()? {
+                    return Ok(None);
+                }
+                self.left = Some(self.child1.next()?);
+                self.child2.rewind()?;
+            }
+            let left = self.left.as_ref().unwrap();
+            while self.child2.has_next()? {
+                let right = self.child2.next()?;
+                if self.pred.filter(left, &right) {
+                    return Ok(Some(Tuple::merge(self.td.clone(), left, &right)));
+                }
+            }
+            self.left = None;
+        }
+    }
+}
+
+impl OpIterator for Join {
+    fn open(&mut self) -> DbResult<()> {
+        self.left = None;
+        self.next = None;
+        self.child1.open()?;
+        self.child2.open()
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.next.is_none() {
+            self.next = self.fetch_next()?;
+        }
+        Ok(self.next.is_some())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        Ok(self.next.take().unwrap())
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.left = None;
+        self.next = None;
+        self.child1.rewind()?;
+        self.child2.rewind()
+    }
+
+    fn close(&mut self) {
+        self.left = None;
+        self.next = None;
+        self.child1.close();
+        self.child2.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::predicate::Op;
+    use crate::test_util::{read_ints, scan_ints};
+
+    #[test]
+    fn test_nested_loop_join() {
+        let left = vec![vec![1, 10], vec![2, 20], vec![3, 30]];
+        let right = vec![vec![2, 5], vec![3, 6], vec![3, 7], vec![4, 8]];
+
+        let mut join = Join::new(
+            JoinPredicate::new(0, Op::Equals, 0),
+            scan_ints("l", 2, &left),
+            scan_ints("r", 2, &right),
+        );
+        assert_eq!(4, join.get_tuple_desc().num_fields());
+        assert_eq!(Some("r.field0"), join.get_join_field2_name());
+        join.open().unwrap();
+        assert_eq!(
+            vec![vec![2, 20, 2, 5], vec![3, 30, 3, 6], vec![3, 30, 3, 7]],
+            read_ints(&mut join)
+        );
+
+        let mut join = Join::new(
+            JoinPredicate::new(0, Op::GreaterThan, 0),
+            scan_ints("l", 2, &left),
+            scan_ints("r", 2, &right),
+        );
+        join.open().unwrap();
+        assert_eq!(vec![vec![3, 30, 2, 5]], read_ints(&mut join));
+        join.rewind().unwrap();
+        assert_eq!(vec![vec![3, 30, 2, 5]], read_ints(&mut join));
+    }
+}
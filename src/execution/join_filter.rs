@@ -0,0 +1,100 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::common::join_predicate::JoinPredicate;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+
+/**
+ * JoinFilter is an operator that keeps the tuples of its child whose two
+ * fields satisfy a JoinPredicate, e.g. a join condition between two tables
+ * that the child has already joined.
+ */
+pub struct JoinFilter {
+    pred: JoinPredicate,
+    child: Box<dyn OpIterator>,
+    next: Option<Tuple>,
+}
+
+impl JoinFilter {
+    /// Constructor accepts a predicate over two fields of the tuples of the
+    /// child, and the child operator to read tuples to filter from.
+    pub fn new(pred: JoinPredicate, child: Box<dyn OpIterator>) -> Self {
+        Self {
+            pred,
+            child,
+            next: None,
+        }
+    }
+
+    pub fn get_join_predicate(&self) -> &JoinPredicate {
+        &self.pred
+    }
+
+    /// Iterates over tuples from the child operator, returning the first one
+    /// that passes the predicate.
+    fn fetch_next(&mut self) -> DbResult<Option<Tuple>> {
+        while self.child.has_next()? {
+            let t = self.child.next()?;
+            if self.pred.filter(&t, &t) {
+                return Ok(Some(t));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl OpIterator for JoinFilter {
+    fn open(&mut self) -> DbResult<()> {
+        self.next = None;
+        self.child.open()
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.next.is_none() {
+            self.next = self.fetch_next()?;
+        }
+        Ok(self.next.is_some())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        Ok(self.next.take().unwrap())
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.next = None;
+        self.child.rewind()
+    }
+
+    fn close(&mut self) {
+        self.next = None;
+        self.child.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        self.child.get_tuple_desc()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::predicate::Op;
+    use crate::test_util::{read_ints, scan_ints};
+
+    #[test]
+    fn test_join_filter() {
+        let rows: Vec<Vec<i32>> = (0..100).map(|i| vec![i % 5, i % 7, i]).collect();
+        let pred = JoinPredicate::new(0, Op::Equals, 1);
+        let mut filter = JoinFilter::new(pred, scan_ints("t", 3, &rows));
+        filter.open().unwrap();
+        let expected: Vec<Vec<i32>> = rows.into_iter().filter(|r| r[0] == r[1]).collect();
+        assert_eq!(expected, read_ints(&mut filter));
+
+        filter.rewind().unwrap();
+        assert_eq!(expected, read_ints(&mut filter));
+        filter.close();
+    }
+}
@@ -0,0 +1,17 @@
+pub mod aggregate;
+pub mod aggregator;
+pub mod delete;
+pub mod filter;
+pub mod hash_equi_join;
+pub mod index_scan;
+pub mod insert;
+pub mod integer_aggregator;
+pub mod join;
+pub mod join_filter;
+pub mod op_iterator;
+pub mod order_by;
+pub mod project;
+pub mod seq_scan;
+pub mod sort_merge_join;
+pub mod string_aggregator;
+pub mod tuple_iterator;
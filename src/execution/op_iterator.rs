@@ -0,0 +1,32 @@
+use crate::common::error::DbResult;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+
+/**
+ * OpIterator is the iterator interface that all SimpleDB operators should
+ * implement. Operators pull tuples from their children, volcano style.
+ */
+pub trait OpIterator {
+    /// Opens the iterator. This must be called before any of the other methods.
+    fn open(&mut self) -> DbResult<()>;
+
+    /// Returns true if the iterator has more tuples.
+    fn has_next(&mut self) -> DbResult<bool>;
+
+    /**
+     * Returns the next tuple from the operator (typically implementing by
+     * reading from a child operator or an access method). Fails with
+     * NoSuchElement if there are no more tuples.
+     */
+    fn next(&mut self) -> DbResult<Tuple>;
+
+    /// Resets the iterator to the start.
+    fn rewind(&mut self) -> DbResult<()>;
+
+    /// Closes the iterator. When the iterator is closed, calling next(),
+    /// has_next(), or rewind() should fail.
+    fn close(&mut self);
+
+    /// Returns the TupleDesc associated with this OpIterator.
+    fn get_tuple_desc(&self) -> &TupleDesc;
+}
@@ -0,0 +1,399 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::process;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// Default number of bytes of tuples OrderBy sorts in memory.
+pub const DEFAULT_MEMORY_BUDGET: usize = 4 * 1024 * 1024;
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/**
+ * A field to sort on, and its direction.
+ */
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct SortKey {
+    pub field: usize,
+    pub ascending: bool,
+}
+
+impl SortKey {
+    pub fn new(field: usize, ascending: bool) -> Self {
+        Self { field, ascending }
+    }
+}
+
+/**
+ * A sorted run of tuples spilled to a temporary file. The file is removed
+ * when the run is dropped.
+ */
+struct Run {
+    path: PathBuf,
+    reader: BufReader<File>,
+    len: usize,
+    remaining: usize,
+}
+
+impl Run {
+    fn write(td: &TupleDesc, tuples: &[Tuple]) -> DbResult<Self> {
+        let path = env::temp_dir().join(format!(
+            "simple_db_sort_{}_{}",
+            process::id(),
+            RUN_COUNTER.fetch_add(1, AtomicOrdering::SeqCst)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        let mut writer = BufWriter::new(file);
+        for t in tuples {
+            for field in t.get_fields() {
+                field.serialize(&mut writer)?;
+            }
+        }
+        writer.flush()?;
+        let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        debug_assert_eq!(
+            file.metadata()?.len(),
+            (tuples.len() * td.get_size() as usize) as u64
+        );
+        Ok(Self {
+            path,
+            reader: BufReader::new(file),
+            len: tuples.len(),
+            remaining: tuples.len(),
+        })
+    }
+
+    fn next(&mut self, td: &TupleDesc) -> DbResult<Option<Tuple>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let mut t = Tuple::new(td.clone());
+        for i in 0..td.num_fields() {
+            let ty = td.get_field_type(i).unwrap();
+            t.set_field(i, ty.parse(&mut self.reader)?);
+        }
+        Ok(Some(t))
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.reader.seek(SeekFrom::Start(0))?;
+        self.remaining = self.len;
+        Ok(())
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/**
+ * The next tuple of a run during the merge. Heads are ordered the other way
+ * around, so that the BinaryHeap, a max-heap, pops the smallest tuple first,
+ * and of equal tuples the one of the earliest run.
+ */
+struct Head {
+    tuple: Tuple,
+    run: usize,
+    keys: Rc<[SortKey]>,
+}
+
+impl Head {
+    /// Pushes the next tuple of `runs[run]`, if any, onto the heap.
+    fn push_next(
+        heads: &mut BinaryHeap<Head>,
+        runs: &mut [Run],
+        run: usize,
+        keys: &Rc<[SortKey]>,
+        td: &TupleDesc,
+    ) -> DbResult<()> {
+        if let Some(tuple) = runs[run].next(td)? {
+            heads.push(Head {
+                tuple,
+                run,
+                keys: keys.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> Ordering {
+        OrderBy::compare(&self.keys, &self.tuple, &other.tuple)
+            .then(self.run.cmp(&other.run))
+            .reverse()
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+enum Sorted {
+    Memory {
+        tuples: Vec<Tuple>,
+        pos: usize,
+    },
+    Runs {
+        runs: Vec<Run>,
+        heads: BinaryHeap<Head>,
+        keys: Rc<[SortKey]>,
+    },
+}
+
+/**
+ * OrderBy is an operator that implements ORDER BY over one or more fields,
+ * each ascending or descending.
+ *
+ * The input is sorted in memory while it fits in the memory budget.
+ * Otherwise, it is cut into sorted runs that are spilled to temporary files
+ * and merged back together when iterating.
+ */
+pub struct OrderBy {
+    keys: Vec<SortKey>,
+    child: Box<dyn OpIterator>,
+    memory_budget: usize,
+    sorted: Option<Sorted>,
+}
+
+impl OrderBy {
+    /// Creates an OrderBy sorting on `keys`, in order of precedence.
+    pub fn new(keys: Vec<SortKey>, child: Box<dyn OpIterator>) -> DbResult<Self> {
+        Self::with_memory_budget(keys, child, DEFAULT_MEMORY_BUDGET)
+    }
+
+    /**
+     * Creates an OrderBy that sorts at most `memory_budget` bytes of tuples
+     * in memory before spilling a sorted run to disk.
+     */
+    pub fn with_memory_budget(
+        keys: Vec<SortKey>,
+        child: Box<dyn OpIterator>,
+        memory_budget: usize,
+    ) -> DbResult<Self> {
+        let num_fields = child.get_tuple_desc().num_fields();
+        if let Some(key) = keys.iter().find(|key| key.field >= num_fields) {
+            return Err(DbError::NoSuchElement(format!("field {}", key.field)));
+        }
+        Ok(Self {
+            keys,
+            child,
+            memory_budget,
+            sorted: None,
+        })
+    }
+
+    pub fn get_keys(&self) -> &[SortKey] {
+        &self.keys
+    }
+
+    /// Returns the number of runs spilled to disk, 0 if sorted in memory.
+    pub fn num_runs(&self) -> usize {
+        match &self.sorted {
+            Some(Sorted::Runs { runs, .. }) => runs.len(),
+            _ => 0,
+        }
+    }
+
+    fn compare(keys: &[SortKey], a: &Tuple, b: &Tuple) -> Ordering {
+        for key in keys {
+            let ord = a.get_field(key.field).cmp(&b.get_field(key.field));
+            let ord = if key.ascending { ord } else { ord.reverse() };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    }
+
+    fn sort(&mut self) -> DbResult<Sorted> {
+        let td = self.child.get_tuple_desc().clone();
+        let tuple_size = td.get_size() as usize;
+        let keys = &self.keys;
+        let mut buffer = Vec::new();
+        let mut runs = Vec::new();
+        while self.child.has_next()? {
+            buffer.push(self.child.next()?);
+            if (buffer.len() + 1) * tuple_size > self.memory_budget {
+                buffer.sort_by(|a, b| Self::compare(keys, a, b));
+                runs.push(Run::write(&td, &buffer)?);
+                buffer.clear();
+            }
+        }
+        buffer.sort_by(|a, b| Self::compare(keys, a, b));
+        if runs.is_empty() {
+            return Ok(Sorted::Memory {
+                tuples: buffer,
+                pos: 0,
+            });
+        }
+        if !buffer.is_empty() {
+            runs.push(Run::write(&td, &buffer)?);
+        }
+        let keys: Rc<[SortKey]> = self.keys.clone().into();
+        let mut heads = BinaryHeap::with_capacity(runs.len());
+        for i in 0..runs.len() {
+            Head::push_next(&mut heads, &mut runs, i, &keys, &td)?;
+        }
+        Ok(Sorted::Runs { runs, heads, keys })
+    }
+}
+
+impl OpIterator for OrderBy {
+    fn open(&mut self) -> DbResult<()> {
+        self.child.open()?;
+        self.sorted = None;
+        self.sorted = Some(self.sort()?);
+        Ok(())
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        match &self.sorted {
+            Some(Sorted::Memory { tuples, pos }) => Ok(*pos < tuples.len()),
+            Some(Sorted::Runs { heads, .. }) => Ok(!heads.is_empty()),
+            None => Err(DbError::Db("operator is not open".to_string())),
+        }
+    }
+
+    /**
+     * With spilled runs, returns the smallest head of all runs (k-way
+     * merge), which a heap of the heads finds in O(log k) time.
+     */
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        let td = self.child.get_tuple_desc();
+        match self.sorted.as_mut().unwrap() {
+            Sorted::Memory { tuples, pos } => {
+                *pos += 1;
+                Ok(tuples[*pos - 1].clone())
+            }
+            Sorted::Runs { runs, heads, keys } => {
+                let head = heads.pop().unwrap();
+                Head::push_next(heads, runs, head.run, keys, td)?;
+                Ok(head.tuple)
+            }
+        }
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        let td = self.child.get_tuple_desc();
+        match self.sorted.as_mut() {
+            Some(Sorted::Memory { pos, .. }) => *pos = 0,
+            Some(Sorted::Runs { runs, heads, keys }) => {
+                heads.clear();
+                for i in 0..runs.len() {
+                    runs[i].rewind()?;
+                    Head::push_next(heads, runs, i, keys, td)?;
+                }
+            }
+            None => return Err(DbError::Db("operator is not open".to_string())),
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.sorted = None;
+        self.child.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        self.child.get_tuple_desc()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{read_ints, scan_ints};
+
+    fn get_rows() -> Vec<Vec<i32>> {
+        (0..1000)
+            .map(|i| vec![(i * 37) % 10, (i * 91) % 1000])
+            .collect()
+    }
+
+    fn sorted_rows(rows: &[Vec<i32>]) -> Vec<Vec<i32>> {
+        let mut expected = rows.to_vec();
+        expected.sort_by(|a, b| a[0].cmp(&b[0]).then(b[1].cmp(&a[1])));
+        expected
+    }
+
+    #[test]
+    fn test_in_memory() {
+        let rows = get_rows();
+        let keys = vec![SortKey::new(0, true), SortKey::new(1, false)];
+
+        let mut order_by = OrderBy::new(keys, scan_ints("t", 2, &rows)).unwrap();
+        order_by.open().unwrap();
+        assert_eq!(0, order_by.num_runs());
+        assert_eq!(sorted_rows(&rows), read_ints(&mut order_by));
+        order_by.rewind().unwrap();
+        assert_eq!(sorted_rows(&rows), read_ints(&mut order_by));
+    }
+
+    #[test]
+    fn test_external() {
+        let rows = get_rows();
+        let scan = scan_ints("t", 2, &rows);
+        let keys = vec![SortKey::new(0, true), SortKey::new(1, false)];
+
+        // 100 tuples of 8 bytes per run
+        let mut order_by = OrderBy::with_memory_budget(keys, scan, 800).unwrap();
+        order_by.open().unwrap();
+        assert_eq!(10, order_by.num_runs());
+        assert_eq!(sorted_rows(&rows), read_ints(&mut order_by));
+        order_by.rewind().unwrap();
+        assert_eq!(sorted_rows(&rows), read_ints(&mut order_by));
+        order_by.close();
+        assert!(order_by.has_next().is_err());
+    }
+
+    #[test]
+    fn test_external_keeps_order_of_equal_tuples() {
+        let rows = get_rows();
+        let scan = scan_ints("t", 2, &rows);
+
+        let mut order_by =
+            OrderBy::with_memory_budget(vec![SortKey::new(0, true)], scan, 800).unwrap();
+        order_by.open().unwrap();
+        assert_eq!(10, order_by.num_runs());
+        let mut expected = rows;
+        expected.sort_by_key(|r| r[0]);
+        assert_eq!(expected, read_ints(&mut order_by));
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        let scan = scan_ints("t", 2, &[]);
+        assert!(OrderBy::new(vec![SortKey::new(2, true)], scan).is_err());
+    }
+}
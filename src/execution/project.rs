@@ -0,0 +1,135 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+
+/**
+ * Project is an operator that implements a relational projection.
+ */
+pub struct Project {
+    field_list: Vec<usize>,
+    td: TupleDesc,
+    child: Box<dyn OpIterator>,
+}
+
+impl Project {
+    /**
+     * Constructor accepts a child operator to read tuples to apply projection
+     * to and a list of fields in output tuple. Fails if a field index is out
+     * of range of the child's TupleDesc.
+     */
+    pub fn new(field_list: Vec<usize>, child: Box<dyn OpIterator>) -> DbResult<Self> {
+        let child_td = child.get_tuple_desc();
+        let names = field_list
+            .iter()
+            .map(|&i| child_td.get_field_name(i).unwrap_or_default().to_string())
+            .collect();
+        Self::with_names(field_list, names, child)
+    }
+
+    /**
+     * Like new, but names the output fields `names` instead of after the
+     * fields of the child.
+     */
+    pub fn with_names(
+        field_list: Vec<usize>,
+        names: Vec<String>,
+        child: Box<dyn OpIterator>,
+    ) -> DbResult<Self> {
+        if names.len() != field_list.len() {
+            return Err(DbError::Db(format!(
+                "{} names given for {} fields",
+                names.len(),
+                field_list.len()
+            )));
+        }
+        let child_td = child.get_tuple_desc();
+        let mut types = Vec::with_capacity(field_list.len());
+        for &i in field_list.iter() {
+            let ty = child_td
+                .get_field_type(i)
+                .ok_or_else(|| DbError::NoSuchElement(format!("field {}", i)))?;
+            types.push(ty);
+        }
+        let td = TupleDesc::new(types, names.iter().map(|n| n.as_str()).collect());
+        Ok(Self {
+            field_list,
+            td,
+            child,
+        })
+    }
+
+    pub fn get_field_list(&self) -> &[usize] {
+        &self.field_list
+    }
+}
+
+impl OpIterator for Project {
+    fn open(&mut self) -> DbResult<()> {
+        self.child.open()
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        self.child.has_next()
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        let t = self.child.next()?;
+        let mut out = Tuple::new(self.td.clone());
+        for (i, &field) in self.field_list.iter().enumerate() {
+            out.set_field(i, t.get_field(field).unwrap().clone_box());
+        }
+        Ok(out)
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.child.rewind()
+    }
+
+    fn close(&mut self) {
+        self.child.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::ty::Type;
+    use crate::test_util::{read_ints, scan_ints};
+
+    #[test]
+    fn test_project() {
+        let rows: Vec<Vec<i32>> = (0..10).map(|i| vec![i, i + 1, i + 2]).collect();
+        let mut project = Project::new(vec![2, 0], scan_ints("t", 3, &rows)).unwrap();
+        assert_eq!(
+            TupleDesc::new(vec![Type::Int, Type::Int], vec!["t.field2", "t.field0"]),
+            *project.get_tuple_desc()
+        );
+        project.open().unwrap();
+        let expected: Vec<Vec<i32>> = rows.iter().map(|r| vec![r[2], r[0]]).collect();
+        assert_eq!(expected, read_ints(&mut project));
+        project.close();
+    }
+
+    #[test]
+    fn test_invalid_field() {
+        assert!(Project::new(vec![2], scan_ints("t", 2, &[])).is_err());
+    }
+
+    #[test]
+    fn test_with_names() {
+        let names = vec!["b".to_string(), "a".to_string()];
+        let scan = scan_ints("t", 2, &[vec![1, 2]]);
+        let mut project = Project::with_names(vec![1, 0], names, scan).unwrap();
+        assert_eq!(
+            TupleDesc::new(vec![Type::Int, Type::Int], vec!["b", "a"]),
+            *project.get_tuple_desc()
+        );
+        project.open().unwrap();
+        assert_eq!(vec![vec![2, 1]], read_ints(&mut project));
+    }
+}
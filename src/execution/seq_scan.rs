@@ -0,0 +1,166 @@
+use super::op_iterator::OpIterator;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::storage::db_file::DbFileIterator;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::sync::Arc;
+
+/**
+ * SeqScan is an implementation of a sequential scan access method that reads
+ * each tuple of a table in no particular order (e.g., as they are laid out on
+ * disk).
+ */
+pub struct SeqScan {
+    db: Arc<Database>,
+    tid: TransactionId,
+    table_id: u64,
+    table_alias: String,
+    td: TupleDesc,
+    iter: Option<DbFileIterator>,
+    next: Option<Tuple>,
+}
+
+impl SeqScan {
+    /**
+     * Creates a sequential scan over the specified table as a part of the
+     * specified transaction.
+     *
+     * table_alias: the alias of this table (needed by the parser); the
+     * returned tuple_desc should have fields with name table_alias.field_name.
+     */
+    pub fn new(
+        db: Arc<Database>,
+        tid: TransactionId,
+        table_id: u64,
+        table_alias: &str,
+    ) -> DbResult<Self> {
+        let td = db.get_catalog().get_tuple_desc(table_id)?;
+        Ok(Self {
+            db,
+            tid,
+            table_id,
+            table_alias: table_alias.to_string(),
+            td: alias_tuple_desc(&td, table_alias),
+            iter: None,
+            next: None,
+        })
+    }
+
+    /// Returns the actual name of the table in the catalog of the database.
+    pub fn get_table_name(&self) -> DbResult<String> {
+        self.db.get_catalog().get_table_name(self.table_id)
+    }
+
+    /// Returns the alias of the table this operator scans.
+    pub fn get_alias(&self) -> &str {
+        &self.table_alias
+    }
+
+    fn fetch_next(&mut self) -> DbResult<Option<Tuple>> {
+        let iter = self
+            .iter
+            .as_mut()
+            .ok_or_else(|| DbError::Db("operator is not open".to_string()))?;
+        match iter.next() {
+            Some(t) => {
+                let mut t = t?;
+                t.set_tuple_desc(self.td.clone());
+                Ok(Some(t))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/**
+ * Returns a copy of a table's TupleDesc with each field named
+ * table_alias.field_name, as scans of the table return them.
+ */
+pub(crate) fn alias_tuple_desc(td: &TupleDesc, table_alias: &str) -> TupleDesc {
+    let names: Vec<String> = (0..td.num_fields())
+        .map(|i| format!("{}.{}", table_alias, td.get_field_name(i).unwrap()))
+        .collect();
+    let types = (0..td.num_fields())
+        .map(|i| td.get_field_type(i).unwrap())
+        .collect();
+    TupleDesc::new(types, names.iter().map(|n| n.as_str()).collect())
+}
+
+impl OpIterator for SeqScan {
+    fn open(&mut self) -> DbResult<()> {
+        let file = self.db.get_catalog().get_database_file(self.table_id)?;
+        self.iter = Some(file.iterator(self.db.clone(), self.tid));
+        self.next = None;
+        Ok(())
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.next.is_none() {
+            self.next = self.fetch_next()?;
+        }
+        Ok(self.next.is_some())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        Ok(self.next.take().unwrap())
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        if self.iter.is_none() {
+            return Err(DbError::Db("operator is not open".to_string()));
+        }
+        self.open()
+    }
+
+    fn close(&mut self) {
+        self.iter = None;
+        self.next = None;
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{create_table, read_ints, TempDir};
+    use crate::transaction::transaction_id::TransactionId;
+
+    #[test]
+    fn test_alias() {
+        let dir = TempDir::new("seq_scan_alias");
+        let db = Arc::new(Database::new(10));
+        let table_id = create_table(&db, &dir, "t", 3, &[]);
+        let scan = SeqScan::new(db, TransactionId::new(), table_id, "x").unwrap();
+        assert_eq!(Some("x.field0"), scan.get_tuple_desc().get_field_name(0));
+        assert_eq!(Some("x.field2"), scan.get_tuple_desc().get_field_name(2));
+        assert_eq!("x", scan.get_alias());
+        assert_eq!("t", scan.get_table_name().unwrap());
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir = TempDir::new("seq_scan_scan");
+        let db = Arc::new(Database::new(10));
+        let rows: Vec<Vec<i32>> = (0..1200).map(|i| vec![i, i * 2]).collect();
+        let table_id = create_table(&db, &dir, "t", 2, &rows);
+
+        let mut scan = SeqScan::new(db, TransactionId::new(), table_id, "t").unwrap();
+        assert!(scan.has_next().is_err());
+        scan.open().unwrap();
+        assert_eq!(rows, read_ints(&mut scan));
+        assert!(matches!(scan.next(), Err(DbError::NoSuchElement(_))));
+
+        scan.rewind().unwrap();
+        assert_eq!(rows, read_ints(&mut scan));
+        scan.close();
+        assert!(scan.next().is_err());
+    }
+}
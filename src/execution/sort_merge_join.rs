@@ -0,0 +1,196 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::common::join_predicate::JoinPredicate;
+use crate::common::predicate::Op;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/**
+ * SortMergeJoin implements an equality join by sorting both children on
+ * their join fields and merging the sorted inputs. Both children are
+ * materialized in memory when the operator is opened.
+ */
+pub struct SortMergeJoin {
+    pred: JoinPredicate,
+    child1: Box<dyn OpIterator>,
+    child2: Box<dyn OpIterator>,
+    td: TupleDesc,
+    left: Vec<Tuple>,
+    right: Vec<Tuple>,
+    i: usize,
+    j: usize,
+    pending: VecDeque<Tuple>,
+}
+
+impl SortMergeJoin {
+    /**
+     * Constructor. Fails if the predicate does not test for equality or if a
+     * join field does not exist in the schema of its child.
+     */
+    pub fn new(
+        pred: JoinPredicate,
+        child1: Box<dyn OpIterator>,
+        child2: Box<dyn OpIterator>,
+    ) -> DbResult<Self> {
+        if pred.get_operator() != Op::Equals {
+            return Err(DbError::Db(format!(
+                "sort-merge join requires an equality predicate, got {}",
+                pred.get_operator()
+            )));
+        }
+        if pred.get_field1() >= child1.get_tuple_desc().num_fields()
+            || pred.get_field2() >= child2.get_tuple_desc().num_fields()
+        {
+            return Err(DbError::NoSuchElement("join field".to_string()));
+        }
+        let td = TupleDesc::merge(
+            child1.get_tuple_desc().clone(),
+            child2.get_tuple_desc().clone(),
+        );
+        Ok(Self {
+            pred,
+            child1,
+            child2,
+            td,
+            left: Vec::new(),
+            right: Vec::new(),
+            i: 0,
+            j: 0,
+            pending: VecDeque::new(),
+        })
+    }
+
+    pub fn get_join_predicate(&self) -> &JoinPredicate {
+        &self.pred
+    }
+
+    fn read_sorted(child: &mut Box<dyn OpIterator>, field: usize) -> DbResult<Vec<Tuple>> {
+        let mut tuples = Vec::new();
+        while child.has_next()? {
+            tuples.push(child.next()?);
+        }
+        tuples.sort_by(|a, b| a.get_field(field).cmp(&b.get_field(field)));
+        Ok(tuples)
+    }
+
+    fn compare(&self, i: usize, j: usize) -> Ordering {
+        let left = self.left[i].get_field(self.pred.get_field1()).unwrap();
+        let right = self.right[j].get_field(self.pred.get_field2()).unwrap();
+        left.cmp(right)
+    }
+
+    /// Advances both inputs until a run of equal keys is found, and emits
+    /// the cross product of the two runs.
+    fn fetch_next(&mut self) -> DbResult<Option<Tuple>> {
+        loop {
+            if let Some(t) = self.pending.pop_front() {
+                return Ok(Some(t));
+            }
+            if self.i >= self.left.len() || self.j >= self.right.len() {
+                return Ok(None);
+            }
+            match self.compare(self.i, self.j) {
+                Ordering::Less => self.i += 1,
+                Ordering::Greater => self.j += 1,
+                Ordering::Equal => {
+                    let mut i_end = self.i + 1;
+                    while i_end < self.left.len() && self.compare(i_end, self.j) == Ordering::Equal
+                    {
+                        i_end += 1;
+                    }
+                    let mut j_end = self.j + 1;
+                    while j_end < self.right.len() && self.compare(self.i, j_end) == Ordering::Equal
+                    {
+                        j_end += 1;
+                    }
+                    for left in self.left[self.i..i_end].iter() {
+                        for right in self.right[self.j..j_end].iter() {
+                            self.pending
+                                .push_back(Tuple::merge(self.td.clone(), left, right));
+                        }
+                    }
+                    self.i = i_end;
+                    self.j = j_end;
+                }
+            }
+        }
+    }
+}
+
+impl OpIterator for SortMergeJoin {
+    fn open(&mut self) -> DbResult<()> {
+        self.child1.open()?;
+        self.child2.open()?;
+        self.left = Self::read_sorted(&mut self.child1, self.pred.get_field1())?;
+        self.right = Self::read_sorted(&mut self.child2, self.pred.get_field2())?;
+        self.rewind()
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        if self.pending.is_empty() {
+            if let Some(t) = self.fetch_next()? {
+                self.pending.push_front(t);
+            }
+        }
+        Ok(!self.pending.is_empty())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        Ok(self.pending.pop_front().unwrap())
+    }
+
+    /// The sorted inputs are kept, so rewinding only restarts the merge.
+    fn rewind(&mut self) -> DbResult<()> {
+        self.i = 0;
+        self.j = 0;
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.left.clear();
+        self.right.clear();
+        self.pending.clear();
+        self.child1.close();
+        self.child2.close();
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execution::join::Join;
+    use crate::test_util::{read_ints, scan_ints};
+
+    #[test]
+    fn test_matches_nested_loop_join() {
+        let left_rows: Vec<Vec<i32>> = (0..600).map(|i| vec![(i * 7) % 40, i]).collect();
+        let right_rows: Vec<Vec<i32>> = (0..80).map(|i| vec![i % 25, i]).collect();
+        let left = || scan_ints("l", 2, &left_rows);
+        let right = || scan_ints("r", 2, &right_rows);
+        let pred = JoinPredicate::new(0, Op::Equals, 0);
+
+        let mut join = SortMergeJoin::new(pred, left(), right()).unwrap();
+        join.open().unwrap();
+        let mut actual = read_ints(&mut join);
+        let mut nested = Join::new(pred, left(), right());
+        nested.open().unwrap();
+        let mut expected = read_ints(&mut nested);
+        actual.sort();
+        expected.sort();
+        assert_eq!(expected, actual);
+
+        join.rewind().unwrap();
+        assert_eq!(expected.len(), read_ints(&mut join).len());
+        join.close();
+    }
+}
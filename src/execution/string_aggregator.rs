@@ -0,0 +1,82 @@
+use super::aggregator::{empty_result, AggOp, AggregateResult, Aggregator};
+use crate::common::error::{DbError, DbResult};
+use crate::common::field::Field;
+use crate::common::int_field::IntField;
+use crate::table::tuple::Tuple;
+use std::collections::BTreeMap;
+
+struct StrState {
+    count: i32,
+    min: Box<dyn Field>,
+    max: Box<dyn Field>,
+}
+
+/**
+ * Knows how to compute some aggregate over a set of StringFields. Only
+ * COUNT, MIN and MAX are supported.
+ */
+pub struct StringAggregator {
+    gfield: Option<usize>,
+    afield: usize,
+    what: AggOp,
+    groups: BTreeMap<Option<Box<dyn Field>>, StrState>,
+}
+
+impl StringAggregator {
+    /// Fails if `what` is SUM or AVG.
+    pub fn new(gfield: Option<usize>, afield: usize, what: AggOp) -> DbResult<Self> {
+        if what == AggOp::Sum || what == AggOp::Avg {
+            return Err(DbError::Db(format!("{} is not supported on strings", what)));
+        }
+        Ok(Self {
+            gfield,
+            afield,
+            what,
+            groups: BTreeMap::new(),
+        })
+    }
+}
+
+impl Aggregator for StringAggregator {
+    fn merge_tuple_into_group(&mut self, t: &Tuple) {
+        let key = self.gfield.map(|g| t.get_field(g).unwrap().clone_box());
+        let value = t.get_field(self.afield).unwrap();
+        match self.groups.get_mut(&key) {
+            Some(state) => {
+                state.count += 1;
+                if value < state.min.as_ref() {
+                    state.min = value.clone_box();
+                }
+                if value > state.max.as_ref() {
+                    state.max = value.clone_box();
+                }
+            }
+            None => {
+                let state = StrState {
+                    count: 1,
+                    min: value.clone_box(),
+                    max: value.clone_box(),
+                };
+                self.groups.insert(key, state);
+            }
+        }
+    }
+
+    fn results(&self) -> DbResult<Vec<AggregateResult>> {
+        if self.gfield.is_none() && self.groups.is_empty() {
+            return Ok(empty_result(self.what));
+        }
+        Ok(self
+            .groups
+            .iter()
+            .map(|(key, state)| {
+                let value = match self.what {
+                    AggOp::Min => state.min.clone(),
+                    AggOp::Max => state.max.clone(),
+                    _ => Box::new(IntField::new(state.count)),
+                };
+                (key.clone(), value)
+            })
+            .collect())
+    }
+}
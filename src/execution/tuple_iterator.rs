@@ -0,0 +1,94 @@
+use super::op_iterator::OpIterator;
+use crate::common::error::{DbError, DbResult};
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+
+/**
+ * TupleIterator is an operator that returns a fixed list of tuples, e.g. the
+ * rows of an INSERT ... VALUES statement.
+ */
+pub struct TupleIterator {
+    td: TupleDesc,
+    tuples: Vec<Tuple>,
+    pos: Option<usize>,
+}
+
+impl TupleIterator {
+    /// Constructor. Fails if a tuple does not have the field types of `td`.
+    pub fn new(td: TupleDesc, tuples: Vec<Tuple>) -> DbResult<Self> {
+        if let Some(t) = tuples.iter().find(|t| !t.get_tuple_desc().types_match(&td)) {
+            return Err(DbError::Db(format!(
+                "tuple ({}) does not match ({})",
+                t.get_tuple_desc(),
+                td
+            )));
+        }
+        Ok(Self {
+            td,
+            tuples,
+            pos: None,
+        })
+    }
+}
+
+impl OpIterator for TupleIterator {
+    fn open(&mut self) -> DbResult<()> {
+        self.pos = Some(0);
+        Ok(())
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        let pos = self
+            .pos
+            .ok_or_else(|| DbError::Db("operator is not open".to_string()))?;
+        Ok(pos < self.tuples.len())
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        if !self.has_next()? {
+            return Err(DbError::NoSuchElement("no more tuples".to_string()));
+        }
+        let pos = self.pos.unwrap();
+        self.pos = Some(pos + 1);
+        let mut t = self.tuples[pos].clone();
+        t.set_tuple_desc(self.td.clone());
+        Ok(t)
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.close();
+        self.open()
+    }
+
+    fn close(&mut self) {
+        self.pos = None;
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::ty::Type;
+    use crate::test_util::{int_tuple, int_tuple_desc, read_ints};
+
+    #[test]
+    fn test_iterate() {
+        let td = TupleDesc::new(vec![Type::Int, Type::Int], vec!["a", "b"]);
+        let rows = vec![vec![1, 2], vec![3, 4]];
+        let tuples = rows.iter().map(|row| int_tuple(row)).collect();
+        let mut it = TupleIterator::new(td.clone(), tuples).unwrap();
+        assert!(it.has_next().is_err());
+        it.open().unwrap();
+        assert_eq!(rows, read_ints(&mut it));
+        assert!(it.next().is_err());
+        it.rewind().unwrap();
+        assert_eq!(td, *it.next().unwrap().get_tuple_desc());
+
+        let tuples = vec![int_tuple(&[1, 2, 3])];
+        assert!(TupleIterator::new(int_tuple_desc(2), tuples).is_err());
+    }
+}
@@ -0,0 +1,1019 @@
+use super::btree_internal_page::BTreeInternalPage;
+use super::btree_leaf_page::BTreeLeafPage;
+use super::btree_page_id::{BTreePageId, PageCategory};
+use super::btree_root_ptr_page::BTreeRootPtrPage;
+use super::index_predicate::IndexPredicate;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::field::Field;
+use crate::common::page_id::{PageId, PageKey};
+use crate::common::permissions::Permissions;
+use crate::common::predicate::Op;
+use crate::storage::buffer_pool::{BufferPool, PageRef};
+use crate::storage::db_file::{DbFile, DbFileIterator};
+use crate::storage::page::{Page, PAGE_SIZE};
+use crate::storage::page_file::PageFile;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::vec::IntoIter;
+
+fn as_leaf(page: &mut Box<dyn Page>) -> &mut BTreeLeafPage {
+    page.as_any_mut().downcast_mut::<BTreeLeafPage>().unwrap()
+}
+
+fn as_internal(page: &mut Box<dyn Page>) -> &mut BTreeInternalPage {
+    page.as_any_mut()
+        .downcast_mut::<BTreeInternalPage>()
+        .unwrap()
+}
+
+fn as_root_ptr(page: &mut Box<dyn Page>) -> &mut BTreeRootPtrPage {
+    page.as_any_mut()
+        .downcast_mut::<BTreeRootPtrPage>()
+        .unwrap()
+}
+
+/// Returns the id of the root page, as recorded by the root pointer page.
+fn get_root_id(page: &PageRef) -> BTreePageId {
+    let page = page.read().unwrap();
+    let root_ptr = page.as_any().downcast_ref::<BTreeRootPtrPage>().unwrap();
+    root_ptr.get_root_id()
+}
+
+/**
+ * The pages fetched by one insert or delete on behalf of a transaction.
+ * Pages fetched for writing are marked dirty right away, so the BufferPool
+ * does not evict them while the tree is being rearranged.
+ */
+struct PageAccess<'a> {
+    pool: &'a BufferPool,
+    tid: TransactionId,
+    dirty: HashMap<PageKey, PageRef>,
+}
+
+impl<'a> PageAccess<'a> {
+    fn new(pool: &'a BufferPool, tid: TransactionId) -> Self {
+        Self {
+            pool,
+            tid,
+            dirty: HashMap::new(),
+        }
+    }
+
+    fn get_page(
+        &mut self,
+        file: &dyn DbFile,
+        pid: &BTreePageId,
+        perm: Permissions,
+    ) -> DbResult<PageRef> {
+        if let Some(page) = self.dirty.get(&pid.key()) {
+            return Ok(page.clone());
+        }
+        let page = self.pool.get_page(self.tid, file, pid, perm)?;
+        if perm == Permissions::ReadWrite {
+            page.write().unwrap().mark_dirty(true, self.tid);
+            self.dirty.insert(pid.key(), page.clone());
+        }
+        Ok(page)
+    }
+
+    fn into_dirty_pages(self) -> Vec<PageRef> {
+        self.dirty.into_values().collect()
+    }
+}
+
+/**
+ * Follows the tree from the root down to the leftmost leaf that may hold
+ * `key`, or to the leftmost leaf if `key` is None. Returns the internal
+ * pages on the way, from the root down, and the leaf. Only the internal
+ * pages are read, with a shared lock.
+ */
+fn find_leaf(
+    pool: &BufferPool,
+    tid: TransactionId,
+    file: &dyn DbFile,
+    key: Option<&dyn Field>,
+) -> DbResult<(Vec<BTreePageId>, BTreePageId)> {
+    let root_ptr = BTreePageId::root_ptr(file.get_id());
+    let mut pid = get_root_id(&pool.get_page(tid, file, &root_ptr, Permissions::ReadOnly)?);
+    let mut path = Vec::new();
+    while pid.get_category() == PageCategory::Internal {
+        let page = pool.get_page(tid, file, &pid, Permissions::ReadOnly)?;
+        let page = page.read().unwrap();
+        let internal = page.as_any().downcast_ref::<BTreeInternalPage>().unwrap();
+        let child = internal.find_child(key);
+        path.push(pid);
+        pid = child;
+    }
+    Ok((path, pid))
+}
+
+/**
+ * BTreeFile is an implementation of a DbFile that stores a B+ tree of
+ * tuples, sorted on a single key field of any type.
+ *
+ * The first page of the file points at the root of the tree. Internal
+ * pages hold keys separating their children, and leaf pages hold the
+ * tuples, chained to their siblings so ranges are read leaf after leaf.
+ * A full page is split in two when a tuple is inserted, and a page less
+ * than half full after a delete takes entries from a sibling, or is merged
+ * into it when the sibling has none to spare.
+ *
+ * Pages do not point at their parent, so inserts and deletes remember the
+ * internal pages they went through on the way down. Pages emptied by
+ * merges are not reused.
+ */
+pub struct BTreeFile {
+    file: PageFile,
+    td: TupleDesc,
+    key_field: usize,
+    id: u64,
+}
+
+impl BTreeFile {
+    /**
+     * Opens the B+ tree file at `path`, keyed on field `key_field` of `td`.
+     * A missing or empty file is set up as an empty tree.
+     */
+    pub fn new<P: AsRef<Path>>(path: P, td: TupleDesc, key_field: usize) -> DbResult<Self> {
+        if key_field >= td.num_fields() {
+            return Err(DbError::NoSuchElement(format!("field {}", key_field)));
+        }
+        let file = PageFile::open(path)?;
+        if file.num_pages() == 0 {
+            file.append_page(&BTreeRootPtrPage::create_page_data())?;
+            file.append_page(&BTreeLeafPage::create_empty_page_data())?;
+        }
+        Ok(Self {
+            id: file.get_id(),
+            file,
+            td,
+            key_field,
+        })
+    }
+
+    /// Returns the absolute path of the file backing this BTreeFile.
+    pub fn get_path(&self) -> &Path {
+        self.file.get_path()
+    }
+
+    fn page_from_data(&self, pid: BTreePageId, data: &[u8]) -> DbResult<Box<dyn Page>> {
+        if pid.get_table_id() != self.id {
+            return Err(DbError::Db("page does not belong to this file".to_string()));
+        }
+        Ok(match pid.get_category() {
+            PageCategory::RootPtr => Box::new(BTreeRootPtrPage::new(pid, data)?),
+            PageCategory::Internal => {
+                let key_type = self.td.get_field_type(self.key_field).unwrap();
+                Box::new(BTreeInternalPage::new(pid, data, key_type)?)
+            }
+            PageCategory::Leaf => Box::new(BTreeLeafPage::new(
+                pid,
+                data,
+                self.td.clone(),
+                self.key_field,
+            )?),
+        })
+    }
+
+    /**
+     * Appends a zeroed page to the file and fetches it for writing, as a
+     * page of the given category.
+     */
+    fn get_empty_page(
+        &self,
+        access: &mut PageAccess,
+        category: PageCategory,
+    ) -> DbResult<(BTreePageId, PageRef)> {
+        let pgno = self.file.append_page(&[0; PAGE_SIZE])?;
+        let pid = BTreePageId::new(self.id, pgno, category);
+        let page = access.get_page(self, &pid, Permissions::ReadWrite)?;
+        Ok((pid, page))
+    }
+
+    fn set_root(&self, access: &mut PageAccess, root: BTreePageId) -> DbResult<()> {
+        let root_ptr = BTreePageId::root_ptr(self.id);
+        let page = access.get_page(self, &root_ptr, Permissions::ReadWrite)?;
+        as_root_ptr(&mut page.write().unwrap()).set_root_id(root);
+        Ok(())
+    }
+
+    /**
+     * Splits a full leaf, moving the upper half of its tuples to a new
+     * leaf to its right, and inserts `t` into the half it belongs to.
+     */
+    fn split_leaf_page(
+        &self,
+        access: &mut PageAccess,
+        path: &mut Vec<BTreePageId>,
+        pid: BTreePageId,
+        t: Tuple,
+    ) -> DbResult<()> {
+        let page = access.get_page(self, &pid, Permissions::ReadWrite)?;
+        let (new_pid, new_page) = self.get_empty_page(access, PageCategory::Leaf)?;
+        let (mid_key, right_sibling) = {
+            let mut guard = page.write().unwrap();
+            let leaf = as_leaf(&mut guard);
+            let mut new_guard = new_page.write().unwrap();
+            let new_leaf = as_leaf(&mut new_guard);
+            let n = leaf.num_tuples();
+            new_leaf.add_last(leaf.remove_last(n - n / 2));
+            new_leaf.set_left_sibling_id(Some(pid));
+            new_leaf.set_right_sibling_id(leaf.get_right_sibling_id());
+            leaf.set_right_sibling_id(Some(new_pid));
+
+            let mid_key = new_leaf.first_key().unwrap().clone_box();
+            if t.get_field(self.key_field).unwrap() >= mid_key.as_ref() {
+                new_leaf.insert_tuple(t)?;
+            } else {
+                leaf.insert_tuple(t)?;
+            }
+            (mid_key, new_leaf.get_right_sibling_id())
+        };
+        if let Some(right) = right_sibling {
+            let page = access.get_page(self, &right, Permissions::ReadWrite)?;
+            as_leaf(&mut page.write().unwrap()).set_left_sibling_id(Some(new_pid));
+        }
+        self.insert_into_parent(access, path, pid, mid_key, new_pid)
+    }
+
+    /**
+     * Adds `key` to the parent of `left`, the last page of `path`, with
+     * `right` as the child to its right. A full parent is split first, and
+     * splitting the root grows the tree by one level.
+     */
+    fn insert_into_parent(
+        &self,
+        access: &mut PageAccess,
+        path: &mut Vec<BTreePageId>,
+        left: BTreePageId,
+        key: Box<dyn Field>,
+        right: BTreePageId,
+    ) -> DbResult<()> {
+        let parent_pid = match path.pop() {
+            Some(pid) => pid,
+            None => {
+                let (root_pid, root) = self.get_empty_page(access, PageCategory::Internal)?;
+                {
+                    let mut guard = root.write().unwrap();
+                    let root = as_internal(&mut guard);
+                    root.set_child_category(left.get_category());
+                    root.insert_entry(key, &left, &right)?;
+                }
+                return self.set_root(access, root_pid);
+            }
+        };
+
+        let page = access.get_page(self, &parent_pid, Permissions::ReadWrite)?;
+        let mut guard = page.write().unwrap();
+        let parent = as_internal(&mut guard);
+        if parent.get_num_empty_slots() > 0 {
+            return parent.insert_entry(key, &left, &right);
+        }
+        drop(guard);
+
+        let (new_pid, new_page) = self.get_empty_page(access, PageCategory::Internal)?;
+        let mid_key = {
+            let mut guard = page.write().unwrap();
+            let parent = as_internal(&mut guard);
+            let mut new_guard = new_page.write().unwrap();
+            let new_parent = as_internal(&mut new_guard);
+            let (mid_key, keys, children) = parent.split_off();
+            new_parent.set_child_category(parent.get_child_category());
+            new_parent.append_entries(None, keys, children);
+            if new_parent.child_index(&left).is_some() {
+                new_parent.insert_entry(key, &left, &right)?;
+            } else {
+                parent.insert_entry(key, &left, &right)?;
+            }
+            mid_key
+        };
+        self.insert_into_parent(access, path, parent_pid, mid_key, new_pid)
+    }
+
+    /**
+     * Finds the leaf holding a tuple equal to `t`, searching every subtree
+     * whose key range includes the key of `t`. Returns the internal pages
+     * on the way, from the root down, and the leaf.
+     */
+    fn find_tuple(
+        &self,
+        access: &mut PageAccess,
+        pid: BTreePageId,
+        path: &mut Vec<BTreePageId>,
+        t: &Tuple,
+    ) -> DbResult<Option<BTreePageId>> {
+        let key = t.get_field(self.key_field).unwrap();
+        let page = access.get_page(self, &pid, Permissions::ReadOnly)?;
+        let candidates = {
+            let page = page.read().unwrap();
+            if pid.get_category() == PageCategory::Leaf {
+                let leaf = page.as_any().downcast_ref::<BTreeLeafPage>().unwrap();
+                let found = leaf
+                    .iterator()
+                    .any(|other| other.get_fields().eq(t.get_fields()));
+                return Ok(if found { Some(pid) } else { None });
+            }
+            let internal = page.as_any().downcast_ref::<BTreeInternalPage>().unwrap();
+            let first = internal
+                .child_index(&internal.find_child(Some(key)))
+                .unwrap();
+            let mut last = first;
+            while internal.get_key(last) == Some(key) {
+                last += 1;
+            }
+            internal.children()[first..=last].to_vec()
+        };
+        path.push(pid);
+        for child in candidates {
+            if let Some(leaf) = self.find_tuple(access, child, path, t)? {
+                return Ok(Some(leaf));
+            }
+        }
+        path.pop();
+        Ok(None)
+    }
+
+    /**
+     * Restores the occupancy of page `pid`, the child of the last page of
+     * `path`, if it is less than half full: it takes entries from a
+     * sibling that has more than half, or else is merged with it. A merge
+     * removes an entry from the parent, which may in turn need rebalancing.
+     * A root left with a single child is replaced by that child.
+     */
+    fn rebalance(
+        &self,
+        access: &mut PageAccess,
+        path: &mut Vec<BTreePageId>,
+        pid: BTreePageId,
+    ) -> DbResult<()> {
+        let page = access.get_page(self, &pid, Permissions::ReadWrite)?;
+        let parent_pid = match path.last() {
+            Some(&parent_pid) => parent_pid,
+            None => {
+                let mut guard = page.write().unwrap();
+                if pid.get_category() == PageCategory::Internal {
+                    let root = as_internal(&mut guard);
+                    if root.num_keys() == 0 {
+                        let child = root.get_child_id(0).unwrap();
+                        drop(guard);
+                        return self.set_root(access, child);
+                    }
+                }
+                return Ok(());
+            }
+        };
+        let underfull = {
+            let mut guard = page.write().unwrap();
+            match pid.get_category() {
+                PageCategory::Leaf => {
+                    let leaf = as_leaf(&mut guard);
+                    leaf.num_tuples() < leaf.get_min_tuples()
+                }
+                _ => {
+                    let internal = as_internal(&mut guard);
+                    internal.num_keys() < internal.get_min_keys()
+                }
+            }
+        };
+        if !underfull {
+            return Ok(());
+        }
+
+        let parent = access.get_page(self, &parent_pid, Permissions::ReadWrite)?;
+        let (i, left, right) = {
+            let mut guard = parent.write().unwrap();
+            let parent = as_internal(&mut guard);
+            let i = parent.child_index(&pid).unwrap();
+            let left = if i > 0 {
+                parent.get_child_id(i - 1)
+            } else {
+                None
+            };
+            (i, left, parent.get_child_id(i + 1))
+        };
+        // Entries always move from the left page of the pair to the right
+        // one or back, and `key` is the index of the key separating them.
+        let (left_pid, right_pid, key) = match left {
+            Some(left) => (left, pid, i - 1),
+            None => (pid, right.unwrap(), i),
+        };
+        let merged = match pid.get_category() {
+            PageCategory::Leaf => self.balance_leaves(access, &parent, left_pid, right_pid, key)?,
+            _ => self.balance_internal_pages(access, &parent, left_pid, right_pid, key)?,
+        };
+        if merged {
+            path.pop();
+            self.rebalance(access, path, parent_pid)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Evens out two sibling leaves, or merges the right one into the left
+     * one if they fit on a single page. Returns true if they were merged.
+     */
+    fn balance_leaves(
+        &self,
+        access: &mut PageAccess,
+        parent: &PageRef,
+        left_pid: BTreePageId,
+        right_pid: BTreePageId,
+        key: usize,
+    ) -> DbResult<bool> {
+        let left = access.get_page(self, &left_pid, Permissions::ReadWrite)?;
+        let right = access.get_page(self, &right_pid, Permissions::ReadWrite)?;
+        let mut left_guard = left.write().unwrap();
+        let left_leaf = as_leaf(&mut left_guard);
+        let mut right_guard = right.write().unwrap();
+        let right_leaf = as_leaf(&mut right_guard);
+        let mut parent_guard = parent.write().unwrap();
+        let parent = as_internal(&mut parent_guard);
+
+        let (n_left, n_right) = (left_leaf.num_tuples(), right_leaf.num_tuples());
+        if n_left + n_right >= 2 * left_leaf.get_min_tuples() {
+            if n_left > n_right {
+                right_leaf.add_first(left_leaf.remove_last((n_left - n_right) / 2));
+            } else {
+                left_leaf.add_last(right_leaf.remove_first((n_right - n_left) / 2));
+            }
+            parent.set_key(key, right_leaf.first_key().unwrap().clone_box());
+            return Ok(false);
+        }
+
+        left_leaf.add_last(right_leaf.remove_first(n_right));
+        let next = right_leaf.get_right_sibling_id();
+        left_leaf.set_right_sibling_id(next);
+        right_leaf.set_left_sibling_id(None);
+        right_leaf.set_right_sibling_id(None);
+        parent.delete_entry(key);
+        drop((left_guard, right_guard, parent_guard));
+        if let Some(next) = next {
+            let page = access.get_page(self, &next, Permissions::ReadWrite)?;
+            as_leaf(&mut page.write().unwrap()).set_left_sibling_id(Some(left_pid));
+        }
+        Ok(true)
+    }
+
+    /**
+     * Evens out two sibling internal pages by rotating entries through
+     * their parent, or merges the right one into the left one if they fit
+     * on a single page. Returns true if they were merged.
+     */
+    fn balance_internal_pages(
+        &self,
+        access: &mut PageAccess,
+        parent: &PageRef,
+        left_pid: BTreePageId,
+        right_pid: BTreePageId,
+        key: usize,
+    ) -> DbResult<bool> {
+        let left = access.get_page(self, &left_pid, Permissions::ReadWrite)?;
+        let right = access.get_page(self, &right_pid, Permissions::ReadWrite)?;
+        let mut left_guard = left.write().unwrap();
+        let left_page = as_internal(&mut left_guard);
+        let mut right_guard = right.write().unwrap();
+        let right_page = as_internal(&mut right_guard);
+        let mut parent_guard = parent.write().unwrap();
+        let parent = as_internal(&mut parent_guard);
+
+        let (n_left, n_right) = (left_page.num_keys(), right_page.num_keys());
+        if n_left + n_right >= 2 * left_page.get_min_keys() {
+            let separator = |parent: &BTreeInternalPage| parent.get_key(key).unwrap().clone_box();
+            if n_left > n_right {
+                for _ in 0..(n_left - n_right) / 2 {
+                    let (moved_key, child) = left_page.pop_last();
+                    right_page.push_first(separator(parent), &child);
+                    parent.set_key(key, moved_key);
+                }
+            } else {
+                for _ in 0..(n_right - n_left) / 2 {
+                    let (moved_key, child) = right_page.pop_first();
+                    left_page.push_last(separator(parent), &child);
+                    parent.set_key(key, moved_key);
+                }
+            }
+            return Ok(false);
+        }
+
+        let (keys, children) = right_page.take_entries();
+        let separator = parent.get_key(key).unwrap().clone_box();
+        left_page.append_entries(Some(separator), keys, children);
+        parent.delete_entry(key);
+        Ok(true)
+    }
+}
+
+impl DbFile for BTreeFile {
+    fn read_page(&self, pid: &dyn PageId) -> DbResult<Box<dyn Page>> {
+        let pid = BTreePageId::deserialize(&pid.serialize())?;
+        let data = self.file.read_page(pid.get_page_number())?;
+        self.page_from_data(pid, &data)
+    }
+
+    fn page_from_image(&self, pid: &[i32], data: &[u8]) -> DbResult<Box<dyn Page>> {
+        self.page_from_data(BTreePageId::deserialize(pid)?, data)
+    }
+
+    /// New pages are appended by the tree itself, so only existing pages can be written.
+    fn write_page(&self, page: &dyn Page) -> DbResult<()> {
+        let pgno = page.get_id().get_page_number();
+        if pgno < 0 || pgno as usize >= self.num_pages() {
+            return Err(DbError::Db(format!("cannot write page {}", pgno)));
+        }
+        self.file.write_page(pgno, &page.get_page_data())
+    }
+
+    fn num_pages(&self) -> usize {
+        self.file.num_pages()
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /// The tuple goes into the leaf its key belongs to, splitting it if it is full.
+    fn insert_tuple(
+        &self,
+        pool: &BufferPool,
+        tid: TransactionId,
+        t: Tuple,
+    ) -> DbResult<Vec<PageRef>> {
+        if *t.get_tuple_desc() != self.td {
+            return Err(DbError::Db("tuple desc mismatch".to_string()));
+        }
+        let mut access = PageAccess::new(pool, tid);
+        let (mut path, pid) = find_leaf(pool, tid, self, t.get_field(self.key_field))?;
+        let page = access.get_page(self, &pid, Permissions::ReadWrite)?;
+        let mut guard = page.write().unwrap();
+        let leaf = as_leaf(&mut guard);
+        if leaf.get_num_empty_slots() > 0 {
+            leaf.insert_tuple(t)?;
+        } else {
+            drop(guard);
+            self.split_leaf_page(&mut access, &mut path, pid, t)?;
+        }
+        Ok(access.into_dirty_pages())
+    }
+
+    /**
+     * The tuple is looked up by value in the leaves its key may be on, and
+     * its leaf is rebalanced if it becomes less than half full.
+     */
+    fn delete_tuple(
+        &self,
+        pool: &BufferPool,
+        tid: TransactionId,
+        t: &Tuple,
+    ) -> DbResult<Vec<PageRef>> {
+        let mut access = PageAccess::new(pool, tid);
+        let root_ptr = BTreePageId::root_ptr(self.id);
+        let root = get_root_id(&access.get_page(self, &root_ptr, Permissions::ReadOnly)?);
+        let mut path = Vec::new();
+        let pid = self
+            .find_tuple(&mut access, root, &mut path, t)?
+            .ok_or_else(|| DbError::Db("tuple is not in this file".to_string()))?;
+        let page = access.get_page(self, &pid, Permissions::ReadWrite)?;
+        as_leaf(&mut page.write().unwrap()).delete_tuple(t)?;
+        self.rebalance(&mut access, &mut path, pid)?;
+        Ok(access.into_dirty_pages())
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+
+    /// Tuples are returned in key order.
+    fn iterator(&self, db: Arc<Database>, tid: TransactionId) -> DbFileIterator {
+        Box::new(BTreeFileIterator::new(
+            db,
+            tid,
+            self.id,
+            self.key_field,
+            None,
+        ))
+    }
+
+    fn get_key_field(&self) -> Option<usize> {
+        Some(self.key_field)
+    }
+
+    /**
+     * Lookups with `=`, `>` and `>=` start at the first leaf that may hold a
+     * match, and lookups with `=`, `<` and `<=` stop at the first key past
+     * the range.
+     */
+    fn index_iterator(
+        &self,
+        db: Arc<Database>,
+        tid: TransactionId,
+        pred: IndexPredicate,
+    ) -> DbResult<DbFileIterator> {
+        Ok(Box::new(BTreeFileIterator::new(
+            db,
+            tid,
+            self.id,
+            self.key_field,
+            Some(pred),
+        )))
+    }
+}
+
+/**
+ * Iterates over the tuples of a BTreeFile in key order, following the
+ * leaves from left to right and fetching one leaf at a time from the
+ * BufferPool. With a predicate, only the matching tuples are returned.
+ */
+struct BTreeFileIterator {
+    db: Arc<Database>,
+    tid: TransactionId,
+    table_id: u64,
+    key_field: usize,
+    pred: Option<IndexPredicate>,
+    started: bool,
+    next_leaf: Option<BTreePageId>,
+    tuples: IntoIter<Tuple>,
+}
+
+impl BTreeFileIterator {
+    fn new(
+        db: Arc<Database>,
+        tid: TransactionId,
+        table_id: u64,
+        key_field: usize,
+        pred: Option<IndexPredicate>,
+    ) -> Self {
+        Self {
+            db,
+            tid,
+            table_id,
+            key_field,
+            pred,
+            started: false,
+            next_leaf: None,
+            tuples: Vec::new().into_iter(),
+        }
+    }
+
+    fn read_next_page(&mut self) -> DbResult<bool> {
+        let file = self.db.get_catalog().get_database_file(self.table_id)?;
+        let pool = self.db.get_buffer_pool();
+        if !self.started {
+            self.started = true;
+            let key = self.pred.as_ref().and_then(|pred| match pred.get_op() {
+                Op::Equals | Op::GreaterThan | Op::GreaterThanOrEq => Some(pred.get_field()),
+                _ => None,
+            });
+            self.next_leaf = Some(find_leaf(pool, self.tid, file.as_ref(), key)?.1);
+        }
+        let pid = match self.next_leaf {
+            Some(pid) => pid,
+            None => return Ok(false),
+        };
+        let page = pool.get_page(self.tid, file.as_ref(), &pid, Permissions::ReadOnly)?;
+        let page = page.read().unwrap();
+        let leaf = page.as_any().downcast_ref::<BTreeLeafPage>().unwrap();
+        self.tuples = leaf.iterator().cloned().collect::<Vec<Tuple>>().into_iter();
+        self.next_leaf = leaf.get_right_sibling_id();
+        Ok(true)
+    }
+}
+
+impl Iterator for BTreeFileIterator {
+    type Item = DbResult<Tuple>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(t) = self.tuples.next() {
+                let pred = match &self.pred {
+                    Some(pred) => pred,
+                    None => return Some(Ok(t)),
+                };
+                let key = t.get_field(self.key_field).unwrap();
+                if pred.is_past(key) {
+                    self.next_leaf = None;
+                    self.tuples = Vec::new().into_iter();
+                    return None;
+                }
+                if pred.matches(key) {
+                    return Some(Ok(t));
+                }
+                continue;
+            }
+            match self.read_next_page() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::string_field::StringField;
+    use crate::common::ty::{Type, STRING_LEN};
+    use crate::downcast;
+    use crate::storage::eviction::LruPolicy;
+    use crate::test_util::TempDir;
+    use crate::transaction::lock_manager::LockManager;
+    use std::time::Duration;
+
+    /// String keys take 132 bytes, so leaves hold 30 tuples and internal pages 30 keys.
+    fn get_td() -> TupleDesc {
+        TupleDesc::new(vec![Type::Str, Type::Int], vec!["key", "value"])
+    }
+
+    fn get_tuple(key: &str, value: i32) -> Tuple {
+        let mut t = Tuple::new(get_td());
+        t.set_field(
+            0,
+            Box::new(StringField::new(key.to_string(), STRING_LEN as usize)),
+        );
+        t.set_field(1, Box::new(IntField::new(value)));
+        t
+    }
+
+    fn values(tuples: &[Tuple]) -> Vec<i32> {
+        tuples
+            .iter()
+            .map(|t| downcast!(t.get_field(1).unwrap(), IntField).get_value())
+            .collect()
+    }
+
+    fn create_tree(dir: &TempDir) -> (Arc<Database>, Arc<dyn DbFile>) {
+        let db = Arc::new(Database::new(500));
+        let f = BTreeFile::new(dir.path().join("t.dat"), get_td(), 0).unwrap();
+        let file: Arc<dyn DbFile> = Arc::new(f);
+        db.get_catalog().add_table(file.clone(), "t", None);
+        (db, file)
+    }
+
+    /// Inserts tuples with keys `k0000`, `k0001`, ... in a scrambled order.
+    fn insert_all(db: &Database, tid: TransactionId, file: &Arc<dyn DbFile>, n: i32) {
+        for i in 0..n {
+            let v = (i * 7919) % n;
+            db.get_buffer_pool()
+                .insert_tuple(tid, file, get_tuple(&format!("k{:04}", v), v))
+                .unwrap();
+        }
+    }
+
+    /// A bound on the keys of a subtree; None if unbounded.
+    type Bound = Option<Box<dyn Field>>;
+
+    /**
+     * Checks the invariants of the subtree at `pid`, whose keys must lie
+     * between `lower` and `upper`, and collects its leaves in order.
+     * Returns the depth of the subtree.
+     */
+    fn check_subtree(
+        db: &Database,
+        tid: TransactionId,
+        file: &dyn DbFile,
+        pid: BTreePageId,
+        bounds: (Bound, Bound),
+        is_root: bool,
+        leaves: &mut Vec<BTreePageId>,
+    ) -> usize {
+        let page = db
+            .get_buffer_pool()
+            .get_page(tid, file, &pid, Permissions::ReadOnly)
+            .unwrap();
+        let page = page.read().unwrap();
+        let (lower, upper) = bounds;
+        let in_bounds = |key: &dyn Field| {
+            lower.as_ref().is_none_or(|l| l.as_ref() <= key)
+                && upper.as_ref().is_none_or(|u| key <= u.as_ref())
+        };
+        if pid.get_category() == PageCategory::Leaf {
+            let leaf = page.as_any().downcast_ref::<BTreeLeafPage>().unwrap();
+            assert!(is_root || leaf.num_tuples() >= leaf.get_min_tuples());
+            let keys: Vec<&dyn Field> = leaf.iterator().map(|t| t.get_field(0).unwrap()).collect();
+            assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+            assert!(keys.iter().all(|&k| in_bounds(k)));
+            leaves.push(pid);
+            return 1;
+        }
+
+        let internal = page.as_any().downcast_ref::<BTreeInternalPage>().unwrap();
+        assert!(internal.num_keys() >= if is_root { 1 } else { internal.get_min_keys() });
+        let children = internal.children();
+        let mut depths = Vec::new();
+        for (i, child) in children.iter().enumerate() {
+            let lower = if i == 0 {
+                lower.clone()
+            } else {
+                Some(internal.get_key(i - 1).unwrap().clone_box())
+            };
+            let upper = match internal.get_key(i) {
+                Some(key) => Some(key.clone_box()),
+                None => upper.clone(),
+            };
+            depths.push(check_subtree(
+                db,
+                tid,
+                file,
+                *child,
+                (lower, upper),
+                false,
+                leaves,
+            ));
+        }
+        assert!(depths.iter().all(|&d| d == depths[0]));
+        depths[0] + 1
+    }
+
+    /// Checks the whole tree and the sibling links; returns its depth.
+    fn check_tree(db: &Database, tid: TransactionId, file: &dyn DbFile) -> usize {
+        let pool = db.get_buffer_pool();
+        let root_ptr = BTreePageId::root_ptr(file.get_id());
+        let root = get_root_id(
+            &pool
+                .get_page(tid, file, &root_ptr, Permissions::ReadOnly)
+                .unwrap(),
+        );
+        let mut leaves = Vec::new();
+        let depth = check_subtree(db, tid, file, root, (None, None), true, &mut leaves);
+        for (i, pid) in leaves.iter().enumerate() {
+            let page = pool
+                .get_page(tid, file, pid, Permissions::ReadOnly)
+                .unwrap();
+            let page = page.read().unwrap();
+            let leaf = page.as_any().downcast_ref::<BTreeLeafPage>().unwrap();
+            let left = if i > 0 { Some(leaves[i - 1]) } else { None };
+            assert_eq!(left, leaf.get_left_sibling_id());
+            assert_eq!(leaves.get(i + 1).copied(), leaf.get_right_sibling_id());
+        }
+        depth
+    }
+
+    fn scan(db: &Arc<Database>, tid: TransactionId, file: &Arc<dyn DbFile>) -> Vec<Tuple> {
+        file.iterator(db.clone(), tid).map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_insert_splits() {
+        let dir = TempDir::new("btree_insert");
+        let (db, file) = create_tree(&dir);
+        let tid = TransactionId::new();
+        assert_eq!(1, check_tree(&db, tid, file.as_ref()));
+        insert_all(&db, tid, &file, 2000);
+
+        // 2000 tuples need more leaves than a single internal page can hold
+        assert_eq!(3, check_tree(&db, tid, file.as_ref()));
+        assert_eq!(
+            (0..2000).collect::<Vec<i32>>(),
+            values(&scan(&db, tid, &file))
+        );
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+
+        // the tree is read back from disk
+        let db = Arc::new(Database::new(500));
+        let f = BTreeFile::new(dir.path().join("t.dat"), get_td(), 0).unwrap();
+        let file: Arc<dyn DbFile> = Arc::new(f);
+        db.get_catalog().add_table(file.clone(), "t", None);
+        let tid = TransactionId::new();
+        assert_eq!(3, check_tree(&db, tid, file.as_ref()));
+        assert_eq!(2000, scan(&db, tid, &file).len());
+    }
+
+    #[test]
+    fn test_delete_merges() {
+        let dir = TempDir::new("btree_delete");
+        let (db, file) = create_tree(&dir);
+        let pool = db.get_buffer_pool();
+        let tid = TransactionId::new();
+        insert_all(&db, tid, &file, 1000);
+        assert_eq!(3, check_tree(&db, tid, file.as_ref()));
+
+        // delete from both ends and the middle, so pages borrow and merge
+        let tuples = scan(&db, tid, &file);
+        for (i, t) in tuples.iter().enumerate() {
+            if i % 10 != 0 {
+                pool.delete_tuple(tid, &file, t).unwrap();
+            }
+        }
+        assert_eq!(2, check_tree(&db, tid, file.as_ref()));
+        let remaining = values(&scan(&db, tid, &file));
+        assert_eq!((0..100).map(|i| i * 10).collect::<Vec<i32>>(), remaining);
+        assert!(pool.delete_tuple(tid, &file, &tuples[1]).is_err());
+
+        for t in scan(&db, tid, &file) {
+            pool.delete_tuple(tid, &file, &t).unwrap();
+        }
+        assert_eq!(1, check_tree(&db, tid, file.as_ref()));
+        assert!(scan(&db, tid, &file).is_empty());
+    }
+
+    #[test]
+    fn test_index_iterator() {
+        let dir = TempDir::new("btree_index");
+        let (db, file) = create_tree(&dir);
+        let pool = db.get_buffer_pool();
+        let tid = TransactionId::new();
+        // 100 tuples for each of 20 keys, spanning several leaves each
+        for i in 0..2000 {
+            let t = get_tuple(&format!("k{:02}", (i * 7) % 20), i);
+            pool.insert_tuple(tid, &file, t).unwrap();
+        }
+        check_tree(&db, tid, file.as_ref());
+
+        let f = BTreeFile::new(dir.path().join("t.dat"), get_td(), 0).unwrap();
+        let count = |op: Op, key: &str| {
+            let key = Box::new(StringField::new(key.to_string(), STRING_LEN as usize));
+            f.index_iterator(db.clone(), tid, IndexPredicate::new(op, key))
+                .unwrap()
+                .collect::<DbResult<Vec<Tuple>>>()
+                .unwrap()
+                .len()
+        };
+        assert_eq!(100, count(Op::Equals, "k05"));
+        assert_eq!(0, count(Op::Equals, "k055"));
+        assert_eq!(1400, count(Op::GreaterThan, "k05"));
+        assert_eq!(1500, count(Op::GreaterThanOrEq, "k05"));
+        assert_eq!(500, count(Op::LessThan, "k05"));
+        assert_eq!(600, count(Op::LessThanOrEq, "k05"));
+        assert_eq!(1900, count(Op::NotEquals, "k05"));
+
+        // duplicates are found wherever they are
+        let key = Box::new(StringField::new("k07".to_string(), STRING_LEN as usize));
+        let pred = IndexPredicate::new(Op::Equals, key);
+        for t in f.index_iterator(db.clone(), tid, pred).unwrap() {
+            pool.delete_tuple(tid, &file, &t.unwrap()).unwrap();
+        }
+        assert_eq!(0, count(Op::Equals, "k07"));
+        assert_eq!(1900, scan(&db, tid, &file).len());
+        check_tree(&db, tid, file.as_ref());
+    }
+
+    #[test]
+    fn test_abort() {
+        let dir = TempDir::new("btree_abort");
+        let (db, file) = create_tree(&dir);
+        let pool = db.get_buffer_pool();
+        let t1 = TransactionId::new();
+        insert_all(&db, t1, &file, 100);
+        pool.transaction_complete(t1, true).unwrap();
+
+        let t2 = TransactionId::new();
+        insert_all(&db, t2, &file, 500);
+        assert_eq!(600, scan(&db, t2, &file).len());
+        pool.transaction_complete(t2, false).unwrap();
+
+        let t3 = TransactionId::new();
+        assert_eq!(100, scan(&db, t3, &file).len());
+        assert_eq!(2, check_tree(&db, t3, file.as_ref()));
+    }
+
+    #[test]
+    fn test_abort_during_split() {
+        let dir = TempDir::new("btree_abort_split");
+        let (db, file) = create_tree(&dir);
+        let tid = TransactionId::new();
+        insert_all(&db, tid, &file, 30);
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+
+        // a pool that has not modified the tree yet, with a lock timeout
+        let lock_manager = LockManager::with_timeout(Duration::from_millis(50));
+        let pool = BufferPool::with_lock_manager(500, Box::new(LruPolicy::new()), lock_manager);
+        let root_ptr = BTreePageId::root_ptr(file.get_id());
+        let reader = TransactionId::new();
+        pool.get_page(reader, file.as_ref(), &root_ptr, Permissions::ReadOnly)
+            .unwrap();
+
+        // the full root leaf is split, but the new root cannot be recorded
+        let writer = TransactionId::new();
+        let result = pool.insert_tuple(writer, &file, get_tuple("k0030", 30));
+        assert!(matches!(result, Err(DbError::TransactionAborted(_))));
+        pool.transaction_complete(writer, false).unwrap();
+        pool.transaction_complete(reader, true).unwrap();
+
+        // the split starts over from the restored pages
+        let tid = TransactionId::new();
+        pool.insert_tuple(tid, &file, get_tuple("k0030", 30))
+            .unwrap();
+        pool.transaction_complete(tid, true).unwrap();
+
+        let db = Arc::new(Database::new(500));
+        let f = BTreeFile::new(dir.path().join("t.dat"), get_td(), 0).unwrap();
+        let file: Arc<dyn DbFile> = Arc::new(f);
+        db.get_catalog().add_table(file.clone(), "t", None);
+        let tid = TransactionId::new();
+        assert_eq!(2, check_tree(&db, tid, file.as_ref()));
+        assert_eq!(
+            (0..31).collect::<Vec<i32>>(),
+            values(&scan(&db, tid, &file))
+        );
+    }
+}
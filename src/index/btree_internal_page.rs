@@ -0,0 +1,369 @@
+use super::btree_page_id::{BTreePageId, PageCategory};
+use crate::common::error::{DbError, DbResult};
+use crate::common::field::Field;
+use crate::common::page_id::PageId;
+use crate::common::ty::Type;
+use crate::downcast::Downcast;
+use crate::storage::page::{Page, PAGE_SIZE};
+use crate::transaction::transaction_id::TransactionId;
+use downcast_macro_derive::Downcast;
+use std::any::Any;
+use std::convert::TryInto;
+
+/// Bytes taken by the child category, the key count and the first child.
+const HEADER_SIZE: usize = 12;
+
+/**
+ * Each instance of BTreeInternalPage stores data for one internal page of
+ * a BTreeFile: `n` keys separating `n + 1` children. All keys in the
+ * subtree of child `i` are lower than or equal to key `i`, and all keys in
+ * the subtree of child `i + 1` are greater than or equal to it. All
+ * children of a page are of the same category.
+ *
+ * The page starts with the category of its children and the number of
+ * keys, followed by the page number of the first child, then each key
+ * followed by the page number of the child to its right.
+ */
+#[derive(Downcast)]
+pub struct BTreeInternalPage {
+    pid: BTreePageId,
+    key_type: Type,
+    child_category: PageCategory,
+    keys: Vec<Box<dyn Field>>,
+    children: Vec<i32>,
+    dirty: Option<TransactionId>,
+    old_data: Vec<u8>,
+}
+
+impl BTreeInternalPage {
+    /// Create a BTreeInternalPage from a set of bytes of data read from disk.
+    pub fn new(pid: BTreePageId, data: &[u8], key_type: Type) -> DbResult<Self> {
+        let corrupt = || DbError::Db(format!("corrupt internal page {}", pid.get_page_number()));
+        let read = |i: usize| i32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        let child_category = PageCategory::get_category(read(0)).ok_or_else(corrupt)?;
+        let num_keys = read(4);
+        if num_keys < 0 || num_keys as usize > Self::compute_max_keys(key_type) {
+            return Err(corrupt());
+        }
+
+        let mut keys = Vec::with_capacity(num_keys as usize);
+        let mut children = Vec::with_capacity(num_keys as usize + 1);
+        if num_keys > 0 {
+            children.push(read(8));
+            let mut input = &data[HEADER_SIZE..];
+            for _ in 0..num_keys {
+                keys.push(key_type.parse(&mut input)?);
+                let mut child = [0; 4];
+                child.copy_from_slice(&input[..4]);
+                input = &input[4..];
+                children.push(i32::from_be_bytes(child));
+            }
+        }
+        Ok(Self {
+            pid,
+            key_type,
+            child_category,
+            keys,
+            children,
+            dirty: None,
+            old_data: data.to_vec(),
+        })
+    }
+
+    /// Returns a byte array representing an empty BTreeInternalPage.
+    pub fn create_empty_page_data() -> Vec<u8> {
+        vec![0; PAGE_SIZE]
+    }
+
+    /// Returns the number of keys that fit on a page.
+    pub fn get_max_keys(&self) -> usize {
+        Self::compute_max_keys(self.key_type)
+    }
+
+    /**
+     * Returns the number of keys a page other than the root must hold. A
+     * full page split in two leaves this many keys on its smaller half.
+     */
+    pub fn get_min_keys(&self) -> usize {
+        (self.get_max_keys() - 1) / 2
+    }
+
+    pub fn num_keys(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn get_num_empty_slots(&self) -> usize {
+        self.get_max_keys() - self.num_keys()
+    }
+
+    pub fn get_child_category(&self) -> PageCategory {
+        self.child_category
+    }
+
+    pub fn set_child_category(&mut self, category: PageCategory) {
+        self.child_category = category;
+    }
+
+    pub fn get_key(&self, i: usize) -> Option<&dyn Field> {
+        self.keys.get(i).map(|key| key.as_ref())
+    }
+
+    pub fn set_key(&mut self, i: usize, key: Box<dyn Field>) {
+        self.keys[i] = key;
+    }
+
+    pub fn get_child_id(&self, i: usize) -> Option<BTreePageId> {
+        self.children.get(i).map(|&pgno| self.child_id(pgno))
+    }
+
+    /// Returns the ids of the children of this page, from left to right.
+    pub fn children(&self) -> Vec<BTreePageId> {
+        self.children
+            .iter()
+            .map(|&pgno| self.child_id(pgno))
+            .collect()
+    }
+
+    /// Returns the position of the child `id` among the children of this page.
+    pub fn child_index(&self, id: &BTreePageId) -> Option<usize> {
+        self.children
+            .iter()
+            .position(|&pgno| pgno == id.get_page_number())
+    }
+
+    /**
+     * Returns the leftmost child whose subtree may hold `key`, or the
+     * leftmost child if `key` is None.
+     */
+    pub fn find_child(&self, key: Option<&dyn Field>) -> BTreePageId {
+        let i = match key {
+            Some(key) => self.keys.partition_point(|k| k.as_ref() < key),
+            None => 0,
+        };
+        self.child_id(self.children[i])
+    }
+
+    /**
+     * Inserts `key` right after the child `left`, with `right` as its right
+     * child. On an empty page, `left` becomes the first child.
+     */
+    pub fn insert_entry(
+        &mut self,
+        key: Box<dyn Field>,
+        left: &BTreePageId,
+        right: &BTreePageId,
+    ) -> DbResult<()> {
+        if key.get_type() != self.key_type {
+            return Err(DbError::Db("key type mismatch".to_string()));
+        }
+        if self.get_num_empty_slots() == 0 {
+            return Err(DbError::Db("page is full".to_string()));
+        }
+        if self.children.is_empty() {
+            self.children.push(left.get_page_number());
+        }
+        let i = self
+            .child_index(left)
+            .ok_or_else(|| DbError::Db("child is not on this page".to_string()))?;
+        self.keys.insert(i, key);
+        self.children.insert(i + 1, right.get_page_number());
+        Ok(())
+    }
+
+    /// Removes key `i` and the child to its right.
+    pub fn delete_entry(&mut self, i: usize) {
+        self.keys.remove(i);
+        self.children.remove(i + 1);
+    }
+
+    /**
+     * Keeps the lower half of the entries on this page, and returns the
+     * middle key with the keys and children of the upper half.
+     */
+    #[allow(clippy::type_complexity)]
+    pub fn split_off(&mut self) -> (Box<dyn Field>, Vec<Box<dyn Field>>, Vec<i32>) {
+        let mid = self.keys.len() / 2;
+        let keys = self.keys.split_off(mid + 1);
+        let children = self.children.split_off(mid + 1);
+        (self.keys.pop().unwrap(), keys, children)
+    }
+
+    /// Removes and returns all the keys and children of this page.
+    pub fn take_entries(&mut self) -> (Vec<Box<dyn Field>>, Vec<i32>) {
+        (
+            std::mem::take(&mut self.keys),
+            std::mem::take(&mut self.children),
+        )
+    }
+
+    /**
+     * Adds the keys and children of a page to the right of this one. The
+     * `separator` goes between the last child of this page and the first
+     * new child; it is None when this page is empty.
+     */
+    pub fn append_entries(
+        &mut self,
+        separator: Option<Box<dyn Field>>,
+        keys: Vec<Box<dyn Field>>,
+        children: Vec<i32>,
+    ) {
+        self.keys.extend(separator);
+        self.keys.extend(keys);
+        self.children.extend(children);
+    }
+
+    /// Removes the first child and the key to its right.
+    pub fn pop_first(&mut self) -> (Box<dyn Field>, BTreePageId) {
+        let child = self.children.remove(0);
+        (self.keys.remove(0), self.child_id(child))
+    }
+
+    /// Removes the last child and the key to its left.
+    pub fn pop_last(&mut self) -> (Box<dyn Field>, BTreePageId) {
+        let child = self.children.pop().unwrap();
+        (self.keys.pop().unwrap(), self.child_id(child))
+    }
+
+    /// Adds a first child, with `key` separating it from the old first child.
+    pub fn push_first(&mut self, key: Box<dyn Field>, child: &BTreePageId) {
+        self.keys.insert(0, key);
+        self.children.insert(0, child.get_page_number());
+    }
+
+    /// Adds a last child, with `key` separating it from the old last child.
+    pub fn push_last(&mut self, key: Box<dyn Field>, child: &BTreePageId) {
+        self.keys.push(key);
+        self.children.push(child.get_page_number());
+    }
+
+    fn child_id(&self, pgno: i32) -> BTreePageId {
+        BTreePageId::new(self.pid.get_table_id(), pgno, self.child_category)
+    }
+
+    fn compute_max_keys(key_type: Type) -> usize {
+        (PAGE_SIZE - HEADER_SIZE) / (key_type.len() as usize + 4)
+    }
+}
+
+impl Page for BTreeInternalPage {
+    fn get_id(&self) -> Box<dyn PageId> {
+        Box::new(self.pid)
+    }
+
+    fn get_page_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(PAGE_SIZE);
+        data.extend_from_slice(&self.child_category.to_i32().to_be_bytes());
+        data.extend_from_slice(&(self.keys.len() as i32).to_be_bytes());
+        data.extend_from_slice(&self.children.first().copied().unwrap_or(0).to_be_bytes());
+        for (key, child) in self.keys.iter().zip(self.children.iter().skip(1)) {
+            // Writing into a Vec cannot fail.
+            key.serialize(&mut data).unwrap();
+            data.extend_from_slice(&child.to_be_bytes());
+        }
+        data.resize(PAGE_SIZE, 0);
+        data
+    }
+
+    fn is_dirty(&self) -> Option<TransactionId> {
+        self.dirty
+    }
+
+    fn mark_dirty(&mut self, dirty: bool, tid: TransactionId) {
+        self.dirty = if dirty { Some(tid) } else { None };
+    }
+
+    fn get_before_image(&self) -> Box<dyn Page> {
+        // The old data was produced by a page of the same layout.
+        Box::new(BTreeInternalPage::new(self.pid, &self.old_data, self.key_type).unwrap())
+    }
+
+    fn set_before_image(&mut self) {
+        self.old_data = self.get_page_data();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+
+    fn int(v: i32) -> Box<dyn Field> {
+        Box::new(IntField::new(v))
+    }
+
+    fn leaf(pgno: i32) -> BTreePageId {
+        BTreePageId::new(1, pgno, PageCategory::Leaf)
+    }
+
+    /// Returns a page with keys 10, 20, ... separating leaves 1, 2, ...
+    fn get_page(num_keys: i32) -> BTreeInternalPage {
+        let pid = BTreePageId::new(1, 9, PageCategory::Internal);
+        let data = BTreeInternalPage::create_empty_page_data();
+        let mut page = BTreeInternalPage::new(pid, &data, Type::Int).unwrap();
+        page.set_child_category(PageCategory::Leaf);
+        for i in 1..=num_keys {
+            page.insert_entry(int(i * 10), &leaf(i), &leaf(i + 1))
+                .unwrap();
+        }
+        page
+    }
+
+    fn keys(page: &BTreeInternalPage) -> Vec<String> {
+        (0..page.num_keys())
+            .map(|i| page.get_key(i).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_child() {
+        let page = get_page(3);
+        assert_eq!(leaf(1), page.find_child(None));
+        assert_eq!(leaf(1), page.find_child(Some(int(10).as_ref())));
+        assert_eq!(leaf(2), page.find_child(Some(int(11).as_ref())));
+        assert_eq!(leaf(4), page.find_child(Some(int(31).as_ref())));
+        assert_eq!(Some(2), page.child_index(&leaf(3)));
+        assert_eq!(510, page.get_max_keys());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut page = get_page(3);
+        // an entry in the middle
+        page.insert_entry(int(15), &leaf(2), &leaf(7)).unwrap();
+        let copy = BTreeInternalPage::new(
+            BTreePageId::new(1, 9, PageCategory::Internal),
+            &page.get_page_data(),
+            Type::Int,
+        )
+        .unwrap();
+        assert_eq!(vec!["10", "15", "20", "30"], keys(&copy));
+        assert_eq!(
+            vec![leaf(1), leaf(2), leaf(7), leaf(3), leaf(4)],
+            copy.children()
+        );
+        assert!(page.insert_entry(int(5), &leaf(8), &leaf(9)).is_err());
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let mut page = get_page(5);
+        let (mid, keys_right, children_right) = page.split_off();
+        assert_eq!("30", mid.to_string());
+        assert_eq!(vec!["10", "20"], keys(&page));
+        assert_eq!(vec![leaf(1), leaf(2), leaf(3)], page.children());
+        assert_eq!(vec![4, 5, 6], children_right);
+
+        page.append_entries(Some(mid), keys_right, children_right);
+        assert_eq!(vec!["10", "20", "30", "40", "50"], keys(&page));
+        let (key, child) = page.pop_first();
+        assert_eq!(("10".to_string(), leaf(1)), (key.to_string(), child));
+        page.push_last(int(60), &leaf(1));
+        assert_eq!(Some(leaf(1)), page.get_child_id(5));
+        page.delete_entry(0);
+        assert_eq!(vec!["30", "40", "50", "60"], keys(&page));
+        assert_eq!(
+            vec![leaf(2), leaf(4), leaf(5), leaf(6), leaf(1)],
+            page.children()
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use super::btree_page_id::{BTreePageId, PageCategory};
+use crate::common::error::{DbError, DbResult};
+use crate::common::field::Field;
+use crate::common::page_id::PageId;
+use crate::downcast::Downcast;
+use crate::storage::page::{Page, PAGE_SIZE};
+use crate::table::record_id::RecordId;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use downcast_macro_derive::Downcast;
+use std::any::Any;
+use std::convert::TryInto;
+
+/// Bytes taken by the sibling pointers and the tuple count.
+const HEADER_SIZE: usize = 12;
+
+/**
+ * Each instance of BTreeLeafPage stores data for one leaf page of a
+ * BTreeFile. Its tuples are kept sorted on the key field, and leaves are
+ * chained to their left and right siblings so ranges can be scanned.
+ *
+ * The page starts with the page numbers of the left and right siblings (0
+ * for none) and the number of tuples, followed by the tuples in key order.
+ * The RecordId of a tuple is its position on the page, which changes as
+ * tuples are inserted before it.
+ */
+#[derive(Downcast)]
+pub struct BTreeLeafPage {
+    pid: BTreePageId,
+    td: TupleDesc,
+    key_field: usize,
+    left_sibling: i32,
+    right_sibling: i32,
+    tuples: Vec<Tuple>,
+    dirty: Option<TransactionId>,
+    old_data: Vec<u8>,
+}
+
+impl BTreeLeafPage {
+    /// Create a BTreeLeafPage from a set of bytes of data read from disk.
+    pub fn new(pid: BTreePageId, data: &[u8], td: TupleDesc, key_field: usize) -> DbResult<Self> {
+        let read = |i: usize| i32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        let num_tuples = read(8);
+        let tuple_size = td.get_size() as usize;
+        if num_tuples < 0 || num_tuples as usize > Self::compute_max_tuples(&td) {
+            return Err(DbError::Db(format!(
+                "corrupt leaf page {}",
+                pid.get_page_number()
+            )));
+        }
+
+        let mut tuples = Vec::with_capacity(num_tuples as usize);
+        let mut input = &data[HEADER_SIZE..HEADER_SIZE + num_tuples as usize * tuple_size];
+        for _ in 0..num_tuples {
+            let mut t = Tuple::new(td.clone());
+            for i in 0..td.num_fields() {
+                let ty = td.get_field_type(i).unwrap();
+                t.set_field(i, ty.parse(&mut input)?);
+            }
+            tuples.push(t);
+        }
+        let mut page = Self {
+            pid,
+            td,
+            key_field,
+            left_sibling: read(0),
+            right_sibling: read(4),
+            tuples,
+            dirty: None,
+            old_data: data.to_vec(),
+        };
+        page.renumber(0);
+        Ok(page)
+    }
+
+    /// Returns a byte array representing an empty BTreeLeafPage.
+    pub fn create_empty_page_data() -> Vec<u8> {
+        vec![0; PAGE_SIZE]
+    }
+
+    /// Returns the number of tuples that fit on a page.
+    pub fn get_max_tuples(&self) -> usize {
+        Self::compute_max_tuples(&self.td)
+    }
+
+    /// Returns the number of tuples a leaf other than the root must hold.
+    pub fn get_min_tuples(&self) -> usize {
+        self.get_max_tuples() / 2
+    }
+
+    pub fn num_tuples(&self) -> usize {
+        self.tuples.len()
+    }
+
+    pub fn get_num_empty_slots(&self) -> usize {
+        self.get_max_tuples() - self.num_tuples()
+    }
+
+    pub fn get_left_sibling_id(&self) -> Option<BTreePageId> {
+        self.sibling_id(self.left_sibling)
+    }
+
+    pub fn get_right_sibling_id(&self) -> Option<BTreePageId> {
+        self.sibling_id(self.right_sibling)
+    }
+
+    pub fn set_left_sibling_id(&mut self, id: Option<BTreePageId>) {
+        self.left_sibling = id.map(|id| id.get_page_number()).unwrap_or(0);
+    }
+
+    pub fn set_right_sibling_id(&mut self, id: Option<BTreePageId>) {
+        self.right_sibling = id.map(|id| id.get_page_number()).unwrap_or(0);
+    }
+
+    /// Returns the key of the first tuple on the page, if any.
+    pub fn first_key(&self) -> Option<&dyn Field> {
+        self.tuples.first().map(|t| self.key(t))
+    }
+
+    /**
+     * Adds the specified tuple to the page, after the tuples with a lower or
+     * equal key.
+     */
+    pub fn insert_tuple(&mut self, t: Tuple) -> DbResult<()> {
+        if *t.get_tuple_desc() != self.td {
+            return Err(DbError::Db("tuple desc mismatch".to_string()));
+        }
+        if self.get_num_empty_slots() == 0 {
+            return Err(DbError::Db("page is full".to_string()));
+        }
+        let key = self.key(&t);
+        let slot = self.tuples.partition_point(|other| self.key(other) <= key);
+        self.tuples.insert(slot, t);
+        self.renumber(slot);
+        Ok(())
+    }
+
+    /**
+     * Delete the specified tuple from the page. The tuple is looked up by
+     * value, starting at the slot named by its RecordId, since slots move
+     * when tuples are inserted or deleted.
+     */
+    pub fn delete_tuple(&mut self, t: &Tuple) -> DbResult<()> {
+        let hint = t
+            .get_record_id()
+            .filter(|rid| rid.get_page_id().key() == self.pid.key())
+            .map(|rid| rid.get_tuple_number() as usize);
+        let same = |other: &Tuple| other.get_fields().eq(t.get_fields());
+        let slot = hint
+            .filter(|&slot| self.tuples.get(slot).map(same).unwrap_or(false))
+            .or_else(|| self.tuples.iter().position(same))
+            .ok_or_else(|| DbError::Db("tuple is not on this page".to_string()))?;
+        self.tuples.remove(slot);
+        self.renumber(slot);
+        Ok(())
+    }
+
+    /// An iterator over all tuples on this page, in key order.
+    pub fn iterator(&self) -> impl Iterator<Item = &Tuple> {
+        self.tuples.iter()
+    }
+
+    /// Removes and returns the first `n` tuples of the page.
+    pub fn remove_first(&mut self, n: usize) -> Vec<Tuple> {
+        let rest = self.tuples.split_off(n);
+        let removed = std::mem::replace(&mut self.tuples, rest);
+        self.renumber(0);
+        removed
+    }
+
+    /// Removes and returns the last `n` tuples of the page.
+    pub fn remove_last(&mut self, n: usize) -> Vec<Tuple> {
+        self.tuples.split_off(self.tuples.len() - n)
+    }
+
+    /// Adds tuples whose keys are lower than or equal to those on the page.
+    pub fn add_first(&mut self, mut tuples: Vec<Tuple>) {
+        tuples.append(&mut self.tuples);
+        self.tuples = tuples;
+        self.renumber(0);
+    }
+
+    /// Adds tuples whose keys are greater than or equal to those on the page.
+    pub fn add_last(&mut self, tuples: Vec<Tuple>) {
+        let start = self.tuples.len();
+        self.tuples.extend(tuples);
+        self.renumber(start);
+    }
+
+    fn key<'a>(&self, t: &'a Tuple) -> &'a dyn Field {
+        t.get_field(self.key_field).unwrap()
+    }
+
+    fn sibling_id(&self, pgno: i32) -> Option<BTreePageId> {
+        if pgno == 0 {
+            None
+        } else {
+            Some(BTreePageId::new(
+                self.pid.get_table_id(),
+                pgno,
+                PageCategory::Leaf,
+            ))
+        }
+    }
+
+    /// Points the RecordIds of the tuples from `start` on at their slots.
+    fn renumber(&mut self, start: usize) {
+        for (i, t) in self.tuples.iter_mut().enumerate().skip(start) {
+            t.set_record_id(RecordId::new(Box::new(self.pid), i as i32));
+        }
+    }
+
+    fn compute_max_tuples(td: &TupleDesc) -> usize {
+        (PAGE_SIZE - HEADER_SIZE) / td.get_size() as usize
+    }
+}
+
+impl Page for BTreeLeafPage {
+    fn get_id(&self) -> Box<dyn PageId> {
+        Box::new(self.pid)
+    }
+
+    fn get_page_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(PAGE_SIZE);
+        data.extend_from_slice(&self.left_sibling.to_be_bytes());
+        data.extend_from_slice(&self.right_sibling.to_be_bytes());
+        data.extend_from_slice(&(self.tuples.len() as i32).to_be_bytes());
+        for t in self.tuples.iter() {
+            for field in t.get_fields() {
+                // Writing into a Vec cannot fail.
+                field.serialize(&mut data).unwrap();
+            }
+        }
+        data.resize(PAGE_SIZE, 0);
+        data
+    }
+
+    fn is_dirty(&self) -> Option<TransactionId> {
+        self.dirty
+    }
+
+    fn mark_dirty(&mut self, dirty: bool, tid: TransactionId) {
+        self.dirty = if dirty { Some(tid) } else { None };
+    }
+
+    fn get_before_image(&self) -> Box<dyn Page> {
+        // The old data was produced by a page of the same layout.
+        Box::new(
+            BTreeLeafPage::new(self.pid, &self.old_data, self.td.clone(), self.key_field).unwrap(),
+        )
+    }
+
+    fn set_before_image(&mut self) {
+        self.old_data = self.get_page_data();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{int_tuple, int_tuple_desc};
+
+    fn get_page(keys: &[i32]) -> BTreeLeafPage {
+        let pid = BTreePageId::new(1, 3, PageCategory::Leaf);
+        let data = BTreeLeafPage::create_empty_page_data();
+        let mut page = BTreeLeafPage::new(pid, &data, int_tuple_desc(2), 1).unwrap();
+        for (i, &key) in keys.iter().enumerate() {
+            page.insert_tuple(int_tuple(&[i as i32, key])).unwrap();
+        }
+        page
+    }
+
+    fn keys(page: &BTreeLeafPage) -> Vec<String> {
+        page.iterator()
+            .map(|t| t.get_field(1).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_sorted_insert() {
+        let page = get_page(&[5, 1, 3, 1]);
+        assert_eq!(vec!["1", "1", "3", "5"], keys(&page));
+        // equal keys stay in insertion order
+        let first: Vec<String> = page
+            .iterator()
+            .map(|t| t.get_field(0).unwrap().to_string())
+            .collect();
+        assert_eq!(vec!["1", "3", "2", "0"], first);
+        for (i, t) in page.iterator().enumerate() {
+            assert_eq!(i as i32, t.get_record_id().unwrap().get_tuple_number());
+        }
+        assert_eq!(510, page.get_max_tuples());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut page = get_page(&[5, 1, 3]);
+        page.set_right_sibling_id(Some(BTreePageId::new(1, 7, PageCategory::Leaf)));
+        let copy = BTreeLeafPage::new(
+            BTreePageId::new(1, 3, PageCategory::Leaf),
+            &page.get_page_data(),
+            int_tuple_desc(2),
+            1,
+        )
+        .unwrap();
+        assert_eq!(keys(&page), keys(&copy));
+        assert_eq!(None, copy.get_left_sibling_id());
+        assert_eq!(7, copy.get_right_sibling_id().unwrap().get_page_number());
+    }
+
+    #[test]
+    fn test_delete_tuple() {
+        let mut page = get_page(&[5, 1, 3]);
+        let t = page.iterator().nth(1).cloned().unwrap();
+        page.delete_tuple(&t).unwrap();
+        assert_eq!(vec!["1", "5"], keys(&page));
+        assert!(page.delete_tuple(&t).is_err());
+
+        // a tuple is found even if it moved since it was read
+        let t = page.iterator().nth(1).cloned().unwrap();
+        page.insert_tuple(int_tuple(&[9, 0])).unwrap();
+        page.delete_tuple(&t).unwrap();
+        assert_eq!(vec!["0", "1"], keys(&page));
+    }
+
+    #[test]
+    fn test_move_tuples() {
+        let mut left = get_page(&[1, 2, 3, 4]);
+        let mut right = get_page(&[5, 6]);
+        right.add_first(left.remove_last(2));
+        assert_eq!(vec!["1", "2"], keys(&left));
+        assert_eq!(vec!["3", "4", "5", "6"], keys(&right));
+        left.add_last(right.remove_first(3));
+        assert_eq!(vec!["1", "2", "3", "4", "5"], keys(&left));
+        assert_eq!("6", right.first_key().unwrap().to_string());
+    }
+}
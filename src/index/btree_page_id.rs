@@ -0,0 +1,132 @@
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::PageId;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/**
+ * The kinds of pages a BTreeFile is made of.
+ */
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum PageCategory {
+    /// The first page of the file, pointing at the root of the tree.
+    RootPtr,
+    Internal,
+    Leaf,
+}
+
+impl PageCategory {
+    pub fn get_category(i: i32) -> Option<PageCategory> {
+        match i {
+            0 => Some(Self::RootPtr),
+            1 => Some(Self::Internal),
+            2 => Some(Self::Leaf),
+            _ => None,
+        }
+    }
+
+    pub fn to_i32(self) -> i32 {
+        match self {
+            Self::RootPtr => 0,
+            Self::Internal => 1,
+            Self::Leaf => 2,
+        }
+    }
+}
+
+/**
+ * Unique identifier for the pages of BTreeFiles. On top of the table and
+ * the page number, it records the kind of page it points at.
+ */
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct BTreePageId {
+    table_id: u64,
+    pgno: i32,
+    category: PageCategory,
+}
+
+impl BTreePageId {
+    pub fn new(table_id: u64, pgno: i32, category: PageCategory) -> Self {
+        Self {
+            table_id,
+            pgno,
+            category,
+        }
+    }
+
+    /// Returns the id of the root pointer page of a table, which is always page 0.
+    pub fn root_ptr(table_id: u64) -> Self {
+        Self::new(table_id, 0, PageCategory::RootPtr)
+    }
+
+    /// Rebuilds a page id from the integers returned by `serialize`.
+    pub fn deserialize(data: &[i32]) -> DbResult<Self> {
+        let malformed = || DbError::Db(format!("malformed btree page id {:?}", data));
+        match data {
+            [hi, lo, pgno, category] => Ok(Self::new(
+                ((*hi as u32 as u64) << 32) | *lo as u32 as u64,
+                *pgno,
+                PageCategory::get_category(*category).ok_or_else(malformed)?,
+            )),
+            _ => Err(malformed()),
+        }
+    }
+
+    pub fn get_category(&self) -> PageCategory {
+        self.category
+    }
+}
+
+impl PageId for BTreePageId {
+    /// The table id is split into its high and low halves.
+    fn serialize(&self) -> Vec<i32> {
+        vec![
+            (self.table_id >> 32) as i32,
+            self.table_id as i32,
+            self.pgno,
+            self.category.to_i32(),
+        ]
+    }
+
+    fn get_table_id(&self) -> u64 {
+        self.table_id
+    }
+
+    fn hash_code(&self) -> u64 {
+        let mut s = DefaultHasher::new();
+        self.hash(&mut s);
+        s.finish()
+    }
+
+    fn equals(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn get_page_number(&self) -> i32 {
+        self.pgno
+    }
+
+    fn clone_box(&self) -> Box<dyn PageId> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        for &category in [
+            PageCategory::RootPtr,
+            PageCategory::Internal,
+            PageCategory::Leaf,
+        ]
+        .iter()
+        {
+            let pid = BTreePageId::new(0x8000_0000_7fff_ffff, 7, category);
+            assert_eq!(pid, BTreePageId::deserialize(&pid.serialize()).unwrap());
+        }
+        assert!(BTreePageId::deserialize(&[1, 2, 3]).is_err());
+        assert!(BTreePageId::deserialize(&[1, 2, 3, 9]).is_err());
+    }
+}
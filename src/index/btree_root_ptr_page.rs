@@ -0,0 +1,84 @@
+use super::btree_page_id::{BTreePageId, PageCategory};
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::PageId;
+use crate::downcast::Downcast;
+use crate::storage::page::{Page, PAGE_SIZE};
+use crate::transaction::transaction_id::TransactionId;
+use downcast_macro_derive::Downcast;
+use std::any::Any;
+use std::convert::TryInto;
+
+/**
+ * BTreeRootPtrPage is the first page of a BTreeFile. It stores the page
+ * number and category of the root of the tree, so the root can move when
+ * it is split or collapses.
+ */
+#[derive(Downcast)]
+pub struct BTreeRootPtrPage {
+    pid: BTreePageId,
+    root: BTreePageId,
+    dirty: Option<TransactionId>,
+    old_data: Vec<u8>,
+}
+
+impl BTreeRootPtrPage {
+    /// Create a BTreeRootPtrPage from a set of bytes of data read from disk.
+    pub fn new(pid: BTreePageId, data: &[u8]) -> DbResult<Self> {
+        let read = |i: usize| i32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        let category = PageCategory::get_category(read(4))
+            .filter(|&c| c != PageCategory::RootPtr)
+            .ok_or_else(|| DbError::Db("corrupt root pointer page".to_string()))?;
+        Ok(Self {
+            pid,
+            root: BTreePageId::new(pid.get_table_id(), read(0), category),
+            dirty: None,
+            old_data: data.to_vec(),
+        })
+    }
+
+    /// Returns the data of a root pointer page pointing at a leaf root on page 1.
+    pub fn create_page_data() -> Vec<u8> {
+        let mut data = vec![0; PAGE_SIZE];
+        data[..4].copy_from_slice(&1i32.to_be_bytes());
+        data[4..8].copy_from_slice(&PageCategory::Leaf.to_i32().to_be_bytes());
+        data
+    }
+
+    pub fn get_root_id(&self) -> BTreePageId {
+        self.root
+    }
+
+    pub fn set_root_id(&mut self, root: BTreePageId) {
+        self.root = root;
+    }
+}
+
+impl Page for BTreeRootPtrPage {
+    fn get_id(&self) -> Box<dyn PageId> {
+        Box::new(self.pid)
+    }
+
+    fn get_page_data(&self) -> Vec<u8> {
+        let mut data = vec![0; PAGE_SIZE];
+        data[..4].copy_from_slice(&self.root.get_page_number().to_be_bytes());
+        data[4..8].copy_from_slice(&self.root.get_category().to_i32().to_be_bytes());
+        data
+    }
+
+    fn is_dirty(&self) -> Option<TransactionId> {
+        self.dirty
+    }
+
+    fn mark_dirty(&mut self, dirty: bool, tid: TransactionId) {
+        self.dirty = if dirty { Some(tid) } else { None };
+    }
+
+    fn get_before_image(&self) -> Box<dyn Page> {
+        // The old data was produced by a page of the same layout.
+        Box::new(BTreeRootPtrPage::new(self.pid, &self.old_data).unwrap())
+    }
+
+    fn set_before_image(&mut self) {
+        self.old_data = self.get_page_data();
+    }
+}
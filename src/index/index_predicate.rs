@@ -0,0 +1,66 @@
+use crate::common::field::Field;
+use crate::common::predicate::Op;
+
+/**
+ * IndexPredicate compares the key of an index against a constant, and is
+ * used to look up a range of an index.
+ */
+#[derive(Clone, Debug)]
+pub struct IndexPredicate {
+    op: Op,
+    field: Box<dyn Field>,
+}
+
+impl IndexPredicate {
+    pub fn new(op: Op, field: Box<dyn Field>) -> Self {
+        Self { op, field }
+    }
+
+    pub fn get_op(&self) -> Op {
+        self.op
+    }
+
+    pub fn get_field(&self) -> &dyn Field {
+        self.field.as_ref()
+    }
+
+    /// Returns true if `key` satisfies this predicate.
+    pub fn matches(&self, key: &dyn Field) -> bool {
+        key.compare_field(self.op, self.field.as_ref())
+            .unwrap_or(false)
+    }
+
+    /**
+     * Returns true if no key greater than `key` can satisfy this predicate,
+     * so a scan in key order can stop once it went past `key`.
+     */
+    pub fn is_past(&self, key: &dyn Field) -> bool {
+        match self.op {
+            Op::Equals | Op::LessThan | Op::LessThanOrEq => key > self.field.as_ref(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+
+    fn int(v: i32) -> Box<dyn Field> {
+        Box::new(IntField::new(v))
+    }
+
+    #[test]
+    fn test_matches() {
+        let pred = IndexPredicate::new(Op::LessThanOrEq, int(5));
+        assert!(pred.matches(int(5).as_ref()));
+        assert!(!pred.matches(int(6).as_ref()));
+        assert!(!pred.is_past(int(5).as_ref()));
+        assert!(pred.is_past(int(6).as_ref()));
+
+        let pred = IndexPredicate::new(Op::GreaterThan, int(5));
+        assert!(pred.matches(int(6).as_ref()));
+        assert!(!pred.is_past(int(100).as_ref()));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod btree_file;
+pub mod btree_internal_page;
+pub mod btree_leaf_page;
+pub mod btree_page_id;
+pub mod btree_root_ptr_page;
+pub mod index_predicate;
@@ -1,5 +1,11 @@
-mod common;
-mod downcast;
-mod table;
-
-fn main() {}
+pub mod common;
+pub mod downcast;
+pub mod execution;
+pub mod index;
+pub mod optimizer;
+pub mod sql;
+pub mod storage;
+pub mod table;
+#[cfg(test)]
+mod test_util;
+pub mod transaction;
@@ -0,0 +1,117 @@
+use simple_db::common::database::Database;
+use simple_db::common::error::{DbError, DbResult};
+use simple_db::common::ty::Type;
+use simple_db::sql::shell::Shell;
+use simple_db::storage::heap_file_encoder;
+use simple_db::transaction::transaction_id::TransactionId;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process;
+use std::sync::Arc;
+
+const USAGE: &str = "usage: simple_db <database directory> [-f script.sql]
+       simple_db convert <input.txt> <output.dat> <type,type,...> [delimiter]
+       simple_db export <database directory> <table> [delimiter]";
+
+/**
+ * Opens the database in a directory holding a `catalog.txt`, then runs the
+ * SQL statements of a script given with `-f`, or reads them from stdin.
+ *
+ * `convert` turns a delimited text file into a heap file holding fields of
+ * the given types (`int` or `string`), and `export` writes the tuples of a
+ * table to stdout as delimited text. The delimiter defaults to `,`.
+ */
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let result = match args.as_slice() {
+        ["convert", input, output, types, rest @ ..] if rest.len() <= 1 => {
+            delimiter(rest).and_then(|d| convert(input, output, types, d))
+        }
+        ["export", dir, table, rest @ ..] if rest.len() <= 1 => {
+            delimiter(rest).and_then(|d| export(dir, table, d))
+        }
+        [dir] => run(dir, None),
+        [dir, "-f", script] => run(dir, Some(script)),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Reads the optional delimiter argument, which must be a single character.
+fn delimiter(args: &[&str]) -> DbResult<char> {
+    let arg = args.first().copied().unwrap_or(",");
+    let mut chars = arg.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ if arg == "\\t" => Ok('\t'),
+        _ => Err(DbError::Parse(format!("invalid delimiter {}", arg))),
+    }
+}
+
+fn convert(input: &str, output: &str, types: &str, delimiter: char) -> DbResult<()> {
+    let types = types
+        .split(',')
+        .map(|ty| ty.trim().parse())
+        .collect::<DbResult<Vec<Type>>>()?;
+    let names: Vec<String> = (0..types.len()).map(|i| format!("field{}", i)).collect();
+    let names = names.iter().map(|n| n.as_str()).collect();
+    let input = BufReader::new(File::open(input)?);
+    heap_file_encoder::convert(input, output, types, names, delimiter)?;
+    Ok(())
+}
+
+fn export(dir: &str, table: &str, delimiter: char) -> DbResult<()> {
+    let db = Arc::new(Database::open(dir)?);
+    let table_id = db.get_catalog().get_table_id(table)?;
+    let tid = TransactionId::new();
+    let result = heap_file_encoder::export(db.clone(), tid, table_id, &mut io::stdout(), delimiter);
+    db.get_buffer_pool()
+        .transaction_complete(tid, result.is_ok())?;
+    result.map(|_| ())
+}
+
+fn run(dir: &str, script: Option<&str>) -> DbResult<()> {
+    let db = Arc::new(Database::open(dir)?);
+    let mut shell = Shell::new(db.clone());
+    let mut out = io::stdout();
+    match script {
+        Some(script) => {
+            shell.run_script(script, &mut out)?;
+        }
+        None => repl(&mut shell, &mut out)?,
+    }
+    db.checkpoint()
+}
+
+/// Reads lines from stdin until `\q` or the end of input, prompting on a terminal.
+fn repl(shell: &mut Shell, out: &mut dyn Write) -> DbResult<()> {
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            let prompt = if shell.in_statement() {
+                "      -> "
+            } else {
+                "simple_db> "
+            };
+            write!(out, "{}", prompt)?;
+            out.flush()?;
+        }
+        let line = match lines.next() {
+            Some(line) => line.map_err(DbError::Io)?,
+            None => return Ok(()),
+        };
+        if !shell.read_line(&line, out)? {
+            return Ok(());
+        }
+    }
+}
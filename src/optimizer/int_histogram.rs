@@ -0,0 +1,201 @@
+use crate::common::predicate::Op;
+
+/**
+ * A class to represent a fixed-width histogram over a single integer-based
+ * field. Values in `[min, max]` are spread over equally wide buckets, and
+ * within a bucket values are assumed to be uniformly distributed.
+ */
+pub struct IntHistogram {
+    buckets: Vec<usize>,
+    min: i32,
+    max: i32,
+    width: f64,
+    count: usize,
+}
+
+impl IntHistogram {
+    /**
+     * Creates a histogram of `buckets` buckets over the values between
+     * `min` and `max`, inclusive. Fewer buckets are used when the range
+     * holds fewer than `buckets` distinct values, so that no bucket is
+     * narrower than a single value.
+     */
+    pub fn new(buckets: usize, min: i32, max: i32) -> Self {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        let range = max as i64 - min as i64 + 1;
+        let buckets = (buckets.max(1) as i64).min(range) as usize;
+        Self {
+            buckets: vec![0; buckets],
+            min,
+            max,
+            width: range as f64 / buckets as f64,
+            count: 0,
+        }
+    }
+
+    /// Adds a value to the set of values that you are keeping a histogram of.
+    pub fn add_value(&mut self, v: i32) {
+        if let Some(i) = self.bucket(v) {
+            self.buckets[i] += 1;
+            self.count += 1;
+        }
+    }
+
+    /// Returns the number of values added to the histogram.
+    pub fn num_values(&self) -> usize {
+        self.count
+    }
+
+    /**
+     * Estimates the fraction of the values in the histogram that satisfy
+     * `value op v`. LIKE on integers means equality.
+     */
+    pub fn estimate_selectivity(&self, op: Op, v: i32) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let selectivity = match op {
+            Op::Equals | Op::Like => self.equals(v),
+            Op::NotEquals => 1.0 - self.equals(v),
+            Op::GreaterThan => self.greater_than(v),
+            Op::GreaterThanOrEq => self.greater_than(v) + self.equals(v),
+            Op::LessThan => 1.0 - self.greater_than(v) - self.equals(v),
+            Op::LessThanOrEq => 1.0 - self.greater_than(v),
+        };
+        selectivity.clamp(0.0, 1.0)
+    }
+
+    /**
+     * Returns the selectivity of `op` averaged over constants drawn from
+     * the values in the histogram.
+     */
+    pub fn avg_selectivity(&self, op: Op) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let n = self.count as f64;
+        // a constant lands in bucket i with probability h/n, and then
+        // matches h/width values of it
+        let equals: f64 = self
+            .buckets
+            .iter()
+            .map(|&h| (h as f64 / n) * (h as f64 / self.width.max(1.0) / n))
+            .sum();
+        match op {
+            Op::Equals | Op::Like => equals,
+            Op::NotEquals => 1.0 - equals,
+            Op::GreaterThan | Op::LessThan => (1.0 - equals) / 2.0,
+            Op::GreaterThanOrEq | Op::LessThanOrEq => (1.0 + equals) / 2.0,
+        }
+    }
+
+    /// Returns the bucket `v` falls into, or None if it is out of range.
+    fn bucket(&self, v: i32) -> Option<usize> {
+        if v < self.min || v > self.max {
+            return None;
+        }
+        let i = ((v as i64 - self.min as i64) as f64 / self.width) as usize;
+        Some(i.min(self.buckets.len() - 1))
+    }
+
+    fn equals(&self, v: i32) -> f64 {
+        match self.bucket(v) {
+            Some(i) => self.buckets[i] as f64 / self.width.max(1.0) / self.count as f64,
+            None => 0.0,
+        }
+    }
+
+    fn greater_than(&self, v: i32) -> f64 {
+        if v < self.min {
+            return 1.0;
+        }
+        let i = match self.bucket(v) {
+            Some(i) => i,
+            None => return 0.0,
+        };
+        // the bucket covers [min + i * width, min + (i + 1) * width)
+        let right = self.min as f64 + (i + 1) as f64 * self.width;
+        let part = ((right - v as f64 - 1.0) / self.width).max(0.0);
+        let above: usize = self.buckets[i + 1..].iter().sum();
+        (self.buckets[i] as f64 * part + above as f64) / self.count as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 0.01,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_uniform() {
+        let mut h = IntHistogram::new(10, 1, 100);
+        for v in 1..=100 {
+            h.add_value(v);
+        }
+        assert_close(0.01, h.estimate_selectivity(Op::Equals, 50));
+        assert_close(0.01, h.estimate_selectivity(Op::Like, 50));
+        assert_close(0.99, h.estimate_selectivity(Op::NotEquals, 50));
+        assert_close(0.5, h.estimate_selectivity(Op::GreaterThan, 50));
+        assert_close(0.51, h.estimate_selectivity(Op::GreaterThanOrEq, 50));
+        assert_close(0.49, h.estimate_selectivity(Op::LessThan, 50));
+        assert_close(0.5, h.estimate_selectivity(Op::LessThanOrEq, 50));
+        assert_close(0.25, h.estimate_selectivity(Op::LessThanOrEq, 25));
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let mut h = IntHistogram::new(10, 1, 100);
+        for v in 1..=100 {
+            h.add_value(v);
+        }
+        assert_close(0.0, h.estimate_selectivity(Op::Equals, -5));
+        assert_close(0.0, h.estimate_selectivity(Op::Equals, 500));
+        assert_close(1.0, h.estimate_selectivity(Op::GreaterThan, -5));
+        assert_close(0.0, h.estimate_selectivity(Op::GreaterThan, 500));
+        assert_close(0.0, h.estimate_selectivity(Op::LessThan, -5));
+        assert_close(1.0, h.estimate_selectivity(Op::LessThan, 500));
+        assert_close(1.0, h.estimate_selectivity(Op::NotEquals, 500));
+        assert_close(0.0, h.estimate_selectivity(Op::LessThan, 1));
+        assert_close(0.0, h.estimate_selectivity(Op::GreaterThan, 100));
+    }
+
+    #[test]
+    fn test_skewed() {
+        // more buckets than values, and a range spanning all of i32
+        let mut h = IntHistogram::new(100, 1, 3);
+        for v in [1, 1, 1, 2] {
+            h.add_value(v);
+        }
+        assert_close(0.75, h.estimate_selectivity(Op::Equals, 1));
+        assert_close(0.0, h.estimate_selectivity(Op::Equals, 3));
+        assert_close(0.25, h.estimate_selectivity(Op::GreaterThan, 1));
+        assert_close(0.625, h.avg_selectivity(Op::Equals));
+
+        let mut h = IntHistogram::new(10, i32::MIN, i32::MAX);
+        h.add_value(i32::MIN);
+        h.add_value(i32::MAX);
+        assert_close(0.5, h.estimate_selectivity(Op::GreaterThan, 0));
+        assert_close(1.0, h.estimate_selectivity(Op::LessThanOrEq, i32::MAX));
+    }
+
+    #[test]
+    fn test_avg_selectivity() {
+        let mut h = IntHistogram::new(10, 1, 100);
+        assert_close(0.0, h.avg_selectivity(Op::Equals));
+        for v in 1..=100 {
+            h.add_value(v);
+        }
+        assert_close(0.01, h.avg_selectivity(Op::Equals));
+        assert_close(0.99, h.avg_selectivity(Op::NotEquals));
+        assert_close(0.495, h.avg_selectivity(Op::LessThan));
+        assert_close(0.505, h.avg_selectivity(Op::GreaterThanOrEq));
+    }
+}
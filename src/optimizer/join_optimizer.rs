@@ -0,0 +1,833 @@
+use super::logical_join_node::LogicalJoinNode;
+use super::table_stats::TableStats;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::join_predicate::JoinPredicate;
+use crate::common::predicate::Op;
+use crate::execution::hash_equi_join::HashEquiJoin;
+use crate::execution::join::Join;
+use crate::execution::join_filter::JoinFilter;
+use crate::execution::op_iterator::OpIterator;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Most joins the optimizer orders; plans are kept for every subset of them.
+pub const MAX_JOINS: usize = 16;
+/// Fraction of the cross product a range join is assumed to produce.
+const RANGE_JOIN_SELECTIVITY: f64 = 0.3;
+
+/// The physical operators a join can be run with.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum JoinMethod {
+    NestedLoop,
+    Hash,
+}
+
+impl fmt::Display for JoinMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match self {
+            Self::NestedLoop => "NestedLoopJoin",
+            Self::Hash => "HashJoin",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/**
+ * A plan for joining a set of tables, with its estimated cost and
+ * cardinality. The left child of a join is its outer input, which a hash
+ * join builds its table on.
+ */
+#[derive(Clone, Debug)]
+pub enum JoinPlan {
+    Scan {
+        alias: String,
+        table: String,
+        cost: f64,
+        card: usize,
+    },
+    Join {
+        join: LogicalJoinNode,
+        method: JoinMethod,
+        left: Box<JoinPlan>,
+        right: Box<JoinPlan>,
+        cost: f64,
+        card: usize,
+    },
+    /// A join between two tables the child has already joined, which
+    /// filters the tuples of the child.
+    Filter {
+        join: LogicalJoinNode,
+        child: Box<JoinPlan>,
+        cost: f64,
+        card: usize,
+    },
+}
+
+impl JoinPlan {
+    /// Returns the estimated cost of running the plan.
+    pub fn cost(&self) -> f64 {
+        match self {
+            Self::Scan { cost, .. } | Self::Join { cost, .. } | Self::Filter { cost, .. } => *cost,
+        }
+    }
+
+    /// Returns the estimated number of tuples the plan produces.
+    pub fn card(&self) -> usize {
+        match self {
+            Self::Scan { card, .. } | Self::Join { card, .. } | Self::Filter { card, .. } => *card,
+        }
+    }
+
+    /// Returns true if the plan reads the table with the specified alias.
+    pub fn contains(&self, alias: &str) -> bool {
+        match self {
+            Self::Scan { alias: a, .. } => a == alias,
+            Self::Join { left, right, .. } => left.contains(alias) || right.contains(alias),
+            Self::Filter { child, .. } => child.contains(alias),
+        }
+    }
+
+    /**
+     * Builds the operators running the plan. `scan` is called with the
+     * alias of each table to build the operator reading it, whose tuples
+     * must name their fields `alias.field`.
+     */
+    pub fn instantiate(
+        &self,
+        scan: &mut dyn FnMut(&str) -> DbResult<Box<dyn OpIterator>>,
+    ) -> DbResult<Box<dyn OpIterator>> {
+        match self {
+            Self::Scan { alias, .. } => scan(alias),
+            Self::Join {
+                join,
+                method,
+                left,
+                right,
+                ..
+            } => {
+                let left = left.instantiate(scan)?;
+                let right = right.instantiate(scan)?;
+                let pred = JoinPredicate::new(
+                    field_index(left.as_ref(), &join.t1_alias, &join.f1)?,
+                    join.op,
+                    field_index(right.as_ref(), &join.t2_alias, &join.f2)?,
+                );
+                Ok(match method {
+                    JoinMethod::NestedLoop => Box::new(Join::new(pred, left, right)),
+                    JoinMethod::Hash => Box::new(HashEquiJoin::new(pred, left, right)?),
+                })
+            }
+            Self::Filter { join, child, .. } => {
+                let child = child.instantiate(scan)?;
+                let pred = JoinPredicate::new(
+                    field_index(child.as_ref(), &join.t1_alias, &join.f1)?,
+                    join.op,
+                    field_index(child.as_ref(), &join.t2_alias, &join.f2)?,
+                );
+                Ok(Box::new(JoinFilter::new(pred, child)))
+            }
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        match self {
+            Self::Scan {
+                alias,
+                table,
+                cost,
+                card,
+            } => writeln!(
+                f,
+                "{}Scan {} AS {} (cost={:.1}, card={})",
+                indent, table, alias, cost, card
+            ),
+            Self::Join {
+                join,
+                method,
+                left,
+                right,
+                cost,
+                card,
+            } => {
+                writeln!(
+                    f,
+                    "{}{} {} (cost={:.1}, card={})",
+                    indent, method, join, cost, card
+                )?;
+                left.fmt_indented(f, depth + 1)?;
+                right.fmt_indented(f, depth + 1)
+            }
+            Self::Filter {
+                join,
+                child,
+                cost,
+                card,
+            } => {
+                writeln!(
+                    f,
+                    "{}Filter {} (cost={:.1}, card={})",
+                    indent, join, cost, card
+                )?;
+                child.fmt_indented(f, depth + 1)
+            }
+        }
+    }
+}
+
+/// Returns the index of the field `alias.field` in the tuples of `op`.
+fn field_index(op: &dyn OpIterator, alias: &str, field: &str) -> DbResult<usize> {
+    let name = format!("{}.{}", alias, field);
+    op.get_tuple_desc()
+        .field_name_to_index(name.clone())
+        .ok_or_else(|| DbError::NoSuchElement(format!("field {}", name)))
+}
+
+/// Shows the plan as a tree, one operator per line, children indented.
+impl fmt::Display for JoinPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/**
+ * JoinOptimizer orders the joins of a query, using the Selinger dynamic
+ * programming algorithm: the cheapest plan for each subset of the joins is
+ * built from the cheapest plans of its subsets one join smaller. Each join
+ * adds one table to a plan, so the plans are left-deep, except that the
+ * new table may be the outer input of the join when that is cheaper. A join
+ * between two tables a plan already joins, as in a join on two columns or a
+ * cycle of joins, filters the tuples of the plan instead.
+ */
+pub struct JoinOptimizer {
+    db: Arc<Database>,
+    tables: HashMap<String, u64>,
+    joins: Vec<LogicalJoinNode>,
+}
+
+impl JoinOptimizer {
+    /**
+     * Constructor.
+     *
+     * tables: the id of the table each alias in the query stands for.
+     * joins: the joins of the query.
+     */
+    pub fn new(
+        db: Arc<Database>,
+        tables: HashMap<String, u64>,
+        joins: Vec<LogicalJoinNode>,
+    ) -> Self {
+        Self { db, tables, joins }
+    }
+
+    /**
+     * Returns the cheapest plan running all the joins.
+     *
+     * stats: the statistics of each table, by table name.
+     * filter_selectivities: the selectivity of the filters on each table,
+     * by alias. Tables without an entry are not filtered.
+     *
+     * Fails if the joins do not connect the tables.
+     */
+    pub fn order_joins(
+        &self,
+        stats: &HashMap<String, TableStats>,
+        filter_selectivities: &HashMap<String, f64>,
+    ) -> DbResult<JoinPlan> {
+        let n = self.joins.len();
+        if n == 0 {
+            return Err(DbError::Db("there are no joins to order".to_string()));
+        }
+        if n > MAX_JOINS {
+            return Err(DbError::Db(format!(
+                "cannot order {} joins, the most is {}",
+                n, MAX_JOINS
+            )));
+        }
+        let scan = |alias: &str| self.scan_plan(alias, stats, filter_selectivities);
+
+        // best[s] is the cheapest plan of the joins in the bit set s. Each
+        // set is built after its subsets, which are smaller numbers.
+        let mut best: Vec<Option<JoinPlan>> = (0..1usize << n).map(|_| None).collect();
+        for set in 1..1usize << n {
+            for (j, join) in self.joins.iter().enumerate() {
+                if set & (1 << j) == 0 {
+                    continue;
+                }
+                let rest = set & !(1 << j);
+                let candidate = if rest == 0 {
+                    Some(self.best_join(join, scan(&join.t1_alias)?, scan(&join.t2_alias)?)?)
+                } else if let Some(prev) = &best[rest] {
+                    match (prev.contains(&join.t1_alias), prev.contains(&join.t2_alias)) {
+                        (true, false) => {
+                            Some(self.best_join(join, prev.clone(), scan(&join.t2_alias)?)?)
+                        }
+                        (false, true) => {
+                            Some(self.best_join(join, scan(&join.t1_alias)?, prev.clone())?)
+                        }
+                        (true, true) => Some(self.filter_join(
+                            join,
+                            prev.clone(),
+                            scan(&join.t1_alias)?.card(),
+                            scan(&join.t2_alias)?.card(),
+                        )?),
+                        (false, false) => None,
+                    }
+                } else {
+                    None
+                };
+                if let Some(plan) = candidate {
+                    if best[set].as_ref().is_none_or(|b| plan.cost() < b.cost()) {
+                        best[set] = Some(plan);
+                    }
+                }
+            }
+        }
+        best.pop()
+            .flatten()
+            .ok_or_else(|| DbError::Db("the joins do not connect the tables".to_string()))
+    }
+
+    /**
+     * Estimates the cost of a join, given the costs and cardinalities of
+     * its outer (1) and inner (2) inputs. Costs count pages read from disk
+     * times their cost, plus one for each tuple handled in memory.
+     */
+    pub fn estimate_join_cost(
+        method: JoinMethod,
+        cost1: f64,
+        cost2: f64,
+        card1: usize,
+        card2: usize,
+    ) -> f64 {
+        let (card1, card2) = (card1 as f64, card2 as f64);
+        match method {
+            // the inner input is read again for every outer tuple
+            JoinMethod::NestedLoop => cost1 + card1 * cost2 + card1 * card2,
+            // each input is read once, building and probing the hash table
+            JoinMethod::Hash => cost1 + cost2 + card1 + card2,
+        }
+    }
+
+    /**
+     * Estimates the number of tuples produced by a join, given the
+     * cardinalities of its inputs. An equality join on a primary key
+     * produces at most one tuple per tuple of the other input.
+     */
+    pub fn estimate_join_cardinality(
+        &self,
+        join: &LogicalJoinNode,
+        card1: usize,
+        card2: usize,
+    ) -> DbResult<usize> {
+        Ok(match join.op {
+            Op::Equals => {
+                let pkey1 = self.is_primary_key(&join.t1_alias, &join.f1)?;
+                let pkey2 = self.is_primary_key(&join.t2_alias, &join.f2)?;
+                match (pkey1, pkey2) {
+                    (true, true) => card1.min(card2),
+                    (true, false) => card2,
+                    (false, true) => card1,
+                    (false, false) => card1.max(card2),
+                }
+            }
+            Op::NotEquals => card1 * card2,
+            _ => (card1 as f64 * card2 as f64 * RANGE_JOIN_SELECTIVITY) as usize,
+        })
+    }
+
+    /**
+     * Returns the cheapest way of running `join` over the two plans, trying
+     * both methods and both orders of the inputs.
+     */
+    fn best_join(
+        &self,
+        join: &LogicalJoinNode,
+        left: JoinPlan,
+        right: JoinPlan,
+    ) -> DbResult<JoinPlan> {
+        let card = self.estimate_join_cardinality(join, left.card(), right.card())?;
+        let mut orders = vec![(join.clone(), &left, &right)];
+        if let Some(swapped) = join.swap_inner_outer() {
+            orders.push((swapped, &right, &left));
+        }
+        let mut methods = vec![JoinMethod::NestedLoop];
+        if join.op == Op::Equals {
+            methods.push(JoinMethod::Hash);
+        }
+
+        let mut best: Option<JoinPlan> = None;
+        for (join, outer, inner) in orders {
+            for &method in methods.iter() {
+                let cost = Self::estimate_join_cost(
+                    method,
+                    outer.cost(),
+                    inner.cost(),
+                    outer.card(),
+                    inner.card(),
+                );
+                if best.as_ref().is_none_or(|b| cost < b.cost()) {
+                    best = Some(JoinPlan::Join {
+                        join: join.clone(),
+                        method,
+                        left: Box::new(outer.clone()),
+                        right: Box::new(inner.clone()),
+                        cost,
+                        card,
+                    });
+                }
+            }
+        }
+        // there is always at least one order and one method
+        Ok(best.unwrap())
+    }
+
+    /**
+     * Returns the plan running `join` as a filter over `child`, which joins
+     * both of its tables. The selectivity of the filter is that of the join
+     * over the tables alone, whose cardinalities are `card1` and `card2`,
+     * and each tuple of the child is handled once.
+     */
+    fn filter_join(
+        &self,
+        join: &LogicalJoinNode,
+        child: JoinPlan,
+        card1: usize,
+        card2: usize,
+    ) -> DbResult<JoinPlan> {
+        let cross = card1 as f64 * card2 as f64;
+        let selectivity = if cross > 0.0 {
+            self.estimate_join_cardinality(join, card1, card2)? as f64 / cross
+        } else {
+            1.0
+        };
+        Ok(JoinPlan::Filter {
+            join: join.clone(),
+            cost: child.cost() + child.card() as f64,
+            card: (child.card() as f64 * selectivity) as usize,
+            child: Box::new(child),
+        })
+    }
+
+    fn scan_plan(
+        &self,
+        alias: &str,
+        stats: &HashMap<String, TableStats>,
+        filter_selectivities: &HashMap<String, f64>,
+    ) -> DbResult<JoinPlan> {
+        let table = self
+            .db
+            .get_catalog()
+            .get_table_name(self.table_id(alias)?)?;
+        let table_stats = stats
+            .get(&table)
+            .ok_or_else(|| DbError::NoSuchElement(format!("statistics of table {}", table)))?;
+        let selectivity = filter_selectivities.get(alias).copied().unwrap_or(1.0);
+        Ok(JoinPlan::Scan {
+            alias: alias.to_string(),
+            cost: table_stats.estimate_scan_cost(),
+            card: table_stats.estimate_table_cardinality(selectivity),
+            table,
+        })
+    }
+
+    fn is_primary_key(&self, alias: &str, field: &str) -> DbResult<bool> {
+        let pkey = self
+            .db
+            .get_catalog()
+            .get_primary_key(self.table_id(alias)?)?;
+        Ok(pkey.as_deref() == Some(field))
+    }
+
+    fn table_id(&self, alias: &str) -> DbResult<u64> {
+        self.tables
+            .get(alias)
+            .copied()
+            .ok_or_else(|| DbError::NoSuchElement(format!("table alias {}", alias)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::execution::seq_scan::SeqScan;
+    use crate::optimizer::table_stats::IO_COST_PER_PAGE;
+    use crate::storage::db_file::DbFile;
+    use crate::test_util::{create_heap_file, read_ints, TempDir};
+    use crate::transaction::transaction_id::TransactionId;
+
+    /**
+     * Creates tables `small` (10 rows), `medium` (1000 rows) and `large`
+     * (10000 rows), whose field0 is a primary key and whose field1 joins
+     * `small` to the others.
+     */
+    fn create_tables(db: &Arc<Database>, dir: &TempDir) -> HashMap<String, TableStats> {
+        let mut stats = HashMap::new();
+        for &(name, rows) in [("small", 10), ("medium", 1000), ("large", 10000)].iter() {
+            let rows: Vec<Vec<i32>> = (0..rows).map(|i| vec![i, i % 10]).collect();
+            let path = dir.path().join(format!("{}.dat", name));
+            let f = Arc::new(create_heap_file(&path, 2, &rows));
+            db.get_catalog().add_table(f.clone(), name, Some("field0"));
+            let s = TableStats::new(db.clone(), f.get_id(), IO_COST_PER_PAGE).unwrap();
+            stats.insert(name.to_string(), s);
+        }
+        stats
+    }
+
+    fn aliases(db: &Database, pairs: &[(&str, &str)]) -> HashMap<String, u64> {
+        pairs
+            .iter()
+            .map(|(alias, table)| {
+                let id = db.get_catalog().get_table_id(table).unwrap();
+                (alias.to_string(), id)
+            })
+            .collect()
+    }
+
+    /// Lists the aliases scanned by a plan, left to right.
+    fn scans(plan: &JoinPlan) -> Vec<String> {
+        match plan {
+            JoinPlan::Scan { alias, .. } => vec![alias.clone()],
+            JoinPlan::Join { left, right, .. } => {
+                let mut v = scans(left);
+                v.extend(scans(right));
+                v
+            }
+            JoinPlan::Filter { child, .. } => scans(child),
+        }
+    }
+
+    /// Runs a plan, returning the index of each field by name and the rows.
+    fn run(
+        db: &Arc<Database>,
+        tables: &HashMap<String, u64>,
+        plan: &JoinPlan,
+    ) -> (HashMap<String, usize>, Vec<Vec<i32>>) {
+        let tid = TransactionId::new();
+        let mut op = plan
+            .instantiate(&mut |alias| {
+                Ok(Box::new(SeqScan::new(
+                    db.clone(),
+                    tid,
+                    tables[alias],
+                    alias,
+                )?))
+            })
+            .unwrap();
+        let td = op.get_tuple_desc();
+        let fields = (0..td.num_fields())
+            .map(|i| (td.get_field_name(i).unwrap().to_string(), i))
+            .collect();
+        op.open().unwrap();
+        (fields, read_ints(op.as_mut()))
+    }
+
+    #[test]
+    fn test_join_estimates() {
+        let dir = TempDir::new("join_optimizer_estimates");
+        let db = Arc::new(Database::new(50));
+        create_tables(&db, &dir);
+        let opt = JoinOptimizer::new(
+            db.clone(),
+            aliases(&db, &[("s", "small"), ("m", "medium")]),
+            vec![],
+        );
+        let card = |join: LogicalJoinNode| opt.estimate_join_cardinality(&join, 10, 1000).unwrap();
+        assert_eq!(
+            10,
+            card(LogicalJoinNode::new(
+                "s",
+                "field0",
+                Op::Equals,
+                "m",
+                "field0"
+            ))
+        );
+        assert_eq!(
+            1000,
+            card(LogicalJoinNode::new(
+                "s",
+                "field0",
+                Op::Equals,
+                "m",
+                "field1"
+            ))
+        );
+        assert_eq!(
+            10,
+            card(LogicalJoinNode::new(
+                "s",
+                "field1",
+                Op::Equals,
+                "m",
+                "field0"
+            ))
+        );
+        assert_eq!(
+            1000,
+            card(LogicalJoinNode::new(
+                "s",
+                "field1",
+                Op::Equals,
+                "m",
+                "field1"
+            ))
+        );
+        assert_eq!(
+            3000,
+            card(LogicalJoinNode::new(
+                "s",
+                "field1",
+                Op::LessThan,
+                "m",
+                "field1"
+            ))
+        );
+        assert_eq!(
+            10000,
+            card(LogicalJoinNode::new(
+                "s",
+                "field1",
+                Op::NotEquals,
+                "m",
+                "field1"
+            ))
+        );
+
+        let nl = JoinOptimizer::estimate_join_cost(JoinMethod::NestedLoop, 100.0, 1000.0, 10, 1000);
+        assert_eq!(100.0 + 10.0 * 1000.0 + 10.0 * 1000.0, nl);
+        let hash = JoinOptimizer::estimate_join_cost(JoinMethod::Hash, 100.0, 1000.0, 10, 1000);
+        assert_eq!(100.0 + 1000.0 + 10.0 + 1000.0, hash);
+    }
+
+    #[test]
+    fn test_order_joins() {
+        let dir = TempDir::new("join_optimizer_order");
+        let db = Arc::new(Database::new(50));
+        let stats = create_tables(&db, &dir);
+        let joins = vec![
+            LogicalJoinNode::new("l", "field1", Op::Equals, "m", "field1"),
+            LogicalJoinNode::new("m", "field1", Op::Equals, "s", "field0"),
+        ];
+        let tables = aliases(&db, &[("s", "small"), ("m", "medium"), ("l", "large")]);
+        let opt = JoinOptimizer::new(db.clone(), tables, joins);
+        let plan = opt.order_joins(&stats, &HashMap::new()).unwrap();
+
+        // the small tables are joined first, all with hash joins
+        match &plan {
+            JoinPlan::Join {
+                method,
+                left,
+                right,
+                ..
+            } => {
+                assert_eq!(JoinMethod::Hash, *method);
+                let (scan, sub) = if left.contains("l") {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                assert_eq!(vec!["l"], scans(scan), "{}", plan);
+                assert!(sub.contains("s") && sub.contains("m"), "{}", plan);
+            }
+            _ => panic!("expected a join"),
+        }
+        let explain = plan.to_string();
+        assert_eq!(5, explain.lines().count(), "{}", explain);
+        assert!(explain.starts_with("HashJoin"), "{}", explain);
+        assert!(explain.contains("  Scan large AS l"), "{}", explain);
+
+        // filters shrink the tables they apply to
+        let mut selectivities = HashMap::new();
+        selectivities.insert("l".to_string(), 0.0001);
+        let plan = opt.order_joins(&stats, &selectivities).unwrap();
+        let explain = plan.to_string();
+        assert!(
+            explain.contains("Scan large AS l (cost=20000.0, card=1)"),
+            "{}",
+            explain
+        );
+        assert_eq!(1000, plan.card());
+    }
+
+    #[test]
+    fn test_range_join_method() {
+        let dir = TempDir::new("join_optimizer_range");
+        let db = Arc::new(Database::new(50));
+        let stats = create_tables(&db, &dir);
+        let tables = aliases(&db, &[("s", "small"), ("m", "medium")]);
+        let joins = vec![LogicalJoinNode::new(
+            "s",
+            "field0",
+            Op::GreaterThan,
+            "m",
+            "field0",
+        )];
+        let opt = JoinOptimizer::new(db, tables, joins);
+        let plan = opt.order_joins(&stats, &HashMap::new()).unwrap();
+        match &plan {
+            JoinPlan::Join { method, card, .. } => {
+                assert_eq!(JoinMethod::NestedLoop, *method);
+                assert_eq!(3000, *card);
+            }
+            _ => panic!("expected a join"),
+        }
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let dir = TempDir::new("join_optimizer_instantiate");
+        let db = Arc::new(Database::new(50));
+        let stats = create_tables(&db, &dir);
+        let tables = aliases(&db, &[("s", "small"), ("m", "medium"), ("t", "small")]);
+        let joins = vec![
+            LogicalJoinNode::new("m", "field0", Op::Equals, "s", "field1"),
+            LogicalJoinNode::new("s", "field0", Op::LessThan, "t", "field0"),
+        ];
+        let opt = JoinOptimizer::new(db.clone(), tables.clone(), joins);
+        let plan = opt.order_joins(&stats, &HashMap::new()).unwrap();
+
+        let tid = TransactionId::new();
+        let mut op = plan
+            .instantiate(&mut |alias| {
+                Ok(Box::new(SeqScan::new(
+                    db.clone(),
+                    tid,
+                    tables[alias],
+                    alias,
+                )?))
+            })
+            .unwrap();
+        op.open().unwrap();
+        let rows = read_ints(op.as_mut());
+        // s.field1 = s.field0 for the small table, so every s row finds
+        // one m row, and 9 - s.field0 rows of t
+        assert_eq!(45, rows.len(), "{}", plan);
+        let td = op.get_tuple_desc();
+        let (s0, m0, s1, t0) = (
+            td.field_name_to_index("s.field0".to_string()).unwrap(),
+            td.field_name_to_index("m.field0".to_string()).unwrap(),
+            td.field_name_to_index("s.field1".to_string()).unwrap(),
+            td.field_name_to_index("t.field0".to_string()).unwrap(),
+        );
+        for row in rows {
+            assert_eq!(row[m0], row[s1]);
+            assert!(row[s0] < row[t0]);
+        }
+    }
+
+    #[test]
+    fn test_join_on_two_columns() {
+        let dir = TempDir::new("join_optimizer_two_columns");
+        let db = Arc::new(Database::new(50));
+        let stats = create_tables(&db, &dir);
+        let tables = aliases(&db, &[("s", "small"), ("m", "medium")]);
+        let joins = vec![
+            LogicalJoinNode::new("m", "field1", Op::Equals, "s", "field1"),
+            LogicalJoinNode::new("m", "field0", Op::Equals, "s", "field0"),
+        ];
+        let opt = JoinOptimizer::new(db.clone(), tables.clone(), joins);
+        let plan = opt.order_joins(&stats, &HashMap::new()).unwrap();
+
+        // the tables are joined on their primary keys, and the other
+        // condition filters the join
+        match &plan {
+            JoinPlan::Filter { child, card, .. } => {
+                assert!(matches!(**child, JoinPlan::Join { .. }), "{}", plan);
+                assert_eq!(10, child.card());
+                assert_eq!(1, *card);
+            }
+            _ => panic!("expected a filter:\n{}", plan),
+        }
+        let explain = plan.to_string();
+        assert!(
+            explain.starts_with("Filter m.field1 = s.field1"),
+            "{}",
+            explain
+        );
+        assert_eq!(4, explain.lines().count(), "{}", explain);
+
+        let (fields, rows) = run(&db, &tables, &plan);
+        assert_eq!(10, rows.len(), "{}", plan);
+        for row in rows {
+            assert_eq!(row[fields["m.field0"]], row[fields["s.field0"]]);
+            assert_eq!(row[fields["m.field1"]], row[fields["s.field1"]]);
+        }
+    }
+
+    #[test]
+    fn test_join_cycle() {
+        let dir = TempDir::new("join_optimizer_cycle");
+        let db = Arc::new(Database::new(50));
+        let stats = create_tables(&db, &dir);
+        let tables = aliases(&db, &[("s", "small"), ("m", "medium"), ("l", "large")]);
+        let joins = vec![
+            LogicalJoinNode::new("s", "field0", Op::Equals, "m", "field0"),
+            LogicalJoinNode::new("m", "field1", Op::Equals, "l", "field1"),
+            LogicalJoinNode::new("l", "field0", Op::Equals, "s", "field0"),
+        ];
+        let opt = JoinOptimizer::new(db.clone(), tables.clone(), joins);
+        let plan = opt.order_joins(&stats, &HashMap::new()).unwrap();
+        let mut aliases = scans(&plan);
+        aliases.sort();
+        assert_eq!(vec!["l", "m", "s"], aliases);
+        assert_eq!(1, plan.to_string().matches("Filter ").count(), "{}", plan);
+
+        let (fields, rows) = run(&db, &tables, &plan);
+        assert_eq!(10, rows.len(), "{}", plan);
+        for row in rows {
+            let s0 = row[fields["s.field0"]];
+            assert_eq!(s0, row[fields["m.field0"]]);
+            assert_eq!(s0, row[fields["l.field0"]]);
+        }
+    }
+
+    #[test]
+    fn test_unorderable() {
+        let dir = TempDir::new("join_optimizer_unorderable");
+        let db = Arc::new(Database::new(50));
+        let stats = create_tables(&db, &dir);
+        let tables = aliases(&db, &[("s", "small"), ("m", "medium"), ("l", "large")]);
+        let opt = JoinOptimizer::new(db.clone(), tables.clone(), vec![]);
+        assert!(opt.order_joins(&stats, &HashMap::new()).is_err());
+
+        // tables that are not connected
+        let tables = aliases(
+            &db,
+            &[
+                ("s", "small"),
+                ("m", "medium"),
+                ("l", "large"),
+                ("t", "small"),
+            ],
+        );
+        let joins = vec![
+            LogicalJoinNode::new("s", "field0", Op::Equals, "m", "field0"),
+            LogicalJoinNode::new("l", "field0", Op::Equals, "t", "field0"),
+        ];
+        let opt = JoinOptimizer::new(db.clone(), tables.clone(), joins);
+        assert!(opt.order_joins(&stats, &HashMap::new()).is_err());
+
+        // unknown aliases and tables without statistics
+        let joins = vec![LogicalJoinNode::new(
+            "s",
+            "field0",
+            Op::Equals,
+            "x",
+            "field0",
+        )];
+        let opt = JoinOptimizer::new(db.clone(), tables.clone(), joins);
+        assert!(opt.order_joins(&stats, &HashMap::new()).is_err());
+        let joins = vec![LogicalJoinNode::new(
+            "s",
+            "field0",
+            Op::Equals,
+            "m",
+            "field0",
+        )];
+        let opt = JoinOptimizer::new(db, tables, joins);
+        assert!(opt.order_joins(&HashMap::new(), &HashMap::new()).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::common::predicate::Op;
+use std::fmt;
+
+/**
+ * A LogicalJoinNode represents the state needed of a join of two tables in
+ * a logical query plan: `t1_alias.f1 op t2_alias.f2`. Field names are the
+ * pure names, without the alias.
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogicalJoinNode {
+    pub t1_alias: String,
+    pub t2_alias: String,
+    pub f1: String,
+    pub f2: String,
+    pub op: Op,
+}
+
+impl LogicalJoinNode {
+    pub fn new(t1_alias: &str, f1: &str, op: Op, t2_alias: &str, f2: &str) -> Self {
+        Self {
+            t1_alias: t1_alias.to_string(),
+            t2_alias: t2_alias.to_string(),
+            f1: f1.to_string(),
+            f2: f2.to_string(),
+            op,
+        }
+    }
+
+    /**
+     * Returns the same join with the tables the other way around, or None
+     * for LIKE, which cannot be turned around.
+     */
+    pub fn swap_inner_outer(&self) -> Option<Self> {
+        let op = match self.op {
+            Op::GreaterThan => Op::LessThan,
+            Op::GreaterThanOrEq => Op::LessThanOrEq,
+            Op::LessThan => Op::GreaterThan,
+            Op::LessThanOrEq => Op::GreaterThanOrEq,
+            Op::Like => return None,
+            op => op,
+        };
+        Some(Self::new(
+            &self.t2_alias,
+            &self.f2,
+            op,
+            &self.t1_alias,
+            &self.f1,
+        ))
+    }
+}
+
+impl fmt::Display for LogicalJoinNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{} {} {}.{}",
+            self.t1_alias, self.f1, self.op, self.t2_alias, self.f2
+        )
+    }
+}
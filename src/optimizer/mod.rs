@@ -0,0 +1,5 @@
+pub mod int_histogram;
+pub mod join_optimizer;
+pub mod logical_join_node;
+pub mod string_histogram;
+pub mod table_stats;
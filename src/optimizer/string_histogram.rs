@@ -0,0 +1,122 @@
+use super::int_histogram::IntHistogram;
+use crate::common::predicate::Op;
+
+/// Guess of the fraction of strings containing a nonempty pattern.
+const LIKE_SELECTIVITY: f64 = 0.1;
+
+/**
+ * A class to represent a fixed-width histogram over a single String-based
+ * field. Strings are mapped to integers that keep their order, by their
+ * first four bytes, and counted by an IntHistogram.
+ */
+pub struct StringHistogram {
+    hist: IntHistogram,
+}
+
+impl StringHistogram {
+    /// Creates a histogram of `buckets` buckets.
+    pub fn new(buckets: usize) -> Self {
+        Self {
+            hist: IntHistogram::new(buckets, Self::min_val(), Self::max_val()),
+        }
+    }
+
+    /**
+     * Converts a string to an integer, such that if `s1 < s2` then
+     * `string_to_int(s1) <= string_to_int(s2)`.
+     */
+    pub fn string_to_int(s: &str) -> i32 {
+        let mut v: u32 = 0;
+        for i in 0..4 {
+            v = v << 8 | s.as_bytes().get(i).copied().unwrap_or(0) as u32;
+        }
+        // dropping the lowest bit keeps the order and the value positive
+        (v >> 1) as i32
+    }
+
+    fn min_val() -> i32 {
+        Self::string_to_int("")
+    }
+
+    fn max_val() -> i32 {
+        i32::MAX
+    }
+
+    /// Adds a value to the set of values that you are keeping a histogram of.
+    pub fn add_value(&mut self, s: &str) {
+        self.hist.add_value(Self::string_to_int(s));
+    }
+
+    /// Returns the number of values added to the histogram.
+    pub fn num_values(&self) -> usize {
+        self.hist.num_values()
+    }
+
+    /**
+     * Estimates the fraction of the values in the histogram that satisfy
+     * `value op s`. LIKE matches values containing `s`, which the prefixes
+     * kept by the histogram cannot tell, so a fixed fraction is assumed.
+     */
+    pub fn estimate_selectivity(&self, op: Op, s: &str) -> f64 {
+        match op {
+            Op::Like if self.num_values() == 0 => 0.0,
+            Op::Like if s.is_empty() => 1.0,
+            Op::Like => LIKE_SELECTIVITY,
+            _ => self.hist.estimate_selectivity(op, Self::string_to_int(s)),
+        }
+    }
+
+    /**
+     * Returns the selectivity of `op` averaged over constants drawn from
+     * the values in the histogram.
+     */
+    pub fn avg_selectivity(&self, op: Op) -> f64 {
+        match op {
+            Op::Like if self.num_values() == 0 => 0.0,
+            Op::Like => LIKE_SELECTIVITY,
+            _ => self.hist.avg_selectivity(op),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_string_to_int() {
+        let words = ["", "a", "ab", "abc", "abcd", "abcz", "b", "zzzz", "\u{ff}"];
+        for pair in words.windows(2) {
+            assert!(
+                StringHistogram::string_to_int(pair[0]) < StringHistogram::string_to_int(pair[1]),
+                "{:?}",
+                pair
+            );
+        }
+        // only the first four bytes count
+        assert_eq!(
+            StringHistogram::string_to_int("abcd"),
+            StringHistogram::string_to_int("abcdef")
+        );
+    }
+
+    #[test]
+    fn test_selectivity() {
+        let mut h = StringHistogram::new(100);
+        assert_eq!(0.0, h.estimate_selectivity(Op::Like, "a"));
+        for c in b'a'..=b'z' {
+            h.add_value(&(c as char).to_string().repeat(4));
+        }
+        assert_eq!(26, h.num_values());
+        let lt = h.estimate_selectivity(Op::LessThan, "n");
+        assert!((lt - 0.5).abs() < 0.05, "{}", lt);
+        let gt = h.estimate_selectivity(Op::GreaterThan, "n");
+        assert!((gt - 0.5).abs() < 0.05, "{}", gt);
+        assert!(h.estimate_selectivity(Op::GreaterThan, "zzzzzz") < 0.05);
+        assert_eq!(1.0, h.estimate_selectivity(Op::GreaterThanOrEq, ""));
+        assert!(h.estimate_selectivity(Op::Equals, "mmmm") > 0.0);
+        assert_eq!(1.0, h.estimate_selectivity(Op::Like, ""));
+        assert_eq!(LIKE_SELECTIVITY, h.estimate_selectivity(Op::Like, "m"));
+        assert!(h.avg_selectivity(Op::NotEquals) > 0.9);
+    }
+}
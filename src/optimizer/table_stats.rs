@@ -0,0 +1,234 @@
+use super::int_histogram::IntHistogram;
+use super::string_histogram::StringHistogram;
+use crate::common::database::Database;
+use crate::common::error::DbResult;
+use crate::common::field::Field;
+use crate::common::int_field::IntField;
+use crate::common::predicate::Op;
+use crate::common::string_field::StringField;
+use crate::common::ty::Type;
+use crate::downcast;
+use crate::transaction::transaction_id::TransactionId;
+use std::sync::Arc;
+
+/// Default cost of reading a page from disk, in the units of the join costs.
+pub const IO_COST_PER_PAGE: usize = 1000;
+/// Number of buckets in each histogram.
+pub const NUM_HIST_BINS: usize = 100;
+
+enum Histogram {
+    Int(IntHistogram),
+    Str(StringHistogram),
+}
+
+/**
+ * TableStats represents statistics (e.g., histograms) about base tables in
+ * a query, used by the optimizer to estimate the cost and cardinality of
+ * plans.
+ */
+pub struct TableStats {
+    num_tuples: usize,
+    num_pages: usize,
+    io_cost_per_page: usize,
+    histograms: Vec<Histogram>,
+}
+
+impl TableStats {
+    /**
+     * Computes the statistics of the specified table by scanning it once,
+     * in a transaction of its own.
+     *
+     * io_cost_per_page: the cost of reading a page of the table, as used
+     * by estimate_scan_cost.
+     */
+    pub fn new(db: Arc<Database>, table_id: u64, io_cost_per_page: usize) -> DbResult<Self> {
+        let file = db.get_catalog().get_database_file(table_id)?;
+        let td = file.get_tuple_desc().clone();
+
+        // the range of the int columns is only known once all values are read
+        let mut ints: Vec<Vec<i32>> = vec![Vec::new(); td.num_fields()];
+        let mut strings: Vec<Option<StringHistogram>> = (0..td.num_fields())
+            .map(|i| match td.get_field_type(i) {
+                Some(Type::Str) => Some(StringHistogram::new(NUM_HIST_BINS)),
+                _ => None,
+            })
+            .collect();
+        let mut num_tuples = 0;
+        let tid = TransactionId::new();
+        let scan: DbResult<()> = file.iterator(db.clone(), tid).try_for_each(|t| {
+            let t = t?;
+            for (i, field) in t.get_fields().enumerate() {
+                match strings[i].as_mut() {
+                    Some(h) => h.add_value(&downcast!(field, StringField).get_value()),
+                    None => ints[i].push(downcast!(field, IntField).get_value()),
+                }
+            }
+            num_tuples += 1;
+            Ok(())
+        });
+        db.get_buffer_pool()
+            .transaction_complete(tid, scan.is_ok())?;
+        scan?;
+
+        let histograms = ints
+            .into_iter()
+            .zip(strings)
+            .map(|(values, string)| match string {
+                Some(h) => Histogram::Str(h),
+                None => {
+                    let min = values.iter().copied().min().unwrap_or(0);
+                    let max = values.iter().copied().max().unwrap_or(0);
+                    let mut h = IntHistogram::new(NUM_HIST_BINS, min, max);
+                    values.into_iter().for_each(|v| h.add_value(v));
+                    Histogram::Int(h)
+                }
+            })
+            .collect();
+        Ok(Self {
+            num_tuples,
+            num_pages: file.num_pages(),
+            io_cost_per_page,
+            histograms,
+        })
+    }
+
+    /**
+     * Estimates the cost of sequentially scanning the file, given that the
+     * cost to read a page is io_cost_per_page. Seeks are not counted, and
+     * the pages are assumed not to be in the buffer pool.
+     */
+    pub fn estimate_scan_cost(&self) -> f64 {
+        (self.num_pages * self.io_cost_per_page) as f64
+    }
+
+    /**
+     * Returns the number of tuples expected in the table once a predicate
+     * of the given selectivity is applied to it.
+     */
+    pub fn estimate_table_cardinality(&self, selectivity: f64) -> usize {
+        (self.num_tuples as f64 * selectivity).round() as usize
+    }
+
+    /**
+     * Returns the expected selectivity of `field op constant` over the
+     * table, for a constant that is not known yet. Returns 1.0 for a field
+     * the table does not have.
+     */
+    pub fn avg_selectivity(&self, field: usize, op: Op) -> f64 {
+        match self.histograms.get(field) {
+            Some(Histogram::Int(h)) => h.avg_selectivity(op),
+            Some(Histogram::Str(h)) => h.avg_selectivity(op),
+            None => 1.0,
+        }
+    }
+
+    /**
+     * Estimates the selectivity of the predicate `field op constant` on the
+     * table. Returns 1.0 for a field the table does not have, or one whose
+     * type differs from that of the constant.
+     */
+    pub fn estimate_selectivity(&self, field: usize, op: Op, constant: &dyn Field) -> f64 {
+        match (self.histograms.get(field), constant.get_type()) {
+            (Some(Histogram::Int(h)), Type::Int) => {
+                h.estimate_selectivity(op, downcast!(constant, IntField).get_value())
+            }
+            (Some(Histogram::Str(h)), Type::Str) => {
+                h.estimate_selectivity(op, &downcast!(constant, StringField).get_value())
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Returns the total number of tuples in this table.
+    pub fn total_tuples(&self) -> usize {
+        self.num_tuples
+    }
+
+    /// Returns the number of pages of this table.
+    pub fn num_pages(&self) -> usize {
+        self.num_pages
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::ty::STRING_LEN;
+    use crate::table::tuple::Tuple;
+    use crate::table::tuple_desc::TupleDesc;
+    use crate::test_util::{create_table, create_table_with, TempDir};
+
+    fn int(v: i32) -> IntField {
+        IntField::new(v)
+    }
+
+    fn string(v: &str) -> StringField {
+        StringField::new(v.to_string(), STRING_LEN as usize)
+    }
+
+    #[test]
+    fn test_int_table() {
+        let dir = TempDir::new("table_stats_int");
+        let db = Arc::new(Database::new(50));
+        let rows: Vec<Vec<i32>> = (0..1000).map(|i| vec![i, i % 10]).collect();
+        let id = create_table(&db, &dir, "t", 2, &rows);
+        let stats = TableStats::new(db.clone(), id, IO_COST_PER_PAGE).unwrap();
+
+        assert_eq!(1000, stats.total_tuples());
+        assert_eq!(2, stats.num_pages());
+        assert_eq!(2000.0, stats.estimate_scan_cost());
+        assert_eq!(100, stats.estimate_table_cardinality(0.1));
+        let eq = stats.estimate_selectivity(1, Op::Equals, &int(3));
+        assert!((eq - 0.1).abs() < 0.01, "{}", eq);
+        let lt = stats.estimate_selectivity(0, Op::LessThan, &int(250));
+        assert!((lt - 0.25).abs() < 0.01, "{}", lt);
+        assert_eq!(
+            0.0,
+            stats.estimate_selectivity(0, Op::GreaterThan, &int(5000))
+        );
+        let avg = stats.avg_selectivity(1, Op::Equals);
+        assert!((avg - 0.1).abs() < 0.01, "{}", avg);
+
+        // nothing is known about other fields or types
+        assert_eq!(1.0, stats.estimate_selectivity(2, Op::Equals, &int(3)));
+        assert_eq!(1.0, stats.estimate_selectivity(1, Op::Equals, &string("3")));
+        assert_eq!(1.0, stats.avg_selectivity(2, Op::Equals));
+    }
+
+    #[test]
+    fn test_string_table() {
+        let dir = TempDir::new("table_stats_string");
+        let db = Arc::new(Database::new(50));
+        let td = TupleDesc::new(vec![Type::Int, Type::Str], vec!["id", "name"]);
+        let tuples = (0..260)
+            .map(|i| {
+                let mut t = Tuple::new(td.clone());
+                t.set_field(0, Box::new(int(i)));
+                let c = (b'a' + (i % 26) as u8) as char;
+                t.set_field(1, Box::new(string(&c.to_string().repeat(4))));
+                t
+            })
+            .collect();
+        let id = create_table_with(&db, &dir, "t", td, tuples);
+        let stats = TableStats::new(db, id, 10).unwrap();
+
+        assert_eq!(260, stats.total_tuples());
+        assert_eq!(stats.num_pages() as f64 * 10.0, stats.estimate_scan_cost());
+        let lt = stats.estimate_selectivity(1, Op::LessThan, &string("n"));
+        assert!((lt - 0.5).abs() < 0.05, "{}", lt);
+        assert_eq!(1.0, stats.estimate_selectivity(1, Op::Like, &string("")));
+        assert!(stats.avg_selectivity(1, Op::NotEquals) > 0.9);
+    }
+
+    #[test]
+    fn test_empty_table() {
+        let dir = TempDir::new("table_stats_empty");
+        let db = Arc::new(Database::new(50));
+        let id = create_table(&db, &dir, "t", 2, &[]);
+        let stats = TableStats::new(db, id, IO_COST_PER_PAGE).unwrap();
+        assert_eq!(0, stats.total_tuples());
+        assert_eq!(0.0, stats.estimate_scan_cost());
+        assert_eq!(0.0, stats.estimate_selectivity(0, Op::Equals, &int(1)));
+        assert_eq!(0, stats.estimate_table_cardinality(0.5));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::common::predicate::Op;
+use crate::execution::aggregator::AggOp;
+use std::fmt;
+
+/// A column, optionally qualified by the alias of its table.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ColumnRef {
+    pub table: Option<String>,
+    pub column: String,
+}
+
+impl fmt::Display for ColumnRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.table {
+            Some(table) => write!(f, "{}.{}", table, self.column),
+            None => write!(f, "{}", self.column),
+        }
+    }
+}
+
+/// A constant in a statement.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Literal {
+    Int(i32),
+    Str(String),
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Str(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+/// One side of a comparison.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Operand {
+    Column(ColumnRef),
+    Literal(Literal),
+}
+
+/// A comparison `left op right` in a WHERE clause.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Condition {
+    pub left: Operand,
+    pub op: Op,
+    pub right: Operand,
+}
+
+/// An entry of the list of values a SELECT returns.
+#[derive(PartialEq, Clone, Debug)]
+pub enum SelectItem {
+    /// `*`, all columns of all tables.
+    Star,
+    Column {
+        column: ColumnRef,
+        alias: Option<String>,
+    },
+    /// An aggregate over a column, or over all rows for `COUNT(*)`.
+    Aggregate {
+        op: AggOp,
+        column: Option<ColumnRef>,
+        alias: Option<String>,
+    },
+}
+
+/// A table in a FROM clause, and the alias it goes by.
+#[derive(PartialEq, Clone, Debug)]
+pub struct TableRef {
+    pub name: String,
+    pub alias: String,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Select {
+    pub items: Vec<SelectItem>,
+    pub from: Vec<TableRef>,
+    pub conditions: Vec<Condition>,
+    pub group_by: Option<ColumnRef>,
+    /// The columns to sort on, and whether the order is ascending.
+    pub order_by: Vec<(ColumnRef, bool)>,
+}
+
+/// Where the rows of an INSERT come from.
+#[derive(PartialEq, Clone, Debug)]
+pub enum InsertSource {
+    Values(Vec<Vec<Literal>>),
+    Select(Box<Select>),
+}
+
+/// A parsed SQL statement.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Statement {
+    Select(Select),
+    Insert {
+        table: String,
+        source: InsertSource,
+    },
+    Delete {
+        table: String,
+        conditions: Vec<Condition>,
+    },
+}
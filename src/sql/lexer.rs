@@ -0,0 +1,146 @@
+use crate::common::error::{DbError, DbResult};
+use std::fmt;
+
+/// Symbols of the language, longest first so that `<=` is not read as `<`.
+const SYMBOLS: [&str; 14] = [
+    "<>", "!=", "<=", ">=", "=", "<", ">", ",", ".", "(", ")", "*", ";", "-",
+];
+
+/**
+ * A token of a SQL statement. Keywords are Words, told apart by the parser.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub enum Token {
+    Word(String),
+    Int(i64),
+    Str(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(w) => write!(f, "{}", w),
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Str(s) => write!(f, "'{}'", s),
+            Self::Symbol(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/**
+ * Splits a statement into tokens, each paired with the column (counting
+ * from 1) it starts at. Strings are quoted with `'`, and a quote inside a
+ * string is written `''`.
+ */
+pub fn tokenize(sql: &str) -> DbResult<Vec<(Token, usize)>> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Token::Word(chars[start..i].iter().collect())
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            let value = digits.parse().map_err(|_| {
+                DbError::Parse(format!(
+                    "column {}: number {} is too large",
+                    start + 1,
+                    digits
+                ))
+            })?;
+            Token::Int(value)
+        } else if c == '\'' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some('\'') if chars.get(i + 1) == Some(&'\'') => {
+                        s.push('\'');
+                        i += 2;
+                    }
+                    Some('\'') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&c) => {
+                        s.push(c);
+                        i += 1;
+                    }
+                    None => {
+                        return Err(DbError::Parse(format!(
+                            "column {}: unterminated string",
+                            start + 1
+                        )))
+                    }
+                }
+            }
+            Token::Str(s)
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(*s))
+                .ok_or_else(|| {
+                    DbError::Parse(format!("column {}: unexpected character {}", start + 1, c))
+                })?;
+            i += symbol.len();
+            Token::Symbol(symbol)
+        };
+        tokens.push((token, start + 1));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tokens(sql: &str) -> Vec<Token> {
+        tokenize(sql).unwrap().into_iter().map(|(t, _)| t).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            vec![
+                Token::Word("SELECT".to_string()),
+                Token::Word("t".to_string()),
+                Token::Symbol("."),
+                Token::Word("a_1".to_string()),
+                Token::Symbol(","),
+                Token::Symbol("*"),
+                Token::Word("where".to_string()),
+                Token::Word("x".to_string()),
+                Token::Symbol("<="),
+                Token::Symbol("-"),
+                Token::Int(12),
+                Token::Symbol("<>"),
+                Token::Str("it's".to_string()),
+                Token::Symbol(";"),
+            ],
+            tokens("SELECT t.a_1, * where x<=-12 <> 'it''s';")
+        );
+        let columns: Vec<usize> = tokenize("a  = 'b'").unwrap().iter().map(|t| t.1).collect();
+        assert_eq!(vec![1, 4, 6], columns);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(tokenize("'abc").is_err());
+        assert!(tokenize("a # b").is_err());
+        assert!(tokenize("99999999999999999999999").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod ast;
+pub mod lexer;
+pub mod parser;
+pub mod planner;
+pub mod shell;
@@ -0,0 +1,445 @@
+use super::ast::{
+    ColumnRef, Condition, InsertSource, Literal, Operand, Select, SelectItem, Statement, TableRef,
+};
+use super::lexer::{tokenize, Token};
+use crate::common::error::{DbError, DbResult};
+use crate::common::predicate::Op;
+use crate::execution::aggregator::AggOp;
+use std::convert::TryFrom;
+
+/// Words that end a clause, and so cannot be used as aliases.
+const KEYWORDS: [&str; 16] = [
+    "SELECT", "FROM", "WHERE", "AND", "GROUP", "BY", "ORDER", "ASC", "DESC", "AS", "INSERT",
+    "INTO", "VALUES", "DELETE", "LIKE", "SET",
+];
+
+/**
+ * Parses a single SQL statement, optionally followed by a `;`:
+ *
+ * - `SELECT items FROM table [alias], ... [WHERE conditions]
+ *   [GROUP BY column] [ORDER BY column [ASC|DESC], ...]`, where items is
+ *   `*` or a list of columns and aggregates such as `COUNT(*)` or
+ *   `MAX(t.a)`, each optionally followed by `[AS] alias`
+ * - `INSERT INTO table VALUES (value, ...), ...`
+ * - `INSERT INTO table SELECT ...`
+ * - `DELETE FROM table [WHERE conditions]`
+ *
+ * Conditions are comparisons of columns and constants joined by AND.
+ * Keywords are case insensitive.
+ */
+pub fn parse(sql: &str) -> DbResult<Statement> {
+    let mut parser = Parser {
+        tokens: tokenize(sql)?,
+        pos: 0,
+    };
+    let statement = parser.statement()?;
+    parser.eat_symbol(";");
+    match parser.peek() {
+        None => Ok(statement),
+        Some(_) => Err(parser.error("end of statement")),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    /// Returns an error saying what was expected at the current token.
+    fn error(&self, expected: &str) -> DbError {
+        match self.tokens.get(self.pos) {
+            Some((token, column)) => DbError::Parse(format!(
+                "column {}: expected {}, found {}",
+                column, expected, token
+            )),
+            None => DbError::Parse(format!("expected {}, found end of statement", expected)),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> DbResult<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(keyword))
+        }
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> DbResult<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(symbol))
+        }
+    }
+
+    /// Parses a name, which may not be a keyword.
+    fn identifier(&mut self) -> DbResult<String> {
+        match self.peek() {
+            Some(Token::Word(w)) if !is_keyword(w) => {
+                let w = w.clone();
+                self.pos += 1;
+                Ok(w)
+            }
+            _ => Err(self.error("a name")),
+        }
+    }
+
+    /// Parses `[AS] alias`, if present.
+    fn alias(&mut self) -> DbResult<Option<String>> {
+        if self.eat_keyword("AS") {
+            return self.identifier().map(Some);
+        }
+        match self.peek() {
+            Some(Token::Word(w)) if !is_keyword(w) => self.identifier().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses a list of at least one item separated by commas.
+    fn list<T>(&mut self, item: fn(&mut Self) -> DbResult<T>) -> DbResult<Vec<T>> {
+        let mut items = vec![item(self)?];
+        while self.eat_symbol(",") {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn statement(&mut self) -> DbResult<Statement> {
+        if self.is_keyword("SELECT") {
+            Ok(Statement::Select(self.select()?))
+        } else if self.eat_keyword("INSERT") {
+            self.expect_keyword("INTO")?;
+            let table = self.identifier()?;
+            let source = if self.eat_keyword("VALUES") {
+                InsertSource::Values(self.list(Self::row)?)
+            } else if self.is_keyword("SELECT") {
+                InsertSource::Select(Box::new(self.select()?))
+            } else {
+                return Err(self.error("VALUES or SELECT"));
+            };
+            Ok(Statement::Insert { table, source })
+        } else if self.eat_keyword("DELETE") {
+            self.expect_keyword("FROM")?;
+            let table = self.identifier()?;
+            let conditions = self.where_clause()?;
+            Ok(Statement::Delete { table, conditions })
+        } else {
+            Err(self.error("SELECT, INSERT or DELETE"))
+        }
+    }
+
+    fn select(&mut self) -> DbResult<Select> {
+        self.expect_keyword("SELECT")?;
+        let items = self.list(Self::select_item)?;
+        self.expect_keyword("FROM")?;
+        let from = self.list(Self::table_ref)?;
+        let conditions = self.where_clause()?;
+        let group_by = if self.eat_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            Some(self.column()?)
+        } else {
+            None
+        };
+        let order_by = if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            self.list(Self::order_item)?
+        } else {
+            Vec::new()
+        };
+        Ok(Select {
+            items,
+            from,
+            conditions,
+            group_by,
+            order_by,
+        })
+    }
+
+    fn select_item(&mut self) -> DbResult<SelectItem> {
+        if self.eat_symbol("*") {
+            return Ok(SelectItem::Star);
+        }
+        let aggregate = match (self.peek(), self.tokens.get(self.pos + 1)) {
+            (Some(Token::Word(w)), Some((Token::Symbol("("), _))) => Some(agg_op(w)),
+            _ => None,
+        };
+        match aggregate {
+            Some(Some(op)) => {
+                self.pos += 2;
+                let column = if self.eat_symbol("*") {
+                    if op != AggOp::Count {
+                        return Err(DbError::Parse(format!("{}(*) is not supported", op)));
+                    }
+                    None
+                } else {
+                    Some(self.column()?)
+                };
+                self.expect_symbol(")")?;
+                let alias = self.alias()?;
+                Ok(SelectItem::Aggregate { op, column, alias })
+            }
+            Some(None) => Err(self.error("MIN, MAX, SUM, AVG or COUNT")),
+            None => {
+                let column = self.column()?;
+                let alias = self.alias()?;
+                Ok(SelectItem::Column { column, alias })
+            }
+        }
+    }
+
+    fn table_ref(&mut self) -> DbResult<TableRef> {
+        let name = self.identifier()?;
+        let alias = self.alias()?.unwrap_or_else(|| name.clone());
+        Ok(TableRef { name, alias })
+    }
+
+    fn order_item(&mut self) -> DbResult<(ColumnRef, bool)> {
+        let column = self.column()?;
+        let ascending = !self.eat_keyword("DESC");
+        if ascending {
+            self.eat_keyword("ASC");
+        }
+        Ok((column, ascending))
+    }
+
+    fn where_clause(&mut self) -> DbResult<Vec<Condition>> {
+        let mut conditions = Vec::new();
+        if self.eat_keyword("WHERE") {
+            conditions.push(self.condition()?);
+            while self.eat_keyword("AND") {
+                conditions.push(self.condition()?);
+            }
+        }
+        Ok(conditions)
+    }
+
+    fn condition(&mut self) -> DbResult<Condition> {
+        let left = self.operand()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("=")) => Op::Equals,
+            Some(Token::Symbol("<>")) | Some(Token::Symbol("!=")) => Op::NotEquals,
+            Some(Token::Symbol("<")) => Op::LessThan,
+            Some(Token::Symbol("<=")) => Op::LessThanOrEq,
+            Some(Token::Symbol(">")) => Op::GreaterThan,
+            Some(Token::Symbol(">=")) => Op::GreaterThanOrEq,
+            _ if self.is_keyword("LIKE") => Op::Like,
+            _ => return Err(self.error("a comparison operator")),
+        };
+        self.pos += 1;
+        let right = self.operand()?;
+        Ok(Condition { left, op, right })
+    }
+
+    fn operand(&mut self) -> DbResult<Operand> {
+        match self.peek() {
+            Some(Token::Word(_)) => Ok(Operand::Column(self.column()?)),
+            _ => Ok(Operand::Literal(self.literal()?)),
+        }
+    }
+
+    fn column(&mut self) -> DbResult<ColumnRef> {
+        let first = self.identifier()?;
+        if self.eat_symbol(".") {
+            Ok(ColumnRef {
+                table: Some(first),
+                column: self.identifier()?,
+            })
+        } else {
+            Ok(ColumnRef {
+                table: None,
+                column: first,
+            })
+        }
+    }
+
+    fn literal(&mut self) -> DbResult<Literal> {
+        let negative = self.eat_symbol("-");
+        match self.peek() {
+            Some(Token::Int(v)) => {
+                let v = if negative { -*v } else { *v };
+                let v = i32::try_from(v).map_err(|_| self.error("a 32-bit integer"))?;
+                self.pos += 1;
+                Ok(Literal::Int(v))
+            }
+            Some(Token::Str(s)) if !negative => {
+                let s = s.clone();
+                self.pos += 1;
+                Ok(Literal::Str(s))
+            }
+            _ => Err(self.error("a constant")),
+        }
+    }
+
+    fn row(&mut self) -> DbResult<Vec<Literal>> {
+        self.expect_symbol("(")?;
+        let values = self.list(Self::literal)?;
+        self.expect_symbol(")")?;
+        Ok(values)
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+fn agg_op(word: &str) -> Option<AggOp> {
+    match word.to_ascii_uppercase().as_str() {
+        "MIN" => Some(AggOp::Min),
+        "MAX" => Some(AggOp::Max),
+        "SUM" => Some(AggOp::Sum),
+        "AVG" => Some(AggOp::Avg),
+        "COUNT" => Some(AggOp::Count),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn col(table: Option<&str>, column: &str) -> ColumnRef {
+        ColumnRef {
+            table: table.map(|t| t.to_string()),
+            column: column.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let statement = parse(
+            "select t.a as x, b, count(*) c from t, u v \
+             WHERE t.a = v.b AND b LIKE 'x' and -3 < a \
+             group by b order by b desc, t.a;",
+        )
+        .unwrap();
+        let expected = Select {
+            items: vec![
+                SelectItem::Column {
+                    column: col(Some("t"), "a"),
+                    alias: Some("x".to_string()),
+                },
+                SelectItem::Column {
+                    column: col(None, "b"),
+                    alias: None,
+                },
+                SelectItem::Aggregate {
+                    op: AggOp::Count,
+                    column: None,
+                    alias: Some("c".to_string()),
+                },
+            ],
+            from: vec![
+                TableRef {
+                    name: "t".to_string(),
+                    alias: "t".to_string(),
+                },
+                TableRef {
+                    name: "u".to_string(),
+                    alias: "v".to_string(),
+                },
+            ],
+            conditions: vec![
+                Condition {
+                    left: Operand::Column(col(Some("t"), "a")),
+                    op: Op::Equals,
+                    right: Operand::Column(col(Some("v"), "b")),
+                },
+                Condition {
+                    left: Operand::Column(col(None, "b")),
+                    op: Op::Like,
+                    right: Operand::Literal(Literal::Str("x".to_string())),
+                },
+                Condition {
+                    left: Operand::Literal(Literal::Int(-3)),
+                    op: Op::LessThan,
+                    right: Operand::Column(col(None, "a")),
+                },
+            ],
+            group_by: Some(col(None, "b")),
+            order_by: vec![(col(None, "b"), false), (col(Some("t"), "a"), true)],
+        };
+        assert_eq!(Statement::Select(expected), statement);
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        assert_eq!(
+            Statement::Insert {
+                table: "t".to_string(),
+                source: InsertSource::Values(vec![
+                    vec![Literal::Int(1), Literal::Str("a".to_string())],
+                    vec![Literal::Int(-2147483648), Literal::Str("".to_string())],
+                ]),
+            },
+            parse("INSERT INTO t VALUES (1, 'a'), (-2147483648, '')").unwrap()
+        );
+        match parse("insert into t select * from u").unwrap() {
+            Statement::Insert {
+                source: InsertSource::Select(select),
+                ..
+            } => assert_eq!(vec![SelectItem::Star], select.items),
+            statement => panic!("unexpected {:?}", statement),
+        }
+        assert_eq!(
+            Statement::Delete {
+                table: "t".to_string(),
+                conditions: vec![Condition {
+                    left: Operand::Column(col(None, "a")),
+                    op: Op::GreaterThanOrEq,
+                    right: Operand::Literal(Literal::Int(5)),
+                }],
+            },
+            parse("DELETE FROM t WHERE a >= 5;").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let message = |sql: &str| match parse(sql) {
+            Err(DbError::Parse(msg)) => msg,
+            result => panic!("unexpected {:?}", result.map(|_| ())),
+        };
+        assert_eq!(
+            "column 10: expected FROM, found where",
+            message("SELECT a where")
+        );
+        assert_eq!(
+            "expected a name, found end of statement",
+            message("SELECT a FROM")
+        );
+        assert_eq!(
+            "column 27: expected end of statement, found b",
+            message("SELECT a FROM t WHERE a=1 b")
+        );
+        assert!(message("SELECT SUM(*) FROM t").contains("SUM(*)"));
+        assert!(message("SELECT FOO(a) FROM t").contains("MIN, MAX"));
+        assert!(message("INSERT INTO t VALUES (2147483648)").contains("32-bit"));
+        assert!(message("UPDATE t SET a = 1").contains("SELECT, INSERT or DELETE"));
+        assert!(message("SELECT a FROM t WHERE a").contains("comparison operator"));
+    }
+}
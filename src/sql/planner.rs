@@ -0,0 +1,977 @@
+use super::ast::{
+    ColumnRef, Condition, InsertSource, Literal, Operand, Select, SelectItem, Statement,
+};
+use super::parser::parse;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::field::Field;
+use crate::common::int_field::IntField;
+use crate::common::predicate::{Op, Predicate};
+use crate::common::string_field::StringField;
+use crate::common::ty::{Type, STRING_LEN};
+use crate::execution::aggregate::Aggregate;
+use crate::execution::delete::Delete;
+use crate::execution::filter::Filter;
+use crate::execution::index_scan::IndexScan;
+use crate::execution::insert::Insert;
+use crate::execution::op_iterator::OpIterator;
+use crate::execution::order_by::{OrderBy, SortKey};
+use crate::execution::project::Project;
+use crate::execution::seq_scan::SeqScan;
+use crate::execution::tuple_iterator::TupleIterator;
+use crate::optimizer::join_optimizer::{JoinOptimizer, JoinPlan};
+use crate::optimizer::logical_join_node::LogicalJoinNode;
+use crate::optimizer::table_stats::{TableStats, IO_COST_PER_PAGE};
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A column of a table in the FROM clause.
+struct Column {
+    alias: String,
+    field: String,
+    ty: Type,
+}
+
+impl Column {
+    /// The name of the column in the tuples of the scan of its table.
+    fn name(&self) -> String {
+        format!("{}.{}", self.alias, self.field)
+    }
+}
+
+/// A condition comparing a column to a constant.
+struct LogicalFilter {
+    field: String,
+    op: Op,
+    value: Box<dyn Field>,
+}
+
+/// The filters of a query, by the alias of the table they apply to.
+type Filters = HashMap<String, Vec<LogicalFilter>>;
+
+/// The operators running (part of) a statement, and their description.
+type Planned = (Box<dyn OpIterator>, PlanNode);
+
+/// The description of an operator of a plan and its children, for EXPLAIN.
+struct PlanNode {
+    label: String,
+    children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    fn new(label: String, children: Vec<PlanNode>) -> Self {
+        Self { label, children }
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(f, "{}{}", "  ".repeat(depth), self.label)?;
+        self.children
+            .iter()
+            .try_for_each(|child| child.fmt_indented(f, depth + 1))
+    }
+
+    /// Describes a join plan, given the descriptions of its scans.
+    fn from_join_plan(plan: &JoinPlan, scans: &mut HashMap<String, PlanNode>) -> Self {
+        match plan {
+            JoinPlan::Scan { alias, .. } => scans.remove(alias).unwrap(),
+            JoinPlan::Join {
+                join,
+                method,
+                left,
+                right,
+                cost,
+                card,
+            } => Self::new(
+                format!("{} {} (cost={:.1}, card={})", method, join, cost, card),
+                vec![
+                    Self::from_join_plan(left, scans),
+                    Self::from_join_plan(right, scans),
+                ],
+            ),
+            JoinPlan::Filter {
+                join,
+                child,
+                cost,
+                card,
+            } => Self::new(
+                format!("Filter {} (cost={:.1}, card={})", join, cost, card),
+                vec![Self::from_join_plan(child, scans)],
+            ),
+        }
+    }
+}
+
+/// Shows the plan as a tree, one operator per line, children indented.
+impl fmt::Display for PlanNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// The tables of a query, by alias, in the order of the FROM clause.
+struct Scope {
+    tables: Vec<(String, u64, TupleDesc)>,
+}
+
+impl Scope {
+    /// Finds the table of a column, which must be unique if not given.
+    fn resolve(&self, column: &ColumnRef) -> DbResult<Column> {
+        let mut matches = self.tables.iter().filter(|(alias, _, td)| {
+            column.table.as_ref().is_none_or(|t| t == alias)
+                && td.field_name_to_index(column.column.clone()).is_some()
+        });
+        let (alias, _, td) = match (matches.next(), matches.next()) {
+            (Some(table), None) => table,
+            (Some(_), Some(_)) => {
+                return Err(DbError::Parse(format!("column {} is ambiguous", column)))
+            }
+            (None, _) => {
+                return Err(match &column.table {
+                    Some(t) if !self.tables.iter().any(|(alias, _, _)| alias == t) => {
+                        DbError::Parse(format!("unknown table {}", t))
+                    }
+                    _ => DbError::Parse(format!("unknown column {}", column)),
+                })
+            }
+        };
+        let index = td.field_name_to_index(column.column.clone()).unwrap();
+        Ok(Column {
+            alias: alias.clone(),
+            field: column.column.clone(),
+            ty: td.get_field_type(index).unwrap(),
+        })
+    }
+}
+
+/**
+ * Planner turns SQL statements into trees of operators. The joins of a
+ * query are ordered by the JoinOptimizer, using statistics of the tables
+ * that are computed the first time a table is joined and kept until
+ * refresh_statistics is called.
+ */
+pub struct Planner {
+    db: Arc<Database>,
+    stats: HashMap<String, TableStats>,
+}
+
+impl Planner {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            stats: HashMap::new(),
+        }
+    }
+
+    /// Forgets the statistics of the tables, e.g. after they were modified.
+    pub fn refresh_statistics(&mut self) {
+        self.stats.clear();
+    }
+
+    /**
+     * Parses a statement and returns the operators running it as a part of
+     * transaction `tid`. INSERT and DELETE return a single tuple holding
+     * the number of affected rows.
+     */
+    pub fn plan(&mut self, tid: TransactionId, sql: &str) -> DbResult<Box<dyn OpIterator>> {
+        self.plan_statement(tid, &parse(sql)?)
+    }
+
+    pub fn plan_statement(
+        &mut self,
+        tid: TransactionId,
+        statement: &Statement,
+    ) -> DbResult<Box<dyn OpIterator>> {
+        Ok(self.build(tid, statement)?.0)
+    }
+
+    /**
+     * Parses a statement and describes the operators that would run it,
+     * one per line, with the inputs of each operator indented below it.
+     */
+    pub fn explain(&mut self, tid: TransactionId, sql: &str) -> DbResult<String> {
+        Ok(self.build(tid, &parse(sql)?)?.1.to_string())
+    }
+
+    fn build(&mut self, tid: TransactionId, statement: &Statement) -> DbResult<Planned> {
+        match statement {
+            Statement::Select(select) => self.plan_select(tid, select),
+            Statement::Insert { table, source } => {
+                let table_id = self.table_id(table)?;
+                let (child, node): Planned = match source {
+                    InsertSource::Values(rows) => {
+                        let td = self.db.get_catalog().get_tuple_desc(table_id)?;
+                        let tuples = rows
+                            .iter()
+                            .map(|row| literal_tuple(table, &td, row))
+                            .collect::<DbResult<Vec<Tuple>>>()?;
+                        let label = format!("Values ({} rows)", tuples.len());
+                        (
+                            Box::new(TupleIterator::new(td, tuples)?),
+                            PlanNode::new(label, vec![]),
+                        )
+                    }
+                    InsertSource::Select(select) => self.plan_select(tid, select)?,
+                };
+                Ok((
+                    Box::new(Insert::new(self.db.clone(), tid, child, table_id)?),
+                    PlanNode::new(format!("Insert INTO {}", table), vec![node]),
+                ))
+            }
+            Statement::Delete { table, conditions } => {
+                let table_id = self.table_id(table)?;
+                let scope = self.scope(&[(table.clone(), table_id)])?;
+                let (filters, joins) = self.split_conditions(&scope, conditions)?;
+                assert!(joins.is_empty(), "a single table has no joins");
+                let (child, node) = self.scan(tid, table, table_id, filters.get(table))?;
+                Ok((
+                    Box::new(Delete::new(self.db.clone(), tid, child, table_id)?),
+                    PlanNode::new(format!("Delete FROM {}", table), vec![node]),
+                ))
+            }
+        }
+    }
+
+    fn plan_select(&mut self, tid: TransactionId, select: &Select) -> DbResult<Planned> {
+        let mut tables = Vec::with_capacity(select.from.len());
+        for table in select.from.iter() {
+            if tables.iter().any(|(alias, _)| *alias == table.alias) {
+                return Err(DbError::Parse(format!(
+                    "table alias {} is used twice",
+                    table.alias
+                )));
+            }
+            tables.push((table.alias.clone(), self.table_id(&table.name)?));
+        }
+        let scope = self.scope(&tables)?;
+        let (filters, joins) = self.split_conditions(&scope, &select.conditions)?;
+
+        let (mut op, mut node) = if joins.is_empty() {
+            if tables.len() > 1 {
+                return Err(DbError::Parse(
+                    "the tables in FROM must be joined by conditions in WHERE".to_string(),
+                ));
+            }
+            let (alias, table_id) = &tables[0];
+            self.scan(tid, alias, *table_id, filters.get(alias))?
+        } else {
+            self.plan_joins(tid, &scope, &filters, joins)?
+        };
+
+        // the fields each item of the SELECT list is read from, and their names
+        let aggregates: Vec<&SelectItem> = select
+            .items
+            .iter()
+            .filter(|item| matches!(item, SelectItem::Aggregate { .. }))
+            .collect();
+        let mut outputs = Vec::new();
+        match (aggregates.as_slice(), &select.group_by) {
+            ([], None) => {
+                let td = op.get_tuple_desc();
+                for item in select.items.iter() {
+                    match item {
+                        SelectItem::Star => {
+                            for (alias, _, table_td) in scope.tables.iter() {
+                                for i in 0..table_td.num_fields() {
+                                    let name = format!(
+                                        "{}.{}",
+                                        alias,
+                                        table_td.get_field_name(i).unwrap()
+                                    );
+                                    outputs.push((index_of(td, &name)?, name));
+                                }
+                            }
+                        }
+                        SelectItem::Column { column, alias } => {
+                            let name = scope.resolve(column)?.name();
+                            let index = index_of(td, &name)?;
+                            outputs.push((index, alias.clone().unwrap_or(name)));
+                        }
+                        SelectItem::Aggregate { .. } => unreachable!(),
+                    }
+                }
+            }
+            ([], Some(_)) => {
+                return Err(DbError::Parse(
+                    "GROUP BY needs an aggregate in the SELECT list".to_string(),
+                ))
+            }
+            (
+                [SelectItem::Aggregate {
+                    op: aop, column, ..
+                }],
+                group_by,
+            ) => {
+                let group = group_by.as_ref().map(|c| scope.resolve(c)).transpose()?;
+                let afield = match column {
+                    Some(c) => index_of(op.get_tuple_desc(), &scope.resolve(c)?.name())?,
+                    None => 0,
+                };
+                let gfield = match &group {
+                    Some(g) => Some(index_of(op.get_tuple_desc(), &g.name())?),
+                    None => None,
+                };
+                let mut label = format!(
+                    "Aggregate {}({})",
+                    aop,
+                    match column {
+                        Some(_) => op
+                            .get_tuple_desc()
+                            .get_field_name(afield)
+                            .unwrap()
+                            .to_string(),
+                        None => "*".to_string(),
+                    }
+                );
+                op = Box::new(Aggregate::new(op, afield, gfield, *aop)?);
+                if let Some(g) = &group {
+                    label.push_str(&format!(" GROUP BY {}", g.name()));
+                }
+                node = PlanNode::new(label, vec![node]);
+                for item in select.items.iter() {
+                    match item {
+                        SelectItem::Aggregate {
+                            op: aop,
+                            column,
+                            alias,
+                        } => {
+                            let name = match column {
+                                Some(c) => format!("{}({})", aop, scope.resolve(c)?.name()),
+                                None => format!("{}(*)", aop),
+                            };
+                            let index = if group.is_some() { 1 } else { 0 };
+                            outputs.push((index, alias.clone().unwrap_or(name)));
+                        }
+                        SelectItem::Column { column, alias } => {
+                            let name = scope.resolve(column)?.name();
+                            if group.as_ref().map(|g| g.name()) != Some(name.clone()) {
+                                return Err(DbError::Parse(format!(
+                                    "column {} must appear in GROUP BY",
+                                    column
+                                )));
+                            }
+                            outputs.push((0, alias.clone().unwrap_or(name)));
+                        }
+                        SelectItem::Star => {
+                            return Err(DbError::Parse(
+                                "* cannot be selected with an aggregate".to_string(),
+                            ))
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(DbError::Parse(
+                    "only one aggregate per query is supported".to_string(),
+                ))
+            }
+        }
+
+        if !select.order_by.is_empty() {
+            let mut keys = Vec::with_capacity(select.order_by.len());
+            let mut names = Vec::with_capacity(select.order_by.len());
+            for (column, ascending) in select.order_by.iter() {
+                // a name given in the SELECT list, or a column
+                let output = outputs
+                    .iter()
+                    .find(|(_, name)| column.table.is_none() && *name == column.column);
+                let index = match output {
+                    Some((index, _)) => *index,
+                    None => {
+                        let name = scope.resolve(column)?.name();
+                        op.get_tuple_desc()
+                            .field_name_to_index(name)
+                            .ok_or_else(|| {
+                                DbError::Parse(format!("cannot order by {} after grouping", column))
+                            })?
+                    }
+                };
+                keys.push(SortKey::new(index, *ascending));
+                let name = op.get_tuple_desc().get_field_name(index).unwrap();
+                names.push(format!("{}{}", name, if *ascending { "" } else { " DESC" }));
+            }
+            op = Box::new(OrderBy::new(keys, op)?);
+            node = PlanNode::new(format!("OrderBy {}", names.join(", ")), vec![node]);
+        }
+
+        let (fields, names): (Vec<usize>, Vec<String>) = outputs.into_iter().unzip();
+        let label = format!("Project {}", names.join(", "));
+        Ok((
+            Box::new(Project::with_names(fields, names, op)?),
+            PlanNode::new(label, vec![node]),
+        ))
+    }
+
+    /// Orders the joins and builds the operators running them.
+    fn plan_joins(
+        &mut self,
+        tid: TransactionId,
+        scope: &Scope,
+        filters: &Filters,
+        joins: Vec<LogicalJoinNode>,
+    ) -> DbResult<Planned> {
+        let mut aliases = HashMap::new();
+        let mut selectivities = HashMap::new();
+        for (alias, table_id, td) in scope.tables.iter() {
+            let name = self.db.get_catalog().get_table_name(*table_id)?;
+            if !self.stats.contains_key(&name) {
+                let stats = TableStats::new(self.db.clone(), *table_id, IO_COST_PER_PAGE)?;
+                self.stats.insert(name.clone(), stats);
+            }
+            let stats = &self.stats[&name];
+            let selectivity = filters.get(alias).map_or(1.0, |filters| {
+                filters
+                    .iter()
+                    .map(|f| {
+                        let field = td.field_name_to_index(f.field.clone()).unwrap();
+                        stats.estimate_selectivity(field, f.op, f.value.as_ref())
+                    })
+                    .product()
+            });
+            aliases.insert(alias.clone(), *table_id);
+            selectivities.insert(alias.clone(), selectivity);
+        }
+
+        let optimizer = JoinOptimizer::new(self.db.clone(), aliases.clone(), joins);
+        let plan = optimizer.order_joins(&self.stats, &selectivities)?;
+        if let Some((alias, _, _)) = scope.tables.iter().find(|(a, _, _)| !plan.contains(a)) {
+            return Err(DbError::Parse(format!(
+                "table {} is not joined to the other tables",
+                alias
+            )));
+        }
+        let mut scans = HashMap::new();
+        let op = plan.instantiate(&mut |alias| {
+            let (op, node) = self.scan(tid, alias, aliases[alias], filters.get(alias))?;
+            scans.insert(alias.to_string(), node);
+            Ok(op)
+        })?;
+        Ok((op, PlanNode::from_join_plan(&plan, &mut scans)))
+    }
+
+    /**
+     * Builds the operator reading a table and applying the filters on it.
+     * An index of the table is used for the first filter it can answer.
+     */
+    fn scan(
+        &self,
+        tid: TransactionId,
+        alias: &str,
+        table_id: u64,
+        filters: Option<&Vec<LogicalFilter>>,
+    ) -> DbResult<Planned> {
+        let td = self.db.get_catalog().get_tuple_desc(table_id)?;
+        let table = self.db.get_catalog().get_table_name(table_id)?;
+        let describe = |p: &Predicate| {
+            let name = td.get_field_name(p.get_field()).unwrap();
+            format!(
+                "{}.{} {} {}",
+                alias,
+                name,
+                p.get_op(),
+                p.get_operand().to_string()
+            )
+        };
+        let mut preds: Vec<Predicate> = filters
+            .map(|filters| filters.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|f| {
+                let field = td.field_name_to_index(f.field.clone()).unwrap();
+                Predicate::new(field, f.op, f.value.clone_box())
+            })
+            .collect();
+        let index = preds.iter().position(|p| {
+            !matches!(p.get_op(), Op::NotEquals | Op::Like)
+                && IndexScan::can_scan(&self.db, table_id, p)
+        });
+        let (mut op, mut node): Planned = match index {
+            Some(i) => {
+                let pred = preds.remove(i);
+                let label = format!("IndexScan {} AS {} WHERE {}", table, alias, describe(&pred));
+                (
+                    Box::new(IndexScan::new(
+                        self.db.clone(),
+                        tid,
+                        table_id,
+                        alias,
+                        &pred,
+                    )?),
+                    PlanNode::new(label, vec![]),
+                )
+            }
+            None => (
+                Box::new(SeqScan::new(self.db.clone(), tid, table_id, alias)?),
+                PlanNode::new(format!("SeqScan {} AS {}", table, alias), vec![]),
+            ),
+        };
+        for pred in preds {
+            node = PlanNode::new(format!("Filter {}", describe(&pred)), vec![node]);
+            op = Box::new(Filter::new(pred, op));
+        }
+        Ok((op, node))
+    }
+
+    /**
+     * Sorts the conditions of a WHERE clause into filters on a table, by
+     * alias, and joins of two tables.
+     */
+    fn split_conditions(
+        &self,
+        scope: &Scope,
+        conditions: &[Condition],
+    ) -> DbResult<(Filters, Vec<LogicalJoinNode>)> {
+        let mut filters = Filters::new();
+        let mut joins = Vec::new();
+        for c in conditions {
+            match (&c.left, &c.right) {
+                (Operand::Column(left), Operand::Column(right)) => {
+                    let (left, right) = (scope.resolve(left)?, scope.resolve(right)?);
+                    if left.ty != right.ty {
+                        return Err(DbError::Parse(format!(
+                            "type mismatch: {} is {} but {} is {}",
+                            left.name(),
+                            left.ty,
+                            right.name(),
+                            right.ty
+                        )));
+                    }
+                    if left.alias == right.alias {
+                        return Err(DbError::Parse(format!(
+                            "comparing two columns of table {} is not supported",
+                            left.alias
+                        )));
+                    }
+                    joins.push(LogicalJoinNode::new(
+                        &left.alias,
+                        &left.field,
+                        c.op,
+                        &right.alias,
+                        &right.field,
+                    ));
+                }
+                (Operand::Column(column), Operand::Literal(value)) => {
+                    let column = scope.resolve(column)?;
+                    let filter = LogicalFilter {
+                        value: literal_field(&column.name(), column.ty, value)?,
+                        field: column.field,
+                        op: c.op,
+                    };
+                    filters.entry(column.alias).or_default().push(filter);
+                }
+                (Operand::Literal(value), Operand::Column(column)) => {
+                    let column = scope.resolve(column)?;
+                    let op = match c.op {
+                        Op::GreaterThan => Op::LessThan,
+                        Op::GreaterThanOrEq => Op::LessThanOrEq,
+                        Op::LessThan => Op::GreaterThan,
+                        Op::LessThanOrEq => Op::GreaterThanOrEq,
+                        Op::Like => {
+                            return Err(DbError::Parse(
+                                "LIKE needs a column on its left".to_string(),
+                            ))
+                        }
+                        op => op,
+                    };
+                    let filter = LogicalFilter {
+                        value: literal_field(&column.name(), column.ty, value)?,
+                        field: column.field,
+                        op,
+                    };
+                    filters.entry(column.alias).or_default().push(filter);
+                }
+                (Operand::Literal(_), Operand::Literal(_)) => {
+                    return Err(DbError::Parse(
+                        "comparing two constants is not supported".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok((filters, joins))
+    }
+
+    fn scope(&self, tables: &[(String, u64)]) -> DbResult<Scope> {
+        let tables = tables
+            .iter()
+            .map(|(alias, id)| {
+                Ok((
+                    alias.clone(),
+                    *id,
+                    self.db.get_catalog().get_tuple_desc(*id)?,
+                ))
+            })
+            .collect::<DbResult<_>>()?;
+        Ok(Scope { tables })
+    }
+
+    fn table_id(&self, name: &str) -> DbResult<u64> {
+        self.db
+            .get_catalog()
+            .get_table_id(name)
+            .map_err(|_| DbError::Parse(format!("unknown table {}", name)))
+    }
+}
+
+/// Returns the index of the field called `name` in `td`.
+fn index_of(td: &TupleDesc, name: &str) -> DbResult<usize> {
+    td.field_name_to_index(name.to_string())
+        .ok_or_else(|| DbError::NoSuchElement(format!("field {}", name)))
+}
+
+/// Converts a constant compared to, or stored into, a column of type `ty`.
+fn literal_field(column: &str, ty: Type, value: &Literal) -> DbResult<Box<dyn Field>> {
+    match (ty, value) {
+        (Type::Int, Literal::Int(v)) => Ok(Box::new(IntField::new(*v))),
+        (Type::Str, Literal::Str(s)) => {
+            Ok(Box::new(StringField::new(s.clone(), STRING_LEN as usize)))
+        }
+        _ => Err(DbError::Parse(format!(
+            "type mismatch: {} is {} but {} is not",
+            column, ty, value
+        ))),
+    }
+}
+
+/// Builds a tuple of table `table` from the values of an INSERT.
+fn literal_tuple(table: &str, td: &TupleDesc, row: &[Literal]) -> DbResult<Tuple> {
+    if row.len() != td.num_fields() {
+        return Err(DbError::Parse(format!(
+            "table {} has {} columns but {} values were given",
+            table,
+            td.num_fields(),
+            row.len()
+        )));
+    }
+    let mut t = Tuple::new(td.clone());
+    for (i, value) in row.iter().enumerate() {
+        let column = format!("{}.{}", table, td.get_field_name(i).unwrap());
+        t.set_field(
+            i,
+            literal_field(&column, td.get_field_type(i).unwrap(), value)?,
+        );
+    }
+    Ok(t)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::index::btree_file::BTreeFile;
+    use crate::storage::db_file::DbFile;
+    use crate::test_util::{create_table_with, int_tuple, int_tuple_desc, TempDir};
+
+    fn string(s: &str) -> Box<dyn Field> {
+        Box::new(StringField::new(s.to_string(), STRING_LEN as usize))
+    }
+
+    /**
+     * Creates `emp (id, name, dept)` holding employees 0 to 5 of department
+     * `id % 3`, and `dept (id, name)` holding departments eng, ops and hr.
+     */
+    fn create_tables(db: &Database, dir: &TempDir) {
+        let td = TupleDesc::new(
+            vec![Type::Int, Type::Str, Type::Int],
+            vec!["id", "name", "dept"],
+        );
+        let tuples = (0..6)
+            .map(|i| {
+                let mut t = Tuple::new(td.clone());
+                t.set_field(0, Box::new(IntField::new(i)));
+                t.set_field(1, string(&format!("e{}", i)));
+                t.set_field(2, Box::new(IntField::new(i % 3)));
+                t
+            })
+            .collect();
+        create_table_with(db, dir, "emp", td, tuples);
+
+        let td = TupleDesc::new(vec![Type::Int, Type::Str], vec!["id", "name"]);
+        let tuples = ["eng", "ops", "hr"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut t = Tuple::new(td.clone());
+                t.set_field(0, Box::new(IntField::new(i as i32)));
+                t.set_field(1, string(name));
+                t
+            })
+            .collect();
+        create_table_with(db, dir, "dept", td, tuples);
+    }
+
+    /// Runs a statement in a transaction of its own, returning the column names and rows.
+    fn run(planner: &mut Planner, sql: &str) -> DbResult<(Vec<String>, Vec<Vec<String>>)> {
+        let tid = TransactionId::new();
+        let mut op = planner.plan(tid, sql)?;
+        let td = op.get_tuple_desc();
+        let names = (0..td.num_fields())
+            .map(|i| td.get_field_name(i).unwrap().to_string())
+            .collect();
+        op.open()?;
+        let mut rows = Vec::new();
+        while op.has_next()? {
+            rows.push(op.next()?.get_fields().map(|f| f.to_string()).collect());
+        }
+        op.close();
+        planner
+            .db
+            .get_buffer_pool()
+            .transaction_complete(tid, true)?;
+        Ok((names, rows))
+    }
+
+    fn rows(planner: &mut Planner, sql: &str) -> Vec<String> {
+        let (_, rows) = run(planner, sql).unwrap();
+        rows.into_iter().map(|row| row.join(" ")).collect()
+    }
+
+    fn error(planner: &mut Planner, sql: &str) -> String {
+        match planner.plan(TransactionId::new(), sql) {
+            Err(DbError::Parse(msg)) => msg,
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("{} should fail", sql),
+        }
+    }
+
+    fn setup(name: &str) -> (TempDir, Planner) {
+        let dir = TempDir::new(name);
+        let db = Arc::new(Database::new(50));
+        create_tables(&db, &dir);
+        (dir, Planner::new(db))
+    }
+
+    #[test]
+    fn test_select() {
+        let (_dir, mut planner) = setup("planner_select");
+        let (names, _) = run(&mut planner, "SELECT * FROM dept").unwrap();
+        assert_eq!(vec!["dept.id", "dept.name"], names);
+
+        let sql = "SELECT name AS n, e.id FROM emp e WHERE dept <> 1 AND 1 < id ORDER BY e.id DESC";
+        let (names, _) = run(&mut planner, sql).unwrap();
+        assert_eq!(vec!["n", "e.id"], names);
+        assert_eq!(vec!["e5 5", "e3 3", "e2 2"], rows(&mut planner, sql));
+
+        let sql = "SELECT id FROM emp WHERE name LIKE '3' OR id = 1";
+        assert!(planner.plan(TransactionId::new(), sql).is_err());
+        let sql = "SELECT id FROM emp WHERE name LIKE '3'";
+        assert_eq!(vec!["3"], rows(&mut planner, sql));
+        let sql = "SELECT id FROM emp WHERE name >= 'e4' ORDER BY id";
+        assert_eq!(vec!["4", "5"], rows(&mut planner, sql));
+    }
+
+    #[test]
+    fn test_join_and_aggregate() {
+        let (_dir, mut planner) = setup("planner_join");
+        let sql = "SELECT d.name, e.name FROM emp e, dept d \
+                   WHERE e.dept = d.id AND d.name = 'ops' ORDER BY e.name";
+        assert_eq!(vec!["ops e1", "ops e4"], rows(&mut planner, sql));
+        let sql = "SELECT e.name FROM emp e, dept d WHERE e.dept = d.id AND e.id = d.id \
+                   ORDER BY e.name";
+        assert_eq!(vec!["e0", "e1", "e2"], rows(&mut planner, sql));
+
+        let sql = "SELECT d.name, COUNT(*) AS c FROM emp e, dept d \
+                   WHERE e.dept = d.id AND e.id > 0 GROUP BY d.name ORDER BY c DESC, d.name";
+        let (names, _) = run(&mut planner, sql).unwrap();
+        assert_eq!(vec!["d.name", "c"], names);
+        assert_eq!(vec!["hr 2", "ops 2", "eng 1"], rows(&mut planner, sql));
+
+        let (names, _) = run(&mut planner, "SELECT MAX(emp.id) FROM emp").unwrap();
+        assert_eq!(vec!["MAX(emp.id)"], names);
+        assert_eq!(vec!["15"], rows(&mut planner, "SELECT SUM(id) FROM emp"));
+        assert_eq!(vec!["6"], rows(&mut planner, "SELECT COUNT(*) FROM emp"));
+        let sql = "SELECT COUNT(*) FROM emp WHERE id > 100";
+        assert_eq!(vec!["0"], rows(&mut planner, sql));
+        let sql = "SELECT MAX(id) FROM emp WHERE id > 100";
+        assert!(rows(&mut planner, sql).is_empty());
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        let (_dir, mut planner) = setup("planner_insert");
+        let sql = "INSERT INTO dept VALUES (3, 'qa'), (4, 'it''s')";
+        assert_eq!(vec!["2"], rows(&mut planner, sql));
+        let sql = "SELECT name FROM dept WHERE id >= 3 ORDER BY id";
+        assert_eq!(vec!["qa", "it's"], rows(&mut planner, sql));
+
+        let sql = "INSERT INTO dept SELECT id, name FROM emp WHERE dept = 2";
+        assert_eq!(vec!["2"], rows(&mut planner, sql));
+        assert_eq!(vec!["7"], rows(&mut planner, "SELECT COUNT(id) FROM dept"));
+
+        assert_eq!(
+            vec!["3"],
+            rows(&mut planner, "DELETE FROM dept WHERE id >= 3;")
+        );
+        assert_eq!(
+            vec!["2"],
+            rows(&mut planner, "DELETE FROM dept WHERE name LIKE 'e'")
+        );
+        let sql = "SELECT name FROM dept ORDER BY name";
+        assert_eq!(vec!["hr", "ops"], rows(&mut planner, sql));
+        assert_eq!(vec!["2"], rows(&mut planner, "DELETE FROM dept"));
+    }
+
+    #[test]
+    fn test_insert_select_same_table() {
+        let (_dir, mut planner) = setup("planner_insert_self");
+        for count in [6, 12, 24, 48, 96, 192, 384, 768] {
+            let sql = "INSERT INTO emp SELECT * FROM emp";
+            assert_eq!(vec![count.to_string()], rows(&mut planner, sql));
+            let sql = "SELECT COUNT(*) FROM emp";
+            assert_eq!(vec![(count * 2).to_string()], rows(&mut planner, sql));
+        }
+        let sql = "SELECT COUNT(*) FROM emp WHERE id = 5";
+        assert_eq!(vec!["256"], rows(&mut planner, sql));
+    }
+
+    #[test]
+    fn test_long_string_literal() {
+        let (_dir, mut planner) = setup("planner_long_literal");
+        // 'x' followed by two-byte characters, so STRING_LEN falls in the
+        // middle of one
+        let long = format!("x{}", "\u{e9}".repeat(STRING_LEN as usize));
+        let sql = format!("INSERT INTO dept VALUES (3, '{}')", long);
+        assert_eq!(vec!["1"], rows(&mut planner, &sql));
+        let sql = format!("SELECT name FROM dept WHERE name = '{}'", long);
+        let found = rows(&mut planner, &sql);
+        assert_eq!(1, found.len());
+        assert_eq!(STRING_LEN as usize - 1, found[0].len());
+        assert!(long.starts_with(&found[0]));
+    }
+
+    #[test]
+    fn test_index() {
+        let (dir, mut planner) = setup("planner_index");
+        let f = BTreeFile::new(dir.path().join("idx.dat"), int_tuple_desc(2), 0).unwrap();
+        let file: Arc<dyn DbFile> = Arc::new(f);
+        planner
+            .db
+            .get_catalog()
+            .add_table(file.clone(), "idx", None);
+        let tid = TransactionId::new();
+        for i in 0..100 {
+            let t = int_tuple(&[(i * 7) % 100, i]);
+            planner
+                .db
+                .get_buffer_pool()
+                .insert_tuple(tid, &file, t)
+                .unwrap();
+        }
+        planner
+            .db
+            .get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+
+        let sql = "SELECT field0 FROM idx WHERE field0 < 5 AND field1 > 0";
+        assert_eq!(vec!["1", "2", "3", "4"], rows(&mut planner, sql));
+        let sql =
+            "SELECT i.field0, e.name FROM idx i, emp e WHERE i.field0 = e.id AND i.field0 <= 1";
+        let mut joined = rows(&mut planner, sql);
+        joined.sort();
+        assert_eq!(vec!["0 e0", "1 e1"], joined);
+    }
+
+    #[test]
+    fn test_explain() {
+        let (_dir, mut planner) = setup("planner_explain");
+        let sql = "SELECT dept, COUNT(id) FROM emp WHERE id > 1 AND name <> 'e3' \
+                   GROUP BY dept ORDER BY dept DESC";
+        let plan = planner.explain(TransactionId::new(), sql).unwrap();
+        assert_eq!(
+            "Project emp.dept, COUNT(emp.id)\n\
+             \x20 OrderBy emp.dept DESC\n\
+             \x20   Aggregate COUNT(emp.id) GROUP BY emp.dept\n\
+             \x20     Filter emp.name <> e3\n\
+             \x20       Filter emp.id > 1\n\
+             \x20         SeqScan emp AS emp\n",
+            plan
+        );
+
+        let sql = "SELECT * FROM emp e, dept d WHERE e.dept = d.id";
+        let plan = planner.explain(TransactionId::new(), sql).unwrap();
+        let lines: Vec<&str> = plan.lines().collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("Project e.id, e.name, e.dept, d.id, d.name", lines[0]);
+        assert!(lines[1].starts_with("  HashJoin "));
+        assert!(lines.contains(&"    SeqScan emp AS e"));
+        assert!(lines.contains(&"    SeqScan dept AS d"));
+
+        let sql = "DELETE FROM dept WHERE id = 1";
+        let plan = planner.explain(TransactionId::new(), sql).unwrap();
+        assert_eq!(
+            "Delete FROM dept\n  Filter dept.id = 1\n    SeqScan dept AS dept\n",
+            plan
+        );
+        let sql = "INSERT INTO dept VALUES (3, 'qa'), (4, 'it')";
+        let plan = planner.explain(TransactionId::new(), sql).unwrap();
+        assert_eq!("Insert INTO dept\n  Values (2 rows)\n", plan);
+    }
+
+    #[test]
+    fn test_errors() {
+        let (_dir, mut planner) = setup("planner_errors");
+        let mut error = |sql: &str| error(&mut planner, sql);
+        assert_eq!("unknown table nope", error("SELECT * FROM nope"));
+        assert_eq!("unknown table x", error("SELECT x.id FROM emp"));
+        assert_eq!("unknown column salary", error("SELECT salary FROM emp"));
+        assert_eq!(
+            "unknown column e.salary",
+            error("SELECT e.salary FROM emp e")
+        );
+        assert_eq!(
+            "column id is ambiguous",
+            error("SELECT id FROM emp, dept WHERE emp.dept = dept.id")
+        );
+        assert_eq!(
+            "type mismatch: emp.id is Int but 'x' is not",
+            error("SELECT id FROM emp WHERE id = 'x'")
+        );
+        assert_eq!(
+            "type mismatch: e.name is Str but d.id is Int",
+            error("SELECT * FROM emp e, dept d WHERE e.name = d.id")
+        );
+        assert_eq!(
+            "type mismatch: dept.name is Str but 2 is not",
+            error("INSERT INTO dept VALUES (1, 2)")
+        );
+        assert_eq!(
+            "table dept has 2 columns but 1 values were given",
+            error("INSERT INTO dept VALUES (1)")
+        );
+        assert_eq!(
+            "the tables in FROM must be joined by conditions in WHERE",
+            error("SELECT * FROM emp, dept")
+        );
+        assert_eq!(
+            "table t is not joined to the other tables",
+            error("SELECT * FROM emp e, dept d, dept t WHERE e.dept = d.id")
+        );
+        assert_eq!(
+            "table alias e is used twice",
+            error("SELECT * FROM emp e, dept e")
+        );
+        assert_eq!(
+            "only one aggregate per query is supported",
+            error("SELECT MIN(id), MAX(id) FROM emp")
+        );
+        assert_eq!(
+            "GROUP BY needs an aggregate in the SELECT list",
+            error("SELECT dept FROM emp GROUP BY dept")
+        );
+        assert_eq!(
+            "column name must appear in GROUP BY",
+            error("SELECT name, COUNT(id) FROM emp GROUP BY dept")
+        );
+        assert_eq!(
+            "cannot order by name after grouping",
+            error("SELECT dept, COUNT(id) FROM emp GROUP BY dept ORDER BY name")
+        );
+        assert_eq!(
+            "comparing two columns of table emp is not supported",
+            error("SELECT * FROM emp WHERE id = dept")
+        );
+        assert!(error("SELECT FROM emp").contains("expected a name"));
+    }
+}
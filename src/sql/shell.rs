@@ -0,0 +1,385 @@
+use super::ast::Statement;
+use super::parser::parse;
+use super::planner::Planner;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::ty::Type;
+use crate::transaction::transaction_id::TransactionId;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+/**
+ * Shell runs the statements and commands typed into the `simple_db` binary,
+ * writing their results to an output.
+ *
+ * Statements end with `;` and may span several lines. Each statement runs in
+ * a transaction of its own, which commits if the statement succeeds and
+ * aborts otherwise. The statistics the joins are planned with are refreshed
+ * after every INSERT or DELETE. A statement prefixed with `explain` prints its plan
+ * instead of running. Lines starting with `\` are commands:
+ *
+ * - `\d` lists the tables, and `\d table` shows the schema of a table.
+ * - `\i file` runs the statements in a file.
+ * - `\q` quits.
+ */
+pub struct Shell {
+    db: Arc<Database>,
+    planner: Planner,
+    /// The lines of a statement whose `;` has not been read yet.
+    buffer: String,
+}
+
+impl Shell {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            planner: Planner::new(db.clone()),
+            db,
+            buffer: String::new(),
+        }
+    }
+
+    /// Returns whether the shell holds part of a statement.
+    pub fn in_statement(&self) -> bool {
+        !self.buffer.trim().is_empty()
+    }
+
+    /**
+     * Reads a line of input, running the statements it completes. Errors of
+     * statements and commands are written to the output, so this only fails
+     * if the output cannot be written. Returns false once the user quits.
+     */
+    pub fn read_line(&mut self, line: &str, out: &mut dyn Write) -> DbResult<bool> {
+        if !self.in_statement() && line.trim_start().starts_with('\\') {
+            return self.command(line.trim(), out);
+        }
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+        let (statements, rest) = split_statements(&self.buffer);
+        self.buffer = rest;
+        for sql in statements {
+            self.execute(&sql, out)?;
+        }
+        Ok(true)
+    }
+
+    /// Runs the statements and commands in a file, as if they were typed in.
+    pub fn run_script<P: AsRef<Path>>(&mut self, path: P, out: &mut dyn Write) -> DbResult<bool> {
+        let script = fs::read_to_string(path)?;
+        for line in script.lines() {
+            if !self.read_line(line, out)? {
+                return Ok(false);
+            }
+        }
+        if self.in_statement() {
+            let sql = std::mem::take(&mut self.buffer);
+            writeln!(out, "error: statement is missing a ;: {}", sql.trim())?;
+        }
+        Ok(true)
+    }
+
+    fn command(&mut self, line: &str, out: &mut dyn Write) -> DbResult<bool> {
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let result = match args.as_slice() {
+            ["\\q"] => Ok(false),
+            ["\\d"] => self.list_tables(out).map(|_| true),
+            ["\\d", table] => self.describe_table(table, out).map(|_| true),
+            ["\\i", file] => self.run_script(file, out),
+            _ => Err(DbError::Parse(format!("unknown command {}", line))),
+        };
+        result.or_else(|e| {
+            writeln!(out, "error: {}", e)?;
+            Ok(true)
+        })
+    }
+
+    fn list_tables(&self, out: &mut dyn Write) -> DbResult<()> {
+        let catalog = self.db.get_catalog();
+        let mut rows = Vec::new();
+        for id in catalog.table_ids() {
+            let columns = catalog.get_tuple_desc(id)?.num_fields();
+            rows.push(vec![catalog.get_table_name(id)?, columns.to_string()]);
+        }
+        let names = ["table".to_string(), "columns".to_string()];
+        write!(
+            out,
+            "{}",
+            format_table(&names, &[Type::Str, Type::Int], &rows)
+        )?;
+        Ok(())
+    }
+
+    fn describe_table(&self, table: &str, out: &mut dyn Write) -> DbResult<()> {
+        let catalog = self.db.get_catalog();
+        let id = catalog
+            .get_table_id(table)
+            .map_err(|_| DbError::Parse(format!("unknown table {}", table)))?;
+        let td = catalog.get_tuple_desc(id)?;
+        let pkey = catalog.get_primary_key(id)?;
+        let rows: Vec<Vec<String>> = (0..td.num_fields())
+            .map(|i| {
+                let name = td.get_field_name(i).unwrap();
+                let key = if pkey.as_deref() == Some(name) {
+                    "pk"
+                } else {
+                    ""
+                };
+                let ty = td.get_field_type(i).unwrap().to_string();
+                vec![name.to_string(), ty, key.to_string()]
+            })
+            .collect();
+        let names = ["column", "type", "key"].map(|s| s.to_string());
+        write!(out, "{}", format_table(&names, &[Type::Str; 3], &rows))?;
+        Ok(())
+    }
+
+    /**
+     * Runs a statement, without its `;`, in a transaction of its own. Its
+     * rows, or the error that aborted it, are written to the output.
+     */
+    fn execute(&mut self, sql: &str, out: &mut dyn Write) -> DbResult<()> {
+        let tid = TransactionId::new();
+        let start = Instant::now();
+        let result = match strip_explain(sql) {
+            Some(sql) => self
+                .planner
+                .explain(tid, sql)
+                .map(|plan| (plan.into_bytes(), false)),
+            None => self.query(tid, sql),
+        };
+        let elapsed = start.elapsed();
+        let pool = self.db.get_buffer_pool();
+        let result = match result {
+            Ok((output, modified)) => pool.transaction_complete(tid, true).map(|_| {
+                if modified {
+                    self.planner.refresh_statistics();
+                }
+                output
+            }),
+            Err(e) => pool.transaction_complete(tid, false).and(Err(e)),
+        };
+        match result {
+            Ok(output) => {
+                out.write_all(&output)?;
+                writeln!(out, "Time: {:.3} ms", elapsed.as_secs_f64() * 1000.0)?;
+            }
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+        Ok(())
+    }
+
+    /**
+     * Runs a statement, returning its rows formatted as a table, and whether
+     * it may have modified a table.
+     */
+    fn query(&mut self, tid: TransactionId, sql: &str) -> DbResult<(Vec<u8>, bool)> {
+        let statement = parse(sql)?;
+        let modified = !matches!(statement, Statement::Select(_));
+        let mut op = self.planner.plan_statement(tid, &statement)?;
+        let td = op.get_tuple_desc().clone();
+        op.open()?;
+        let mut rows = Vec::new();
+        while op.has_next()? {
+            rows.push(op.next()?.get_fields().map(|f| f.to_string()).collect());
+        }
+        op.close();
+
+        let names: Vec<String> = (0..td.num_fields())
+            .map(|i| td.get_field_name(i).unwrap().to_string())
+            .collect();
+        let types: Vec<Type> = (0..td.num_fields())
+            .map(|i| td.get_field_type(i).unwrap())
+            .collect();
+        let mut output = format_table(&names, &types, &rows).into_bytes();
+        let plural = if rows.len() == 1 { "" } else { "s" };
+        writeln!(output, "({} row{})", rows.len(), plural)?;
+        Ok((output, modified))
+    }
+}
+
+/// Returns the statement following an `explain` keyword, if there is one.
+fn strip_explain(sql: &str) -> Option<&str> {
+    let sql = sql.trim_start();
+    let keyword = sql.get(..7)?;
+    let rest = &sql[7..];
+    if keyword.eq_ignore_ascii_case("explain") && rest.starts_with(char::is_whitespace) {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
+/**
+ * Splits the complete statements off the start of the input, returning them
+ * without their `;`, followed by the remaining input. A `;` inside a quoted
+ * string does not end a statement.
+ */
+pub fn split_statements(input: &str) -> (Vec<String>, String) {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            ';' if !quoted => {
+                let sql = input[start..i].trim();
+                if !sql.is_empty() {
+                    statements.push(sql.to_string());
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let rest = input[start..].trim_start();
+    (statements, rest.to_string())
+}
+
+/**
+ * Formats rows as a table under a header of column names. Ints are aligned
+ * to the right of their column, strings to the left.
+ */
+pub fn format_table(names: &[String], types: &[Type], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = names.iter().map(|n| n.chars().count()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let line = |values: &[String], align: &dyn Fn(usize) -> bool| {
+        let cells: Vec<String> = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| match align(i) {
+                true => format!("{:>1$}", v, widths[i]),
+                false => format!("{:<1$}", v, widths[i]),
+            })
+            .collect();
+        format!(" {}\n", cells.join(" | ").trim_end())
+    };
+    let mut table = line(names, &|_| false);
+    let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+    table.push_str(&dashes.join("+"));
+    table.push('\n');
+    for row in rows {
+        table.push_str(&line(row, &|i| types[i] == Type::Int));
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{create_table, TempDir};
+
+    #[test]
+    fn test_split_statements() {
+        let (statements, rest) = split_statements("SELECT 1;\n;select ';' FROM t; DELETE");
+        assert_eq!(vec!["SELECT 1", "select ';' FROM t"], statements);
+        assert_eq!("DELETE", rest);
+        let (statements, rest) = split_statements("SELECT 'a;\n");
+        assert!(statements.is_empty());
+        assert_eq!("SELECT 'a;\n", rest);
+
+        assert_eq!(Some(" SELECT 1"), strip_explain("EXPLAIN SELECT 1"));
+        assert_eq!(None, strip_explain("explained"));
+        assert_eq!(None, strip_explain("SELECT"));
+    }
+
+    #[test]
+    fn test_format_table() {
+        let names = vec!["id".to_string(), "name".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "ann".to_string()],
+            vec!["100".to_string(), "b".to_string()],
+        ];
+        assert_eq!(
+            " id  | name\n-----+------\n   1 | ann\n 100 | b\n",
+            format_table(&names, &[Type::Int, Type::Str], &rows)
+        );
+    }
+
+    #[test]
+    fn test_shell() {
+        let dir = TempDir::new("shell");
+        let db = Arc::new(Database::new(50));
+        create_table(&db, &dir, "t", 2, &[]);
+        let mut shell = Shell::new(db);
+        let mut run = |input: &str| {
+            let mut out = Vec::new();
+            let mut running = true;
+            for line in input.lines() {
+                running = shell.read_line(line, &mut out).unwrap();
+            }
+            (running, String::from_utf8(out).unwrap())
+        };
+
+        let (_, out) = run("INSERT INTO t\nVALUES (1, 2), (30, 4);");
+        assert!(out.starts_with(" count\n-------\n     2\n(1 row)\nTime: "));
+        let (_, out) = run("SELECT * FROM t WHERE field0 > 1; SELECT nope FROM t;");
+        assert!(out.contains(" t.field0 | t.field1\n"));
+        assert!(out.contains("       30 |        4\n(1 row)\n"));
+        assert!(out.ends_with("error: parse error: unknown column nope\n"));
+        let (_, out) = run("explain SELECT * FROM t;");
+        assert!(out.starts_with("Project t.field0, t.field1\n  SeqScan t AS t\n"));
+        let (_, out) = run("\\d");
+        assert_eq!(
+            " table | columns\n-------+---------\n t     |       2\n",
+            out
+        );
+        let (_, out) = run("\\d t");
+        assert!(out.contains(" field1 | Int  |\n"));
+        let (_, out) = run("\\d nope\n\\x");
+        assert_eq!(
+            "error: parse error: unknown table nope\nerror: parse error: unknown command \\x\n",
+            out
+        );
+
+        let script = dir.path().join("script.sql");
+        fs::write(&script, "DELETE FROM t\nWHERE field0 = 1;\n\\d nope\n").unwrap();
+        let (running, out) = run(&format!("\\i {}", script.display()));
+        assert!(running);
+        assert!(out.starts_with(" count\n-------\n     1\n(1 row)\nTime: "));
+        assert!(out.ends_with("error: parse error: unknown table nope\n"));
+        let missing = dir.path().join("missing.sql");
+        let (running, out) = run(&format!("\\i {}\n\\d", missing.display()));
+        assert!(running);
+        assert!(out.starts_with("error: "));
+        assert!(out.ends_with(" t     |       2\n"));
+        assert_eq!((false, String::new()), run("\\q"));
+    }
+
+    #[test]
+    fn test_statistics_refresh() {
+        let dir = TempDir::new("shell_statistics");
+        let db = Arc::new(Database::new(50));
+        let rows: Vec<Vec<i32>> = (0..10).map(|i| vec![i, i]).collect();
+        create_table(&db, &dir, "t", 2, &rows);
+        create_table(&db, &dir, "u", 2, &rows);
+        let mut shell = Shell::new(db);
+        let mut run = |sql: &str| {
+            let mut out = Vec::new();
+            shell.read_line(sql, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let explain = "explain SELECT * FROM t, u WHERE t.field0 = u.field0;";
+
+        // the join produces a tuple for each tuple of the larger table
+        let out = run(explain);
+        assert!(out.contains("Join t.field0 = u.field0 (cost="), "{}", out);
+        assert!(out.contains(", card=10)\n"), "{}", out);
+        let values: Vec<String> = (10..30).map(|i| format!("({}, {})", i, i)).collect();
+        run(&format!("INSERT INTO t VALUES {};", values.join(", ")));
+        let out = run(explain);
+        assert!(out.contains(", card=30)\n"), "{}", out);
+        run("DELETE FROM t WHERE field0 > 4;");
+        let out = run(explain);
+        assert!(out.contains(", card=10)\n"), "{}", out);
+        run("DELETE FROM u WHERE field0 > 1;");
+        let out = run(explain);
+        assert!(out.contains(", card=5)\n"), "{}", out);
+    }
+}
@@ -0,0 +1,602 @@
+use super::db_file::DbFile;
+use super::eviction::{EvictionPolicy, LruPolicy};
+use super::log_file::{LogFile, PageImage};
+use super::page::Page;
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::{PageId, PageKey};
+use crate::common::permissions::Permissions;
+use crate::table::tuple::Tuple;
+use crate::transaction::lock_manager::LockManager;
+use crate::transaction::transaction_id::TransactionId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Default number of pages passed to the constructor.
+pub const DEFAULT_PAGES: usize = 50;
+
+/// A page cached in the BufferPool, shared by everyone using it.
+pub type PageRef = Arc<RwLock<Box<dyn Page>>>;
+
+/**
+ * Counters describing how well the BufferPool is doing.
+ */
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct PoolStats {
+    /// Page requests answered from the cache.
+    pub hits: u64,
+    /// Page requests that had to read the page from disk.
+    pub misses: u64,
+    /// Pages thrown out to make room for other pages.
+    pub evictions: u64,
+}
+
+struct PoolState {
+    pages: HashMap<PageKey, PageRef>,
+    policy: Box<dyn EvictionPolicy>,
+    stats: PoolStats,
+    /// Files of the tables whose pages were modified, to write them back.
+    files: HashMap<u64, Arc<dyn DbFile>>,
+}
+
+/**
+ * BufferPool manages the reading and writing of pages into memory from
+ * disk. Access methods call into it to retrieve pages, and it fetches
+ * pages from the appropriate location.
+ *
+ * The BufferPool caches up to `num_pages` pages; when it is full, the
+ * EvictionPolicy picks the page to throw out.
+ *
+ * The BufferPool is also responsible for locking; when a transaction
+ * fetches a page, BufferPool checks that the transaction has the
+ * appropriate locks to read/write the page.
+ *
+ * Pages modified by a transaction stay dirty in the pool until it
+ * completes: they are never evicted (NO-STEAL), and are written to disk
+ * when the transaction commits (FORCE).
+ *
+ * With a LogFile set, every page write is preceded by an UPDATE record in
+ * the log, so that pages written on behalf of a running transaction (by
+ * `flush_all_pages`) can be rolled back when it aborts.
+ */
+pub struct BufferPool {
+    num_pages: usize,
+    state: Mutex<PoolState>,
+    lock_manager: LockManager,
+    log: RwLock<Option<Arc<LogFile>>>,
+}
+
+impl BufferPool {
+    /// Creates a BufferPool that caches up to `num_pages` pages with LRU eviction.
+    pub fn new(num_pages: usize) -> Self {
+        Self::with_policy(num_pages, Box::new(LruPolicy::new()))
+    }
+
+    /// Creates a BufferPool that caches up to `num_pages` pages with the given policy.
+    pub fn with_policy(num_pages: usize, policy: Box<dyn EvictionPolicy>) -> Self {
+        Self::with_lock_manager(num_pages, policy, LockManager::new())
+    }
+
+    /**
+     * Creates a BufferPool that caches up to `num_pages` pages with the given
+     * policy, and locks pages with the given LockManager.
+     */
+    pub fn with_lock_manager(
+        num_pages: usize,
+        policy: Box<dyn EvictionPolicy>,
+        lock_manager: LockManager,
+    ) -> Self {
+        Self {
+            num_pages,
+            state: Mutex::new(PoolState {
+                pages: HashMap::new(),
+                policy,
+                stats: PoolStats::default(),
+                files: HashMap::new(),
+            }),
+            lock_manager,
+            log: RwLock::new(None),
+        }
+    }
+
+    /// Returns the maximum number of pages cached by this pool.
+    pub fn get_num_pages(&self) -> usize {
+        self.num_pages
+    }
+
+    /// Sets the write-ahead log recording the page writes of this pool.
+    pub fn set_log_file(&self, log: Arc<LogFile>) {
+        *self.log.write().unwrap() = Some(log);
+    }
+
+    pub fn get_log_file(&self) -> Option<Arc<LogFile>> {
+        self.log.read().unwrap().clone()
+    }
+
+    /**
+     * Retrieve the specified page with the associated permissions. Will
+     * acquire a lock and may block if that lock is held by another
+     * transaction; fails with TransactionAborted if waiting would deadlock.
+     *
+     * The page is looked up in the pool; if it is not present, it is read
+     * from `file` and added to the pool, evicting a clean page if the pool
+     * is full. Fails if every cached page is dirty.
+     */
+    pub fn get_page(
+        &self,
+        tid: TransactionId,
+        file: &dyn DbFile,
+        pid: &dyn PageId,
+        perm: Permissions,
+    ) -> DbResult<PageRef> {
+        let key = pid.key();
+        self.lock_manager.acquire(tid, key, perm)?;
+        let mut state = self.state.lock().unwrap();
+        if let Some(page) = state.pages.get(&key).cloned() {
+            state.stats.hits += 1;
+            state.policy.record_access(key);
+            return Ok(page);
+        }
+
+        state.stats.misses += 1;
+        if state.pages.len() >= self.num_pages {
+            Self::evict_page(&mut state)?;
+        }
+        let page = Arc::new(RwLock::new(file.read_page(pid)?));
+        state.pages.insert(key, page.clone());
+        state.policy.record_access(key);
+        Ok(page)
+    }
+
+    /**
+     * Add a tuple to the specified file on behalf of transaction `tid`,
+     * which acquires a write lock on the pages it modifies. Those pages are
+     * marked dirty and stay in the pool until the transaction completes.
+     */
+    pub fn insert_tuple(
+        &self,
+        tid: TransactionId,
+        file: &Arc<dyn DbFile>,
+        t: Tuple,
+    ) -> DbResult<()> {
+        self.register_file(file);
+        let pages = file.insert_tuple(self, tid, t)?;
+        self.mark_dirty(tid, &pages);
+        Ok(())
+    }
+
+    /**
+     * Remove the specified tuple from the file it belongs to on behalf of
+     * transaction `tid`. The pages modified by the file are marked dirty.
+     */
+    pub fn delete_tuple(
+        &self,
+        tid: TransactionId,
+        file: &Arc<dyn DbFile>,
+        t: &Tuple,
+    ) -> DbResult<()> {
+        self.register_file(file);
+        let pages = file.delete_tuple(self, tid, t)?;
+        self.mark_dirty(tid, &pages);
+        Ok(())
+    }
+
+    /**
+     * Releases the lock on a page. Calling this is very risky, and may
+     * result in wrong behavior; it breaks two-phase locking.
+     */
+    pub fn release_page(&self, tid: TransactionId, pid: &dyn PageId) {
+        self.lock_manager.release(tid, pid.key());
+    }
+
+    /// Return true if the specified transaction has a lock on the specified page.
+    pub fn holds_lock(&self, tid: TransactionId, pid: &dyn PageId) -> bool {
+        self.lock_manager.holds_lock(tid, pid.key())
+    }
+
+    /**
+     * Commit or abort a given transaction; release all locks associated
+     * with the transaction. On commit, the pages it dirtied are written to
+     * disk. On abort, they are replaced by their on-disk images, after
+     * the pages it already wrote are rolled back from the log.
+     */
+    pub fn transaction_complete(&self, tid: TransactionId, commit: bool) -> DbResult<()> {
+        let result = if commit {
+            self.commit(tid)
+        } else {
+            self.abort(tid)
+        };
+        self.lock_manager.release_all(tid);
+        result
+    }
+
+    /// Write all pages dirtied by the specified transaction to disk.
+    pub fn flush_pages(&self, tid: TransactionId) -> DbResult<()> {
+        let state = self.state.lock().unwrap();
+        for page in state.pages.values() {
+            if page.read().unwrap().is_dirty() == Some(tid) {
+                self.flush_page(&state, page)?;
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Write all dirty pages to disk. Writing the pages of running
+     * transactions breaks NO-STEAL, so this is only safe when none are.
+     */
+    pub fn flush_all_pages(&self) -> DbResult<()> {
+        let state = self.state.lock().unwrap();
+        for page in state.pages.values() {
+            if page.read().unwrap().is_dirty().is_some() {
+                self.flush_page(&state, page)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the specific page id from the buffer pool without writing it back.
+    pub fn discard_page(&self, pid: &dyn PageId) {
+        let key = pid.key();
+        let mut state = self.state.lock().unwrap();
+        if state.pages.remove(&key).is_some() {
+            state.policy.remove(key);
+        }
+    }
+
+    /// Returns true if the page is currently cached.
+    pub fn holds_page(&self, pid: &dyn PageId) -> bool {
+        self.state.lock().unwrap().pages.contains_key(&pid.key())
+    }
+
+    /// Returns a snapshot of the hit, miss and eviction counters.
+    pub fn get_stats(&self) -> PoolStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Resets the hit, miss and eviction counters to zero.
+    pub fn reset_stats(&self) {
+        self.state.lock().unwrap().stats = PoolStats::default();
+    }
+
+    /**
+     * Remembers the file of a table about to be modified. This happens
+     * before the file touches any page, as a file may dirty pages itself
+     * and then fail, e.g. a B+ tree in the middle of a split, and the
+     * pages must be restored from it when the transaction aborts.
+     */
+    fn register_file(&self, file: &Arc<dyn DbFile>) {
+        let mut state = self.state.lock().unwrap();
+        state.files.insert(file.get_id(), file.clone());
+    }
+
+    /// Marks the pages modified by `tid` dirty, and makes sure they stay in the pool.
+    fn mark_dirty(&self, tid: TransactionId, pages: &[PageRef]) {
+        let mut state = self.state.lock().unwrap();
+        for page in pages {
+            let key = {
+                let mut page = page.write().unwrap();
+                page.mark_dirty(true, tid);
+                page.get_id().key()
+            };
+            state.pages.entry(key).or_insert_with(|| page.clone());
+            state.policy.record_access(key);
+        }
+    }
+
+    /// Writes a dirty page to disk, logging the write first if there is a log.
+    fn flush_page(&self, state: &PoolState, page: &PageRef) -> DbResult<()> {
+        let mut page = page.write().unwrap();
+        let pid = page.get_id();
+        let file = state
+            .files
+            .get(&pid.get_table_id())
+            .ok_or_else(|| DbError::Db(format!("no file to flush page {:?} to", pid.key())))?;
+        let tid = page.is_dirty().unwrap();
+        if let Some(log) = self.get_log_file() {
+            log.log_write(tid, page.get_before_image().as_ref(), page.as_ref())?;
+            log.force()?;
+        }
+        file.write_page(page.as_ref())?;
+        page.mark_dirty(false, tid);
+        Ok(())
+    }
+
+    /**
+     * Writes the pages dirtied by `tid` and logs the commit. The committed
+     * contents of the pages it locked become their new before images.
+     */
+    fn commit(&self, tid: TransactionId) -> DbResult<()> {
+        self.flush_pages(tid)?;
+        if let Some(log) = self.get_log_file() {
+            log.log_commit(tid)?;
+        }
+        let state = self.state.lock().unwrap();
+        for key in self.lock_manager.locked_pages(tid) {
+            if !self.lock_manager.holds_exclusive(tid, key) {
+                continue;
+            }
+            if let Some(page) = state.pages.get(&key) {
+                page.write().unwrap().set_before_image();
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Installs the before images of the pages `tid` wrote to disk, restores
+     * the pages it only dirtied in memory, and logs the abort.
+     */
+    fn abort(&self, tid: TransactionId) -> DbResult<()> {
+        if let Some(log) = self.get_log_file() {
+            log.rollback(tid, &mut |image| self.install_image(image))?;
+            self.restore_pages(tid)?;
+            log.log_abort(tid)
+        } else {
+            self.restore_pages(tid)
+        }
+    }
+
+    /// Writes a page image from the log to disk, dropping the cached copy.
+    fn install_image(&self, image: &PageImage) -> DbResult<()> {
+        let mut state = self.state.lock().unwrap();
+        let file = state.files.get(&image.table_id).cloned().ok_or_else(|| {
+            DbError::Db(format!("no file to roll back table {} in", image.table_id))
+        })?;
+        let page = file.page_from_image(&image.pid, &image.data)?;
+        file.write_page(page.as_ref())?;
+        let key = page.get_id().key();
+        if state.pages.remove(&key).is_some() {
+            state.policy.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Replaces the pages dirtied by `tid` with the images on disk.
+    fn restore_pages(&self, tid: TransactionId) -> DbResult<()> {
+        let state = self.state.lock().unwrap();
+        for page in state.pages.values() {
+            let mut page = page.write().unwrap();
+            if page.is_dirty() != Some(tid) {
+                continue;
+            }
+            let pid = page.get_id();
+            let file = state.files.get(&pid.get_table_id()).ok_or_else(|| {
+                DbError::Db(format!("no file to restore page {:?} from", pid.key()))
+            })?;
+            *page = file.read_page(pid.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Evicts a clean page; pages being modified right now count as dirty.
+    fn evict_page(state: &mut PoolState) -> DbResult<()> {
+        let PoolState {
+            pages,
+            policy,
+            stats,
+            ..
+        } = state;
+        let mut can_evict = |key: PageKey| match pages[&key].try_read() {
+            Ok(page) => page.is_dirty().is_none(),
+            Err(_) => false,
+        };
+        match policy.evict(&mut can_evict) {
+            Some(key) => {
+                pages.remove(&key);
+                stats.evictions += 1;
+                Ok(())
+            }
+            None => Err(DbError::Db(format!(
+                "buffer pool is full: all {} cached pages are dirty",
+                pages.len()
+            ))),
+        }
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAGES)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::heap_file::HeapFile;
+    use crate::storage::heap_page::HeapPage;
+    use crate::storage::heap_page_id::HeapPageId;
+    use crate::test_util::{create_heap_file, int_tuple, TempFile};
+    use std::time::Duration;
+
+    fn get_file(tmp: &TempFile) -> HeapFile {
+        // 2000 rows of two ints fill four pages.
+        let rows: Vec<Vec<i32>> = (0..2000).map(|i| vec![i, i]).collect();
+        create_heap_file(tmp.path(), 2, &rows)
+    }
+
+    /// Returns the number of tuples on page `pgno`, as cached and as stored on disk.
+    fn count_tuples(
+        pool: &BufferPool,
+        tid: TransactionId,
+        f: &HeapFile,
+        pgno: i32,
+    ) -> (usize, usize) {
+        let pid = HeapPageId::new(f.get_id(), pgno);
+        let cached = pool.get_page(tid, f, &pid, Permissions::ReadOnly).unwrap();
+        let cached = cached.read().unwrap();
+        let on_disk = f.read_page(&pid).unwrap();
+        let count = |page: &dyn Page| {
+            page.as_any()
+                .downcast_ref::<HeapPage>()
+                .unwrap()
+                .iterator()
+                .count()
+        };
+        (count(cached.as_ref()), count(on_disk.as_ref()))
+    }
+
+    #[test]
+    fn test_hits_and_misses() {
+        let tmp = TempFile::new("buffer_pool_hits");
+        let f = get_file(&tmp);
+        let pool = BufferPool::new(4);
+        let tid = TransactionId::new();
+        let pid = HeapPageId::new(f.get_id(), 0);
+
+        let p1 = pool.get_page(tid, &f, &pid, Permissions::ReadOnly).unwrap();
+        let p2 = pool.get_page(tid, &f, &pid, Permissions::ReadOnly).unwrap();
+        assert!(Arc::ptr_eq(&p1, &p2));
+        assert_eq!(
+            PoolStats {
+                hits: 1,
+                misses: 1,
+                evictions: 0
+            },
+            pool.get_stats()
+        );
+
+        pool.reset_stats();
+        assert_eq!(PoolStats::default(), pool.get_stats());
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let tmp = TempFile::new("buffer_pool_lru");
+        let f = get_file(&tmp);
+        assert!(f.num_pages() >= 3);
+        let pool = BufferPool::new(2);
+        let tid = TransactionId::new();
+        let pid = |pgno| HeapPageId::new(f.get_id(), pgno);
+
+        pool.get_page(tid, &f, &pid(0), Permissions::ReadOnly)
+            .unwrap();
+        pool.get_page(tid, &f, &pid(1), Permissions::ReadOnly)
+            .unwrap();
+        pool.get_page(tid, &f, &pid(0), Permissions::ReadOnly)
+            .unwrap();
+        pool.get_page(tid, &f, &pid(2), Permissions::ReadOnly)
+            .unwrap();
+
+        assert!(pool.holds_page(&pid(0)));
+        assert!(!pool.holds_page(&pid(1)));
+        assert!(pool.holds_page(&pid(2)));
+        assert_eq!(1, pool.get_stats().evictions);
+        assert_eq!(3, pool.get_stats().misses);
+    }
+
+    #[test]
+    fn test_discard_page() {
+        let tmp = TempFile::new("buffer_pool_discard");
+        let f = get_file(&tmp);
+        let pool = BufferPool::new(2);
+        let tid = TransactionId::new();
+        let pid = HeapPageId::new(f.get_id(), 0);
+
+        pool.get_page(tid, &f, &pid, Permissions::ReadOnly).unwrap();
+        pool.discard_page(&pid);
+        assert!(!pool.holds_page(&pid));
+        pool.get_page(tid, &f, &pid, Permissions::ReadOnly).unwrap();
+        assert_eq!(2, pool.get_stats().misses);
+        assert_eq!(0, pool.get_stats().evictions);
+    }
+
+    #[test]
+    fn test_locking() {
+        let tmp = TempFile::new("buffer_pool_locking");
+        let f = get_file(&tmp);
+        let lock_manager = LockManager::with_timeout(Duration::from_millis(50));
+        let pool = BufferPool::with_lock_manager(4, Box::new(LruPolicy::new()), lock_manager);
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        let pid = HeapPageId::new(f.get_id(), 0);
+
+        pool.get_page(t1, &f, &pid, Permissions::ReadWrite).unwrap();
+        assert!(pool.holds_lock(t1, &pid));
+        assert!(matches!(
+            pool.get_page(t2, &f, &pid, Permissions::ReadOnly),
+            Err(DbError::TransactionAborted(_))
+        ));
+        assert!(!pool.holds_lock(t2, &pid));
+
+        pool.transaction_complete(t1, true).unwrap();
+        assert!(!pool.holds_lock(t1, &pid));
+        pool.get_page(t2, &f, &pid, Permissions::ReadOnly).unwrap();
+        assert!(pool.holds_lock(t2, &pid));
+        pool.release_page(t2, &pid);
+        assert!(!pool.holds_lock(t2, &pid));
+    }
+
+    #[test]
+    fn test_commit() {
+        let tmp = TempFile::new("buffer_pool_commit");
+        let f = Arc::new(get_file(&tmp));
+        let file: Arc<dyn DbFile> = f.clone();
+        let pool = BufferPool::new(10);
+        let tid = TransactionId::new();
+        let before = count_tuples(&pool, tid, &f, 3);
+
+        pool.insert_tuple(tid, &file, int_tuple(&[-1, -1])).unwrap();
+        assert_eq!((before.0 + 1, before.1), count_tuples(&pool, tid, &f, 3));
+        pool.transaction_complete(tid, true).unwrap();
+
+        let tid = TransactionId::new();
+        assert_eq!(
+            (before.0 + 1, before.1 + 1),
+            count_tuples(&pool, tid, &f, 3)
+        );
+        assert!(!pool.holds_lock(tid, &HeapPageId::new(f.get_id(), 0)));
+    }
+
+    #[test]
+    fn test_abort() {
+        let tmp = TempFile::new("buffer_pool_abort");
+        let f = Arc::new(get_file(&tmp));
+        let file: Arc<dyn DbFile> = f.clone();
+        let pool = BufferPool::new(10);
+        let tid = TransactionId::new();
+        let before = count_tuples(&pool, tid, &f, 3);
+
+        pool.insert_tuple(tid, &file, int_tuple(&[-1, -1])).unwrap();
+        pool.transaction_complete(tid, false).unwrap();
+
+        let tid = TransactionId::new();
+        assert_eq!(before, count_tuples(&pool, tid, &f, 3));
+        // the slot freed by the abort is used again
+        pool.insert_tuple(tid, &file, int_tuple(&[-2, -2])).unwrap();
+        assert_eq!(before.0 + 1, count_tuples(&pool, tid, &f, 3).0);
+    }
+
+    #[test]
+    fn test_no_steal() {
+        let tmp = TempFile::new("buffer_pool_no_steal");
+        let f = Arc::new(get_file(&tmp));
+        let file: Arc<dyn DbFile> = f.clone();
+        let pool = BufferPool::new(2);
+        let tid = TransactionId::new();
+        let pid = |pgno| HeapPageId::new(f.get_id(), pgno);
+
+        // dirty page 3, then read two other pages
+        pool.insert_tuple(tid, &file, int_tuple(&[-1, -1])).unwrap();
+        pool.get_page(tid, f.as_ref(), &pid(0), Permissions::ReadOnly)
+            .unwrap();
+        pool.get_page(tid, f.as_ref(), &pid(1), Permissions::ReadOnly)
+            .unwrap();
+        assert!(pool.holds_page(&pid(3)));
+        assert!(!pool.holds_page(&pid(0)));
+
+        // once every cached page is dirty, nothing can be read
+        let t = f.read_page(&pid(1)).unwrap();
+        let t = t.as_any().downcast_ref::<HeapPage>().unwrap();
+        pool.delete_tuple(tid, &file, t.iterator().next().unwrap())
+            .unwrap();
+        match pool.get_page(tid, f.as_ref(), &pid(0), Permissions::ReadOnly) {
+            Err(e) => assert!(e.to_string().contains("dirty")),
+            Ok(_) => panic!("read a page into a pool of dirty pages"),
+        }
+
+        pool.transaction_complete(tid, true).unwrap();
+        let tid = TransactionId::new();
+        pool.get_page(tid, f.as_ref(), &pid(0), Permissions::ReadOnly)
+            .unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+use super::buffer_pool::{BufferPool, PageRef};
+use super::page::Page;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::PageId;
+use crate::index::index_predicate::IndexPredicate;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::sync::Arc;
+
+/// An iterator over the tuples of a DbFile.
+pub type DbFileIterator = Box<dyn Iterator<Item = DbResult<Tuple>>>;
+
+/**
+ * The interface for database files on disk. Each table is represented by a
+ * single DbFile. DbFiles can fetch pages and iterate through tuples. Each
+ * file has a unique id used to store metadata about the table in the
+ * Catalog.
+ */
+pub trait DbFile: Send + Sync {
+    /// Read the specified page from disk.
+    fn read_page(&self, pid: &dyn PageId) -> DbResult<Box<dyn Page>>;
+
+    /// Push the specified page to disk.
+    fn write_page(&self, page: &dyn Page) -> DbResult<()>;
+
+    /**
+     * Builds a page of this file from its serialized page id and its data,
+     * as saved in the log.
+     */
+    fn page_from_image(&self, pid: &[i32], data: &[u8]) -> DbResult<Box<dyn Page>>;
+
+    /// Returns the number of pages in this file.
+    fn num_pages(&self) -> usize;
+
+    /**
+     * Returns a unique id used to identify this DbFile in the Catalog. This
+     * id is what PageId::get_table_id returns for pages of this file.
+     */
+    fn get_id(&self) -> u64;
+
+    /**
+     * Inserts the specified tuple into the file on behalf of transaction
+     * `tid`, fetching the pages it touches through `pool`. Returns the pages
+     * that were modified.
+     */
+    fn insert_tuple(
+        &self,
+        pool: &BufferPool,
+        tid: TransactionId,
+        t: Tuple,
+    ) -> DbResult<Vec<PageRef>>;
+
+    /**
+     * Removes the specified tuple, as named by its RecordId, from the file
+     * on behalf of transaction `tid`. Returns the pages that were modified.
+     */
+    fn delete_tuple(
+        &self,
+        pool: &BufferPool,
+        tid: TransactionId,
+        t: &Tuple,
+    ) -> DbResult<Vec<PageRef>>;
+
+    /// Returns the TupleDesc of the table stored in this DbFile.
+    fn get_tuple_desc(&self) -> &TupleDesc;
+
+    /**
+     * Returns an iterator over all the tuples stored in this DbFile. Pages
+     * are read through the BufferPool of `db`, whose Catalog must contain
+     * this file, on behalf of transaction `tid`.
+     */
+    fn iterator(&self, db: Arc<Database>, tid: TransactionId) -> DbFileIterator;
+
+    /// Returns the field the tuples are sorted on, if this DbFile is an index.
+    fn get_key_field(&self) -> Option<usize> {
+        None
+    }
+
+    /**
+     * Returns an iterator over the tuples whose key field satisfies `pred`,
+     * in key order. Fails if this DbFile is not an index.
+     */
+    fn index_iterator(
+        &self,
+        _db: Arc<Database>,
+        _tid: TransactionId,
+        _pred: IndexPredicate,
+    ) -> DbResult<DbFileIterator> {
+        Err(DbError::Db("file is not an index".to_string()))
+    }
+}
@@ -0,0 +1,91 @@
+use crate::common::page_id::PageKey;
+use std::collections::{BTreeMap, HashMap};
+
+/**
+ * EvictionPolicy decides which page the BufferPool throws out when it is
+ * full. The pool reports every access to a cached page, and every page that
+ * leaves the pool without being chosen as a victim.
+ */
+pub trait EvictionPolicy: Send {
+    /// Records that the page was just read from or loaded into the pool.
+    fn record_access(&mut self, key: PageKey);
+
+    /// Forgets a page that is no longer cached.
+    fn remove(&mut self, key: PageKey);
+
+    /**
+     * Chooses a page for which `can_evict` holds, and forgets it. Returns
+     * None if there is no such page.
+     */
+    fn evict(&mut self, can_evict: &mut dyn FnMut(PageKey) -> bool) -> Option<PageKey>;
+}
+
+/**
+ * Evicts the least recently used page.
+ */
+#[derive(Default)]
+pub struct LruPolicy {
+    clock: u64,
+    last_used: HashMap<PageKey, u64>,
+    order: BTreeMap<u64, PageKey>,
+}
+
+impl LruPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EvictionPolicy for LruPolicy {
+    fn record_access(&mut self, key: PageKey) {
+        self.clock += 1;
+        if let Some(old) = self.last_used.insert(key, self.clock) {
+            self.order.remove(&old);
+        }
+        self.order.insert(self.clock, key);
+    }
+
+    fn remove(&mut self, key: PageKey) {
+        if let Some(old) = self.last_used.remove(&key) {
+            self.order.remove(&old);
+        }
+    }
+
+    fn evict(&mut self, can_evict: &mut dyn FnMut(PageKey) -> bool) -> Option<PageKey> {
+        let (&tick, &key) = self.order.iter().find(|(_, &key)| can_evict(key))?;
+        self.order.remove(&tick);
+        self.last_used.remove(&key);
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lru_order() {
+        let mut lru = LruPolicy::new();
+        lru.record_access((1, 0));
+        lru.record_access((1, 1));
+        lru.record_access((1, 2));
+        lru.record_access((1, 0));
+        lru.remove((1, 2));
+
+        assert_eq!(Some((1, 1)), lru.evict(&mut |_| true));
+        assert_eq!(Some((1, 0)), lru.evict(&mut |_| true));
+        assert_eq!(None, lru.evict(&mut |_| true));
+    }
+
+    #[test]
+    fn test_lru_skips_pinned() {
+        let mut lru = LruPolicy::new();
+        lru.record_access((1, 0));
+        lru.record_access((1, 1));
+        lru.record_access((1, 2));
+
+        assert_eq!(Some((1, 1)), lru.evict(&mut |key| key != (1, 0)));
+        assert_eq!(None, lru.evict(&mut |_| false));
+        assert_eq!(Some((1, 0)), lru.evict(&mut |_| true));
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeSet;
+
+/**
+ * FreeSpaceMap tracks the number of empty slots on each page of a file, so
+ * that inserts can go straight to a page with room instead of scanning the
+ * whole file.
+ *
+ * The map is only a hint: a page it reports as having room must still be
+ * checked before inserting into it.
+ */
+#[derive(Default, Debug)]
+pub struct FreeSpaceMap {
+    free_slots: Vec<usize>,
+    with_space: BTreeSet<i32>,
+}
+
+impl FreeSpaceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that page `pgno` has `free_slots` empty slots, growing the map if needed.
+    pub fn update(&mut self, pgno: i32, free_slots: usize) {
+        let i = pgno as usize;
+        if i >= self.free_slots.len() {
+            self.free_slots.resize(i + 1, 0);
+        }
+        self.free_slots[i] = free_slots;
+        if free_slots > 0 {
+            self.with_space.insert(pgno);
+        } else {
+            self.with_space.remove(&pgno);
+        }
+    }
+
+    /// Returns the lowest numbered page with at least one empty slot.
+    pub fn find_page(&self) -> Option<i32> {
+        self.with_space.iter().next().copied()
+    }
+
+    /// Returns the number of empty slots recorded for page `pgno`.
+    pub fn free_slots(&self, pgno: i32) -> usize {
+        self.free_slots.get(pgno as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns the total number of empty slots in the file.
+    pub fn total_free_slots(&self) -> usize {
+        self.free_slots.iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_page() {
+        let mut map = FreeSpaceMap::new();
+        assert_eq!(None, map.find_page());
+        map.update(0, 0);
+        map.update(2, 5);
+        map.update(1, 3);
+        assert_eq!(Some(1), map.find_page());
+        assert_eq!(8, map.total_free_slots());
+
+        map.update(1, 0);
+        assert_eq!(Some(2), map.find_page());
+        map.update(2, 0);
+        assert_eq!(None, map.find_page());
+        assert_eq!(0, map.free_slots(7));
+    }
+}
@@ -0,0 +1,429 @@
+use super::buffer_pool::{BufferPool, PageRef};
+use super::db_file::{DbFile, DbFileIterator};
+use super::free_space_map::FreeSpaceMap;
+use super::heap_page::HeapPage;
+use super::heap_page_id::HeapPageId;
+use super::page::Page;
+use super::page_file::PageFile;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::PageId;
+use crate::common::permissions::Permissions;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::vec::IntoIter;
+
+/**
+ * HeapFile is an implementation of a DbFile that stores a collection of
+ * tuples in no particular order. Tuples are stored on pages, each of which
+ * is a fixed size, and the file is simply a collection of those pages.
+ *
+ * A FreeSpaceMap, built when the file is opened and kept up to date by
+ * inserts, deletes and page reads and writes, points inserts at a page with
+ * room. When a transaction aborts, the BufferPool reads the pages it
+ * modified back from disk, which resets their entries in the map.
+ */
+pub struct HeapFile {
+    file: PageFile,
+    td: TupleDesc,
+    id: u64,
+    free_space: Mutex<FreeSpaceMap>,
+}
+
+impl HeapFile {
+    /**
+     * Opens the heap file at `path`, creating an empty one if it does not
+     * exist yet.
+     */
+    pub fn new<P: AsRef<Path>>(path: P, td: TupleDesc) -> DbResult<Self> {
+        let file = PageFile::open(path)?;
+        let f = Self {
+            id: file.get_id(),
+            file,
+            td,
+            free_space: Mutex::new(FreeSpaceMap::new()),
+        };
+        for pgno in 0..f.num_pages() as i32 {
+            let free_slots = f.read_heap_page(pgno)?.get_num_empty_slots();
+            f.free_space.lock().unwrap().update(pgno, free_slots);
+        }
+        Ok(f)
+    }
+
+    /// Returns the absolute path of the file backing this HeapFile.
+    pub fn get_path(&self) -> &Path {
+        self.file.get_path()
+    }
+
+    /// Returns the number of empty tuple slots in the file, as tracked by the free-space map.
+    pub fn num_free_slots(&self) -> usize {
+        self.free_space.lock().unwrap().total_free_slots()
+    }
+
+    fn read_heap_page(&self, pgno: i32) -> DbResult<HeapPage> {
+        let data = self.file.read_page(pgno)?;
+        HeapPage::new(HeapPageId::new(self.id, pgno), &data, self.td.clone())
+    }
+
+    /**
+     * Appends an empty page to the file and returns its page number.
+     *
+     * The page is written outside of the BufferPool and of any transaction,
+     * so it stays in the file if the transaction that needed it aborts. It
+     * is empty then, and the free-space map sends the next inserts to it.
+     */
+    fn append_empty_page(&self) -> DbResult<i32> {
+        let data = HeapPage::create_empty_page_data();
+        let pgno = self.file.append_page(&data)?;
+        let free_slots = HeapPage::new(HeapPageId::new(self.id, pgno), &data, self.td.clone())?
+            .get_num_empty_slots();
+        self.free_space.lock().unwrap().update(pgno, free_slots);
+        Ok(pgno)
+    }
+}
+
+impl DbFile for HeapFile {
+    fn read_page(&self, pid: &dyn PageId) -> DbResult<Box<dyn Page>> {
+        if pid.get_table_id() != self.id {
+            return Err(DbError::Db("page does not belong to this file".to_string()));
+        }
+        let page = self.read_heap_page(pid.get_page_number())?;
+        // Pages are read from disk only when not cached, or to throw away
+        // the changes of an aborted transaction, so this is the latest state
+        // of the page.
+        self.free_space
+            .lock()
+            .unwrap()
+            .update(page.get_id().get_page_number(), page.get_num_empty_slots());
+        Ok(Box::new(page))
+    }
+
+    fn page_from_image(&self, pid: &[i32], data: &[u8]) -> DbResult<Box<dyn Page>> {
+        let pid = HeapPageId::deserialize(pid)?;
+        if pid.get_table_id() != self.id {
+            return Err(DbError::Db("page does not belong to this file".to_string()));
+        }
+        Ok(Box::new(HeapPage::new(pid, data, self.td.clone())?))
+    }
+
+    /// Writing the page right after the last one appends it to the file.
+    fn write_page(&self, page: &dyn Page) -> DbResult<()> {
+        let pgno = page.get_id().get_page_number();
+        self.file.write_page(pgno, &page.get_page_data())?;
+        if let Some(page) = page.as_any().downcast_ref::<HeapPage>() {
+            let free_slots = page.get_num_empty_slots();
+            self.free_space.lock().unwrap().update(pgno, free_slots);
+        }
+        Ok(())
+    }
+
+    fn num_pages(&self) -> usize {
+        self.file.num_pages()
+    }
+
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /**
+     * The tuple goes into the lowest numbered page the free-space map
+     * reports as having room, or into a new page appended to the file if
+     * all pages are full.
+     */
+    fn insert_tuple(
+        &self,
+        pool: &BufferPool,
+        tid: TransactionId,
+        t: Tuple,
+    ) -> DbResult<Vec<PageRef>> {
+        if *t.get_tuple_desc() != self.td {
+            return Err(DbError::Db("tuple desc mismatch".to_string()));
+        }
+        loop {
+            let candidate = self.free_space.lock().unwrap().find_page();
+            let pgno = match candidate {
+                Some(pgno) => pgno,
+                None => self.append_empty_page()?,
+            };
+            let pid = HeapPageId::new(self.id, pgno);
+            let page = pool.get_page(tid, self, &pid, Permissions::ReadWrite)?;
+            let mut guard = page.write().unwrap();
+            let heap_page = guard.as_any_mut().downcast_mut::<HeapPage>().unwrap();
+            // The map is only a hint, so the page may turn out to be full.
+            if heap_page.get_num_empty_slots() == 0 {
+                self.free_space.lock().unwrap().update(pgno, 0);
+                continue;
+            }
+            heap_page.insert_tuple(t)?;
+            let free_slots = heap_page.get_num_empty_slots();
+            self.free_space.lock().unwrap().update(pgno, free_slots);
+            drop(guard);
+            return Ok(vec![page]);
+        }
+    }
+
+    fn delete_tuple(
+        &self,
+        pool: &BufferPool,
+        tid: TransactionId,
+        t: &Tuple,
+    ) -> DbResult<Vec<PageRef>> {
+        let pid = t
+            .get_record_id()
+            .ok_or_else(|| DbError::Db("tuple has no record id".to_string()))?
+            .get_page_id();
+        if pid.get_table_id() != self.id {
+            return Err(DbError::Db(
+                "tuple does not belong to this file".to_string(),
+            ));
+        }
+        let page = pool.get_page(tid, self, pid, Permissions::ReadWrite)?;
+        let mut guard = page.write().unwrap();
+        let heap_page = guard.as_any_mut().downcast_mut::<HeapPage>().unwrap();
+        heap_page.delete_tuple(t)?;
+        let free_slots = heap_page.get_num_empty_slots();
+        self.free_space
+            .lock()
+            .unwrap()
+            .update(pid.get_page_number(), free_slots);
+        drop(guard);
+        Ok(vec![page])
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        &self.td
+    }
+
+    fn iterator(&self, db: Arc<Database>, tid: TransactionId) -> DbFileIterator {
+        Box::new(HeapFileIterator {
+            db,
+            tid,
+            table_id: self.id,
+            pgno: 0,
+            tuples: Vec::new().into_iter(),
+        })
+    }
+}
+
+/**
+ * Iterates over all tuples of a HeapFile in page order, fetching one page at
+ * a time from the BufferPool.
+ */
+struct HeapFileIterator {
+    db: Arc<Database>,
+    tid: TransactionId,
+    table_id: u64,
+    pgno: i32,
+    tuples: IntoIter<Tuple>,
+}
+
+impl HeapFileIterator {
+    fn read_next_page(&mut self) -> DbResult<bool> {
+        let file = self.db.get_catalog().get_database_file(self.table_id)?;
+        if self.pgno as usize >= file.num_pages() {
+            return Ok(false);
+        }
+        let pid = HeapPageId::new(self.table_id, self.pgno);
+        let page = self.db.get_buffer_pool().get_page(
+            self.tid,
+            file.as_ref(),
+            &pid,
+            Permissions::ReadOnly,
+        )?;
+        let page = page.read().unwrap();
+        let page = page.as_any().downcast_ref::<HeapPage>().unwrap();
+        self.tuples = page.iterator().cloned().collect::<Vec<Tuple>>().into_iter();
+        self.pgno += 1;
+        Ok(true)
+    }
+}
+
+impl Iterator for HeapFileIterator {
+    type Item = DbResult<Tuple>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(t) = self.tuples.next() {
+                return Some(Ok(t));
+            }
+            match self.read_next_page() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::ty::Type;
+    use crate::test_util::{create_heap_file, int_tuple, TempFile};
+    use crate::transaction::transaction_id::TransactionId;
+
+    fn get_td() -> TupleDesc {
+        TupleDesc::new(vec![Type::Int, Type::Int], vec!["a", "b"])
+    }
+
+    fn fill_file(f: &HeapFile, num_pages: i32, tuples_per_page: i32) {
+        for pgno in 0..num_pages {
+            let pid = HeapPageId::new(f.get_id(), pgno);
+            let mut page =
+                HeapPage::new(pid, &HeapPage::create_empty_page_data(), get_td()).unwrap();
+            for i in 0..tuples_per_page {
+                let mut t = Tuple::new(get_td());
+                t.set_field(0, Box::new(IntField::new(pgno)));
+                t.set_field(1, Box::new(IntField::new(i)));
+                page.insert_tuple(t).unwrap();
+            }
+            f.write_page(&page).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_id() {
+        let tmp = TempFile::new("heap_file_id");
+        let f1 = HeapFile::new(tmp.path(), get_td()).unwrap();
+        let f2 = HeapFile::new(tmp.path(), get_td()).unwrap();
+        assert_eq!(f1.get_id(), f2.get_id());
+
+        let other = TempFile::new("heap_file_id_other");
+        let f3 = HeapFile::new(other.path(), get_td()).unwrap();
+        assert_ne!(f1.get_id(), f3.get_id());
+    }
+
+    #[test]
+    fn test_read_write_page() {
+        let tmp = TempFile::new("heap_file_read_write");
+        let f = HeapFile::new(tmp.path(), get_td()).unwrap();
+        assert_eq!(0, f.num_pages());
+        fill_file(&f, 3, 10);
+        assert_eq!(3, f.num_pages());
+
+        let page = f.read_page(&HeapPageId::new(f.get_id(), 1)).unwrap();
+        assert_eq!(1, page.get_id().get_page_number());
+        assert!(f.read_page(&HeapPageId::new(f.get_id(), 3)).is_err());
+        assert!(f.read_page(&HeapPageId::new(f.get_id() + 1, 0)).is_err());
+    }
+
+    #[test]
+    fn test_iterator() {
+        let tmp = TempFile::new("heap_file_iterator");
+        let f = Arc::new(HeapFile::new(tmp.path(), get_td()).unwrap());
+        let db = Arc::new(Database::new(2));
+        db.get_catalog().add_table(f.clone(), "t", None);
+        assert_eq!(0, f.iterator(db.clone(), TransactionId::new()).count());
+        fill_file(&f, 3, 10);
+
+        let tuples: Vec<Tuple> = f
+            .iterator(db, TransactionId::new())
+            .map(|t| t.unwrap())
+            .collect();
+        assert_eq!(30, tuples.len());
+        for (i, t) in tuples.iter().enumerate() {
+            assert_eq!((i / 10).to_string(), t.get_field(0).unwrap().to_string());
+            assert_eq!((i % 10).to_string(), t.get_field(1).unwrap().to_string());
+            let rid = t.get_record_id().unwrap();
+            assert_eq!((i / 10) as i32, rid.get_page_id().get_page_number());
+            assert_eq!((i % 10) as i32, rid.get_tuple_number());
+        }
+    }
+
+    #[test]
+    fn test_insert_delete() {
+        let tmp = TempFile::new("heap_file_insert_delete");
+        // two ints per tuple leave room for 504 tuples per page
+        let rows: Vec<Vec<i32>> = (0..1008).map(|i| vec![i, i]).collect();
+        let f = Arc::new(create_heap_file(tmp.path(), 2, &rows));
+        let file: Arc<dyn DbFile> = f.clone();
+        let pool = BufferPool::new(10);
+        let tid = TransactionId::new();
+        assert_eq!(2, f.num_pages());
+        assert_eq!(0, f.num_free_slots());
+
+        // all pages are full, so the insert appends a page
+        pool.insert_tuple(tid, &file, int_tuple(&[-1, -1])).unwrap();
+        assert_eq!(3, f.num_pages());
+        assert_eq!(503, f.num_free_slots());
+
+        // the slot freed on page 0 is reused before the new page
+        let page = pool
+            .get_page(
+                tid,
+                f.as_ref(),
+                &HeapPageId::new(f.get_id(), 0),
+                Permissions::ReadOnly,
+            )
+            .unwrap();
+        let victim = page
+            .read()
+            .unwrap()
+            .as_any()
+            .downcast_ref::<HeapPage>()
+            .unwrap()
+            .iterator()
+            .nth(10)
+            .cloned()
+            .unwrap();
+        pool.delete_tuple(tid, &file, &victim).unwrap();
+        assert_eq!(504, f.num_free_slots());
+        pool.insert_tuple(tid, &file, int_tuple(&[-2, -2])).unwrap();
+        let inserted = page
+            .read()
+            .unwrap()
+            .as_any()
+            .downcast_ref::<HeapPage>()
+            .unwrap()
+            .iterator()
+            .nth(10)
+            .cloned()
+            .unwrap();
+        assert_eq!("-2", inserted.get_field(0).unwrap().to_string());
+        assert_eq!(10, inserted.get_record_id().unwrap().get_tuple_number());
+
+        // deleting a tuple twice, or one that was never stored, fails
+        pool.delete_tuple(tid, &file, &inserted).unwrap();
+        assert!(pool.delete_tuple(tid, &file, &inserted).is_err());
+        assert!(pool.delete_tuple(tid, &file, &int_tuple(&[0, 0])).is_err());
+
+        // the free-space map is rebuilt from disk when the file is reopened
+        pool.transaction_complete(tid, true).unwrap();
+        let reopened = HeapFile::new(tmp.path(), f.get_tuple_desc().clone()).unwrap();
+        assert_eq!(504, reopened.num_free_slots());
+    }
+
+    #[test]
+    fn test_abort_restores_free_space() {
+        let tmp = TempFile::new("heap_file_abort");
+        let rows: Vec<Vec<i32>> = (0..1000).map(|i| vec![i, i]).collect();
+        let f = Arc::new(create_heap_file(tmp.path(), 2, &rows));
+        let file: Arc<dyn DbFile> = f.clone();
+        let pool = BufferPool::new(10);
+        assert_eq!(8, f.num_free_slots());
+
+        // fill page 1, then spill over into a new page
+        let tid = TransactionId::new();
+        for i in 0..10 {
+            pool.insert_tuple(tid, &file, int_tuple(&[i, i])).unwrap();
+        }
+        assert_eq!(3, f.num_pages());
+        assert_eq!(502, f.num_free_slots());
+        pool.transaction_complete(tid, false).unwrap();
+
+        // the new page stays in the file, empty, and takes later inserts
+        assert_eq!(3, f.num_pages());
+        assert_eq!(8 + 504, f.num_free_slots());
+        let tid = TransactionId::new();
+        for i in 0..10 {
+            pool.insert_tuple(tid, &file, int_tuple(&[i, i])).unwrap();
+        }
+        pool.transaction_complete(tid, true).unwrap();
+        assert_eq!(3, f.num_pages());
+        assert_eq!(502, f.num_free_slots());
+    }
+}
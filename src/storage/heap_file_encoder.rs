@@ -0,0 +1,396 @@
+use super::db_file::DbFile;
+use super::heap_file::HeapFile;
+use super::heap_page::HeapPage;
+use super::heap_page_id::HeapPageId;
+use crate::common::database::Database;
+use crate::common::error::{DbError, DbResult};
+use crate::common::field::Field;
+use crate::common::int_field::IntField;
+use crate::common::string_field::StringField;
+use crate::common::ty::{Type, STRING_LEN};
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::fs::{self, File};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/**
+ * Converts a delimited text file into a HeapFile at `path` with the schema
+ * given by `types` and `names`, replacing any file already there. Each line
+ * of the input holds one tuple, e.g. `1,alice` for a schema of an Int and a
+ * Str field. Returns the new HeapFile, its tuples packed into as few pages
+ * as possible.
+ *
+ * Fields may be quoted with `"`, in which case they may contain the
+ * delimiter and line breaks, and a quote inside them is written `""`.
+ * Unquoted fields are trimmed. Strings longer than STRING_LEN bytes are
+ * truncated, and blank lines are skipped. On a malformed tuple nothing is
+ * written, and the error names the line and column (both counting from 1)
+ * of the bad field.
+ */
+pub fn convert<R: BufRead, P: AsRef<Path>>(
+    input: R,
+    path: P,
+    types: Vec<Type>,
+    names: Vec<&str>,
+    delimiter: char,
+) -> DbResult<HeapFile> {
+    if types.len() != names.len() {
+        return Err(DbError::Db(format!(
+            "schema has {} types but {} names",
+            types.len(),
+            names.len()
+        )));
+    }
+    let td = TupleDesc::new(types, names);
+    File::create(path.as_ref())?;
+    let f = HeapFile::new(path.as_ref(), td)?;
+    let result = write_pages(&f, input, delimiter);
+    if let Err(e) = result {
+        drop(f);
+        let _ = fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(f)
+}
+
+fn write_pages<R: BufRead>(f: &HeapFile, mut input: R, delimiter: char) -> DbResult<()> {
+    let td = f.get_tuple_desc();
+    let mut page: Option<HeapPage> = None;
+    let mut lineno = 1;
+    while let Some(record) = read_record(&mut input)? {
+        let start = lineno;
+        lineno += 1 + record.matches('\n').count();
+        if record.trim().is_empty() {
+            continue;
+        }
+        let t = parse_record(&record, td, delimiter).map_err(|(offset, e)| {
+            let (line, column) = position(&record, offset);
+            DbError::Parse(format!(
+                "line {}, column {}: {}",
+                start + line,
+                column + 1,
+                e
+            ))
+        })?;
+        let full = page.as_ref().is_none_or(|p| p.get_num_empty_slots() == 0);
+        if full {
+            if let Some(p) = page.take() {
+                f.write_page(&p)?;
+            }
+            let pid = HeapPageId::new(f.get_id(), f.num_pages() as i32);
+            let data = HeapPage::create_empty_page_data();
+            page = Some(HeapPage::new(pid, &data, td.clone())?);
+        }
+        page.as_mut().unwrap().insert_tuple(t)?;
+    }
+    match page {
+        Some(p) => f.write_page(&p),
+        None => Ok(()),
+    }
+}
+
+/**
+ * Reads the next record of the input without its line break, or returns None
+ * at the end of the input. A record spans several lines if a quoted field
+ * holds a line break.
+ */
+fn read_record<R: BufRead>(input: &mut R) -> DbResult<Option<String>> {
+    let mut record = String::new();
+    // An odd number of quotes leaves a quoted field open.
+    while input.read_line(&mut record)? > 0 && record.matches('"').count() % 2 == 1 {}
+    if record.is_empty() {
+        return Ok(None);
+    }
+    if record.ends_with('\n') {
+        record.pop();
+        if record.ends_with('\r') {
+            record.pop();
+        }
+    }
+    Ok(Some(record))
+}
+
+/// Returns the line and column, both counting from 0, of a char in a record.
+fn position(record: &str, offset: usize) -> (usize, usize) {
+    let before: Vec<char> = record.chars().take(offset).collect();
+    let line = before.iter().filter(|c| **c == '\n').count();
+    let column = match before.iter().rposition(|c| *c == '\n') {
+        Some(i) => offset - i - 1,
+        None => offset,
+    };
+    (line, column)
+}
+
+/// Parses a record into a tuple. Errors come with the char they occur at.
+fn parse_record(record: &str, td: &TupleDesc, delimiter: char) -> Result<Tuple, (usize, String)> {
+    let values = split_record(record, delimiter)?;
+    if values.len() != td.num_fields() {
+        return Err((
+            0,
+            format!(
+                "expected {} fields, found {}",
+                td.num_fields(),
+                values.len()
+            ),
+        ));
+    }
+    let mut t = Tuple::new(td.clone());
+    for (i, (column, value)) in values.into_iter().enumerate() {
+        let field: Box<dyn Field> = match td.get_field_type(i).unwrap() {
+            Type::Int => {
+                let v = value
+                    .parse()
+                    .map_err(|_| (column, format!("invalid integer '{}'", value)))?;
+                Box::new(IntField::new(v))
+            }
+            Type::Str => Box::new(StringField::new(value, STRING_LEN as usize)),
+        };
+        t.set_field(i, field);
+    }
+    Ok(t)
+}
+
+/**
+ * Splits a record into its fields, each paired with the char it starts at.
+ * Quoted fields are unquoted, and unquoted fields are trimmed.
+ */
+fn split_record(record: &str, delimiter: char) -> Result<Vec<(usize, String)>, (usize, String)> {
+    let chars: Vec<char> = record.chars().collect();
+    let mut fields = Vec::new();
+    let mut i = 0;
+    loop {
+        let start = i;
+        while chars
+            .get(i)
+            .is_some_and(|c| *c != delimiter && c.is_whitespace())
+        {
+            i += 1;
+        }
+        let value = if chars.get(i) == Some(&'"') {
+            let quote = i;
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some('"') if chars.get(i + 1) == Some(&'"') => {
+                        value.push('"');
+                        i += 2;
+                    }
+                    Some('"') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(c) => {
+                        value.push(*c);
+                        i += 1;
+                    }
+                    None => return Err((quote, "unterminated quote".to_string())),
+                }
+            }
+            while chars
+                .get(i)
+                .is_some_and(|c| *c != delimiter && c.is_whitespace())
+            {
+                i += 1;
+            }
+            if i < chars.len() && chars[i] != delimiter {
+                return Err((i, format!("expected {} after closing quote", delimiter)));
+            }
+            value
+        } else {
+            while i < chars.len() && chars[i] != delimiter {
+                i += 1;
+            }
+            chars[start..i]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string()
+        };
+        fields.push((start, value));
+        if i >= chars.len() {
+            return Ok(fields);
+        }
+        // Skip the delimiter.
+        i += 1;
+    }
+}
+
+/**
+ * Writes the tuples of a table to `out` as delimited text that `convert`
+ * reads back, one tuple per line. Fields holding the delimiter, a quote, a
+ * line break or surrounding whitespace are quoted. Returns the number of tuples written.
+ */
+pub fn export(
+    db: Arc<Database>,
+    tid: TransactionId,
+    table_id: u64,
+    out: &mut dyn Write,
+    delimiter: char,
+) -> DbResult<usize> {
+    let file = db.get_catalog().get_database_file(table_id)?;
+    let mut count = 0;
+    for t in file.iterator(db.clone(), tid) {
+        let values: Vec<String> = t?
+            .get_fields()
+            .map(|f| quote(f.to_string(), delimiter))
+            .collect();
+        writeln!(out, "{}", values.join(&delimiter.to_string()))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn quote(value: String, delimiter: char) -> String {
+    let needs_quotes = value.contains(delimiter)
+        || value.contains('"')
+        || value.contains(['\n', '\r'])
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace);
+    if needs_quotes {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::downcast;
+    use crate::test_util::TempDir;
+
+    fn convert_str(dir: &TempDir, input: &str) -> DbResult<HeapFile> {
+        convert(
+            input.as_bytes(),
+            dir.path().join("t.dat"),
+            vec![Type::Int, Type::Str],
+            vec!["id", "name"],
+            ',',
+        )
+    }
+
+    fn error(dir: &TempDir, input: &str) -> String {
+        match convert_str(dir, input) {
+            Err(DbError::Parse(msg)) => msg,
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("{:?} should fail", input),
+        }
+    }
+
+    #[test]
+    fn test_convert_and_export() {
+        let dir = TempDir::new("encoder_convert");
+        let long = "x".repeat(STRING_LEN as usize + 10);
+        let mut input = format!(" 1 , alice\n\n-2,\"b, \"\"bob\"\"\"\n3,{}\n", long);
+        for i in 4..1000 {
+            input.push_str(&format!("{},e{}\n", i, i));
+        }
+        let f = convert_str(&dir, &input).unwrap();
+        assert!(f.num_pages() > 1);
+
+        let db = Arc::new(Database::new(50));
+        db.get_catalog().add_table(Arc::new(f), "t", None);
+        let table_id = db.get_catalog().get_table_id("t").unwrap();
+        let tid = TransactionId::new();
+        let file = db.get_catalog().get_database_file(table_id).unwrap();
+        let tuples: Vec<Tuple> = file
+            .iterator(db.clone(), tid)
+            .collect::<DbResult<_>>()
+            .unwrap();
+        assert_eq!(999, tuples.len());
+        let name = |i: usize| downcast!(tuples[i].get_field(1).unwrap(), StringField).get_value();
+        assert_eq!(
+            -2,
+            downcast!(tuples[1].get_field(0).unwrap(), IntField).get_value()
+        );
+        assert_eq!("alice", name(0));
+        assert_eq!("b, \"bob\"", name(1));
+        assert_eq!(STRING_LEN as usize, name(2).len());
+
+        let mut out = Vec::new();
+        assert_eq!(
+            999,
+            export(db.clone(), tid, table_id, &mut out, ',').unwrap()
+        );
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("1,alice\n-2,\"b, \"\"bob\"\"\"\n3,xxx"));
+        let copy = convert(
+            out.as_bytes(),
+            dir.path().join("copy.dat"),
+            vec![Type::Int, Type::Str],
+            vec!["id", "name"],
+            ',',
+        )
+        .unwrap();
+        assert_eq!(file.num_pages(), copy.num_pages());
+    }
+
+    #[test]
+    fn test_line_breaks() {
+        let dir = TempDir::new("encoder_line_breaks");
+        let input = "1,\"a\nb\"\r\n2,\"c\r\n\n\"\"d\"\"\"\n3, e\r\n";
+        let f = convert_str(&dir, input).unwrap();
+        let db = Arc::new(Database::new(50));
+        db.get_catalog().add_table(Arc::new(f), "t", None);
+        let table_id = db.get_catalog().get_table_id("t").unwrap();
+        let tid = TransactionId::new();
+        let mut out = Vec::new();
+        assert_eq!(3, export(db.clone(), tid, table_id, &mut out, ',').unwrap());
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!("1,\"a\nb\"\n2,\"c\r\n\n\"\"d\"\"\"\n3,e\n", out);
+
+        let copy = convert(
+            out.as_bytes(),
+            dir.path().join("copy.dat"),
+            vec![Type::Int, Type::Str],
+            vec!["id", "name"],
+            ',',
+        )
+        .unwrap();
+        let copy_id = copy.get_id();
+        db.get_catalog().add_table(Arc::new(copy), "copy", None);
+        let mut copied = Vec::new();
+        export(db.clone(), tid, copy_id, &mut copied, ',').unwrap();
+        assert_eq!(out, String::from_utf8(copied).unwrap());
+        db.get_buffer_pool()
+            .transaction_complete(tid, true)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        let dir = TempDir::new("encoder_errors");
+        assert_eq!(
+            "line 3, column 1: expected 2 fields, found 3",
+            error(&dir, "1,a\n\n2,b,c\n")
+        );
+        assert_eq!(
+            "line 2, column 1: invalid integer 'x1'",
+            error(&dir, "1,a\n x1 ,b\n")
+        );
+        assert_eq!(
+            "line 1, column 7: expected , after closing quote",
+            error(&dir, "1,\"a\" b")
+        );
+        assert_eq!(
+            "line 1, column 3: unterminated quote",
+            error(&dir, "1,\"ab")
+        );
+        assert_eq!(
+            "line 3, column 4: expected , after closing quote",
+            error(&dir, "1,a\n2,\"b\nc\" d\n3,e")
+        );
+        assert_eq!(
+            "line 2, column 3: unterminated quote",
+            error(&dir, "1,a\n2,\"b\n3,c\n")
+        );
+        assert!(!dir.path().join("t.dat").exists());
+    }
+}
@@ -0,0 +1,324 @@
+use super::heap_page_id::HeapPageId;
+use super::page::{Page, PAGE_SIZE};
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::PageId;
+use crate::downcast::Downcast;
+use crate::table::record_id::RecordId;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use downcast_macro_derive::Downcast;
+use std::any::Any;
+use std::iter::Flatten;
+use std::vec::IntoIter;
+
+/**
+ * Each instance of HeapPage stores data for one page of HeapFiles.
+ *
+ * The page starts with a header bitmap with one bit per slot, followed by
+ * the fixed-size tuple slots. Bit `i` of the header is set iff slot `i`
+ * holds a tuple.
+ */
+#[derive(Downcast)]
+pub struct HeapPage {
+    pid: HeapPageId,
+    td: TupleDesc,
+    header: Vec<u8>,
+    tuples: Vec<Option<Tuple>>,
+    page_size: usize,
+    dirty: Option<TransactionId>,
+    old_data: Vec<u8>,
+}
+
+impl HeapPage {
+    /**
+     * Create a HeapPage from a set of bytes of data read from disk.
+     * The size of the page is the length of `data`.
+     */
+    pub fn new(pid: HeapPageId, data: &[u8], td: TupleDesc) -> DbResult<Self> {
+        let page_size = data.len();
+        let num_slots = Self::compute_num_slots(page_size, &td);
+        let header_size = Self::compute_header_size(num_slots);
+        let tuple_size = td.get_size() as usize;
+
+        let header = data[..header_size].to_vec();
+        let mut tuples = Vec::with_capacity(num_slots);
+        for i in 0..num_slots {
+            if header[i / 8] & (1 << (i % 8)) == 0 {
+                tuples.push(None);
+                continue;
+            }
+            let offset = header_size + i * tuple_size;
+            let mut t = Self::read_tuple(&td, &data[offset..offset + tuple_size])?;
+            t.set_record_id(RecordId::new(Box::new(pid), i as i32));
+            tuples.push(Some(t));
+        }
+
+        Ok(Self {
+            pid,
+            td,
+            header,
+            tuples,
+            page_size,
+            dirty: None,
+            old_data: data.to_vec(),
+        })
+    }
+
+    /// Returns a byte array representing an empty HeapPage.
+    pub fn create_empty_page_data() -> Vec<u8> {
+        vec![0; PAGE_SIZE]
+    }
+
+    /// Retrieve the number of tuples on this page.
+    pub fn get_num_slots(&self) -> usize {
+        self.tuples.len()
+    }
+
+    /// Returns the number of empty slots on this page.
+    pub fn get_num_empty_slots(&self) -> usize {
+        (0..self.get_num_slots())
+            .filter(|&i| !self.is_slot_used(i))
+            .count()
+    }
+
+    /// Returns true if associated slot on this page is filled.
+    pub fn is_slot_used(&self, i: usize) -> bool {
+        i < self.get_num_slots() && self.header[i / 8] & (1 << (i % 8)) != 0
+    }
+
+    /**
+     * Adds the specified tuple to the page and assigns it a RecordId
+     * pointing at the slot it was stored in.
+     */
+    pub fn insert_tuple(&mut self, mut t: Tuple) -> DbResult<()> {
+        if *t.get_tuple_desc() != self.td {
+            return Err(DbError::Db("tuple desc mismatch".to_string()));
+        }
+        let slot = (0..self.get_num_slots())
+            .find(|&i| !self.is_slot_used(i))
+            .ok_or_else(|| DbError::Db("page is full".to_string()))?;
+        t.set_record_id(RecordId::new(Box::new(self.pid), slot as i32));
+        self.mark_slot_used(slot, true);
+        self.tuples[slot] = Some(t);
+        Ok(())
+    }
+
+    /// Delete the specified tuple from the page, as named by its RecordId.
+    pub fn delete_tuple(&mut self, t: &Tuple) -> DbResult<()> {
+        let rid = t
+            .get_record_id()
+            .ok_or_else(|| DbError::Db("tuple has no record id".to_string()))?;
+        let pid = rid.get_page_id();
+        if pid.get_table_id() != self.pid.get_table_id()
+            || pid.get_page_number() != self.pid.get_page_number()
+        {
+            return Err(DbError::Db("tuple is not on this page".to_string()));
+        }
+        let slot = rid.get_tuple_number() as usize;
+        if !self.is_slot_used(slot) {
+            return Err(DbError::Db("tuple slot is already empty".to_string()));
+        }
+        self.mark_slot_used(slot, false);
+        self.tuples[slot] = None;
+        Ok(())
+    }
+
+    /// An iterator over all tuples on this page, in slot order.
+    pub fn iterator(&self) -> impl Iterator<Item = &Tuple> {
+        self.tuples.iter().flatten()
+    }
+
+    fn mark_slot_used(&mut self, i: usize, value: bool) {
+        if value {
+            self.header[i / 8] |= 1 << (i % 8);
+        } else {
+            self.header[i / 8] &= !(1 << (i % 8));
+        }
+    }
+
+    /// Each tuple takes `tuple_size * 8` bits of slot plus one header bit.
+    fn compute_num_slots(page_size: usize, td: &TupleDesc) -> usize {
+        (page_size * 8) / (td.get_size() as usize * 8 + 1)
+    }
+
+    fn compute_header_size(num_slots: usize) -> usize {
+        num_slots.div_ceil(8)
+    }
+
+    fn read_tuple(td: &TupleDesc, mut data: &[u8]) -> DbResult<Tuple> {
+        let mut t = Tuple::new(td.clone());
+        for i in 0..td.num_fields() {
+            let ty = td.get_field_type(i).unwrap();
+            t.set_field(i, ty.parse(&mut data)?);
+        }
+        Ok(t)
+    }
+}
+
+impl Page for HeapPage {
+    fn get_id(&self) -> Box<dyn PageId> {
+        Box::new(self.pid)
+    }
+
+    fn get_page_data(&self) -> Vec<u8> {
+        let tuple_size = self.td.get_size() as usize;
+        let mut data = Vec::with_capacity(self.page_size);
+        data.extend_from_slice(&self.header);
+        for slot in self.tuples.iter() {
+            match slot {
+                Some(t) => {
+                    for field in t.get_fields() {
+                        // Writing into a Vec cannot fail.
+                        field.serialize(&mut data).unwrap();
+                    }
+                }
+                None => data.resize(data.len() + tuple_size, 0),
+            }
+        }
+        data.resize(self.page_size, 0);
+        data
+    }
+
+    fn is_dirty(&self) -> Option<TransactionId> {
+        self.dirty
+    }
+
+    fn mark_dirty(&mut self, dirty: bool, tid: TransactionId) {
+        self.dirty = if dirty { Some(tid) } else { None };
+    }
+
+    fn get_before_image(&self) -> Box<dyn Page> {
+        // The old data was produced by a page of the same layout.
+        Box::new(HeapPage::new(self.pid, &self.old_data, self.td.clone()).unwrap())
+    }
+
+    fn set_before_image(&mut self) {
+        self.old_data = self.get_page_data();
+    }
+}
+
+impl IntoIterator for HeapPage {
+    type Item = Tuple;
+    type IntoIter = Flatten<IntoIter<Option<Tuple>>>;
+
+    /// Consumes the page, yielding its tuples in slot order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.tuples.into_iter().flatten()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::string_field::StringField;
+    use crate::common::ty::{Type, STRING_LEN};
+
+    fn get_td() -> TupleDesc {
+        TupleDesc::new(vec![Type::Int, Type::Str], vec!["id", "name"])
+    }
+
+    fn get_tuple(td: &TupleDesc, id: i32, name: &str) -> Tuple {
+        let mut t = Tuple::new(td.clone());
+        t.set_field(0, Box::new(IntField::new(id)));
+        t.set_field(
+            1,
+            Box::new(StringField::new(name.to_string(), STRING_LEN as usize)),
+        );
+        t
+    }
+
+    fn get_page(tuples: i32) -> HeapPage {
+        let td = get_td();
+        let pid = HeapPageId::new(1, 0);
+        let mut page = HeapPage::new(pid, &HeapPage::create_empty_page_data(), td.clone()).unwrap();
+        for i in 0..tuples {
+            page.insert_tuple(get_tuple(&td, i, &format!("name{}", i)))
+                .unwrap();
+        }
+        page
+    }
+
+    #[test]
+    fn test_num_slots() {
+        let page = get_page(0);
+        // 4096 * 8 / (136 * 8 + 1)
+        assert_eq!(30, page.get_num_slots());
+        assert_eq!(30, page.get_num_empty_slots());
+    }
+
+    #[test]
+    fn test_insert_assigns_record_id() {
+        let page = get_page(3);
+        assert_eq!(27, page.get_num_empty_slots());
+        for (i, t) in page.iterator().enumerate() {
+            let rid = t.get_record_id().unwrap();
+            assert_eq!(i as i32, rid.get_tuple_number());
+            assert_eq!(0, rid.get_page_id().get_page_number());
+            assert_eq!(1, rid.get_page_id().get_table_id());
+        }
+    }
+
+    #[test]
+    fn test_insert_full_page() {
+        let mut page = get_page(30);
+        assert_eq!(0, page.get_num_empty_slots());
+        assert!(page.insert_tuple(get_tuple(&get_td(), 30, "x")).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let page = get_page(5);
+        let data = page.get_page_data();
+        assert_eq!(PAGE_SIZE, data.len());
+
+        let read = HeapPage::new(HeapPageId::new(1, 0), &data, get_td()).unwrap();
+        assert_eq!(data, read.get_page_data());
+        let names: Vec<String> = read
+            .iterator()
+            .map(|t| t.get_field(1).unwrap().to_string())
+            .collect();
+        assert_eq!(vec!["name0", "name1", "name2", "name3", "name4"], names);
+    }
+
+    #[test]
+    fn test_before_image() {
+        let mut page = get_page(5);
+        let before = page.get_before_image();
+        let before = before.as_any().downcast_ref::<HeapPage>().unwrap();
+        assert_eq!(0, before.iterator().count());
+
+        page.set_before_image();
+        assert_eq!(
+            page.get_page_data(),
+            page.get_before_image().get_page_data()
+        );
+    }
+
+    #[test]
+    fn test_dirty() {
+        let mut page = get_page(0);
+        let tid = TransactionId::new();
+        assert_eq!(None, page.is_dirty());
+        page.mark_dirty(true, tid);
+        assert_eq!(Some(tid), page.is_dirty());
+        page.mark_dirty(false, tid);
+        assert_eq!(None, page.is_dirty());
+    }
+
+    #[test]
+    fn test_delete_tuple() {
+        let mut page = get_page(3);
+        let rid = RecordId::new(Box::new(HeapPageId::new(1, 0)), 1);
+        let mut t = get_tuple(&get_td(), 1, "name1");
+        t.set_record_id(rid);
+        page.delete_tuple(&t).unwrap();
+        assert!(!page.is_slot_used(1));
+        assert_eq!(2, page.iterator().count());
+        assert!(page.delete_tuple(&t).is_err());
+
+        page.insert_tuple(get_tuple(&get_td(), 5, "name5")).unwrap();
+        assert!(page.is_slot_used(1));
+    }
+}
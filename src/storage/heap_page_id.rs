@@ -0,0 +1,81 @@
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::PageId;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/**
+ * Unique identifier for HeapPage objects.
+ */
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct HeapPageId {
+    table_id: u64,
+    pgno: i32,
+}
+
+impl HeapPageId {
+    /**
+     * Constructor. Create a page id structure for a specific page of a
+     * specific table.
+     */
+    pub fn new(table_id: u64, pgno: i32) -> Self {
+        Self { table_id, pgno }
+    }
+
+    /// Rebuilds a page id from the integers returned by `serialize`.
+    pub fn deserialize(data: &[i32]) -> DbResult<Self> {
+        match data {
+            [hi, lo, pgno] => Ok(Self::new(
+                ((*hi as u32 as u64) << 32) | *lo as u32 as u64,
+                *pgno,
+            )),
+            _ => Err(DbError::Db(format!("malformed heap page id {:?}", data))),
+        }
+    }
+}
+
+impl PageId for HeapPageId {
+    /// The table id is split into its high and low halves.
+    fn serialize(&self) -> Vec<i32> {
+        vec![
+            (self.table_id >> 32) as i32,
+            self.table_id as i32,
+            self.pgno,
+        ]
+    }
+
+    fn get_table_id(&self) -> u64 {
+        self.table_id
+    }
+
+    fn hash_code(&self) -> u64 {
+        let mut s = DefaultHasher::new();
+        self.hash(&mut s);
+        s.finish()
+    }
+
+    fn equals(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn get_page_number(&self) -> i32 {
+        self.pgno
+    }
+
+    fn clone_box(&self) -> Box<dyn PageId> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        for &table_id in [0, 1, u64::MAX, 0x8000_0000_7fff_ffff].iter() {
+            let pid = HeapPageId::new(table_id, 7);
+            assert_eq!(pid, HeapPageId::deserialize(&pid.serialize()).unwrap());
+        }
+        assert!(HeapPageId::deserialize(&[1, 2]).is_err());
+    }
+}
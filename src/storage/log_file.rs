@@ -0,0 +1,613 @@
+use super::page::{Page, PAGE_SIZE};
+use crate::common::error::{DbError, DbResult};
+use crate::transaction::transaction_id::TransactionId;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const ABORT_RECORD: i32 = 1;
+const COMMIT_RECORD: i32 = 2;
+const UPDATE_RECORD: i32 = 3;
+const BEGIN_RECORD: i32 = 4;
+const CHECKPOINT_RECORD: i32 = 5;
+
+/// Offset stored in the header when no checkpoint was taken.
+const NO_CHECKPOINT: i64 = -1;
+/// The header holds the offset of the last checkpoint record.
+const HEADER_SIZE: u64 = 8;
+/// Most integers a serialized PageId is made of.
+const MAX_PID_LEN: i32 = 4;
+
+/**
+ * The contents of a page as saved in the log: the id of its table, its
+ * `PageId::serialize()` form, and its data.
+ */
+#[derive(PartialEq, Clone, Debug)]
+pub struct PageImage {
+    pub table_id: u64,
+    pub pid: Vec<i32>,
+    pub data: Vec<u8>,
+}
+
+impl PageImage {
+    pub fn new(page: &dyn Page) -> Self {
+        let pid = page.get_id();
+        Self {
+            table_id: pid.get_table_id(),
+            pid: pid.serialize(),
+            data: page.get_page_data(),
+        }
+    }
+}
+
+enum Record {
+    Begin,
+    Commit,
+    Abort,
+    Update {
+        before: PageImage,
+        after: PageImage,
+    },
+    /// The transactions running at the checkpoint, with the offset of their first record.
+    Checkpoint(Vec<(u64, u64)>),
+}
+
+struct LogRecord {
+    offset: u64,
+    tid: u64,
+    record: Record,
+}
+
+struct LogState {
+    file: File,
+    /// Transactions with records in the log, and the offset of their BEGIN record.
+    active: HashMap<u64, u64>,
+}
+
+/**
+ * LogFile implements the write-ahead log used by the BufferPool for
+ * rollback and recovery.
+ *
+ * The file starts with the offset of the last CHECKPOINT record, followed
+ * by the records. Each record is its type, the id of its transaction, a
+ * payload, and the offset at which the record starts:
+ *
+ * - BEGIN, COMMIT and ABORT have no payload. BEGIN is written before the
+ *   first other record of a transaction.
+ * - UPDATE holds the before and after images of a page, each written as
+ *   the table id, the serialized page id and the page data.
+ * - CHECKPOINT holds the running transactions and the offset of their
+ *   BEGIN records. All dirty pages are written to disk before it.
+ *
+ * Every image installed by rollback or recovery is handed to a callback,
+ * which writes it to the right file and drops any cached copy.
+ */
+pub struct LogFile {
+    path: PathBuf,
+    state: Mutex<LogState>,
+}
+
+impl LogFile {
+    /// Opens the log at `path`, creating an empty one if it does not exist yet.
+    pub fn new<P: AsRef<Path>>(path: P) -> DbResult<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.as_ref())?;
+        if file.metadata()?.len() < HEADER_SIZE {
+            file.set_len(0)?;
+            file.write_all(&NO_CHECKPOINT.to_be_bytes())?;
+            file.sync_all()?;
+        }
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            state: Mutex::new(LogState {
+                file,
+                active: HashMap::new(),
+            }),
+        })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the size of the log in bytes.
+    pub fn len(&self) -> DbResult<u64> {
+        Ok(self.state.lock().unwrap().file.metadata()?.len())
+    }
+
+    /// Returns true if the log holds no records.
+    pub fn is_empty(&self) -> DbResult<bool> {
+        Ok(self.len()? <= HEADER_SIZE)
+    }
+
+    /**
+     * Write an UPDATE record to the log for the specified tid and page
+     * (with provided before and after images). The record is not forced to
+     * disk; call `force` before writing the page itself.
+     */
+    pub fn log_write(
+        &self,
+        tid: TransactionId,
+        before: &dyn Page,
+        after: &dyn Page,
+    ) -> DbResult<()> {
+        let mut state = self.state.lock().unwrap();
+        Self::begin(&mut state, tid.get_id())?;
+        let record = Record::Update {
+            before: PageImage::new(before),
+            after: PageImage::new(after),
+        };
+        Self::append(&mut state, tid.get_id(), &record)?;
+        Ok(())
+    }
+
+    /**
+     * Write a COMMIT record to disk for the specified tid. Does nothing if
+     * the transaction did not log any update.
+     */
+    pub fn log_commit(&self, tid: TransactionId) -> DbResult<()> {
+        self.log_end(tid.get_id(), &Record::Commit)
+    }
+
+    /**
+     * Write an ABORT record to disk for the specified tid. The changes of
+     * the transaction should have been rolled back first.
+     */
+    pub fn log_abort(&self, tid: TransactionId) -> DbResult<()> {
+        self.log_end(tid.get_id(), &Record::Abort)
+    }
+
+    /**
+     * Write a CHECKPOINT record listing the running transactions, and point
+     * the header at it. All dirty pages must have been written to disk.
+     */
+    pub fn log_checkpoint(&self) -> DbResult<()> {
+        let mut state = self.state.lock().unwrap();
+        let mut active: Vec<(u64, u64)> = state.active.iter().map(|(&t, &o)| (t, o)).collect();
+        active.sort_unstable();
+        let offset = Self::append(&mut state, 0, &Record::Checkpoint(active))?;
+        state.file.seek(SeekFrom::Start(0))?;
+        state.file.write_all(&(offset as i64).to_be_bytes())?;
+        state.file.sync_all()?;
+        Ok(())
+    }
+
+    /// Force all buffered log records to disk.
+    pub fn force(&self) -> DbResult<()> {
+        self.state.lock().unwrap().file.sync_all()?;
+        Ok(())
+    }
+
+    /**
+     * Rollback the specified transaction: hands the before image of each
+     * page it updated to `install`, most recent update first.
+     */
+    pub fn rollback(
+        &self,
+        tid: TransactionId,
+        install: &mut dyn FnMut(&PageImage) -> DbResult<()>,
+    ) -> DbResult<()> {
+        let images = {
+            let mut state = self.state.lock().unwrap();
+            let start = match state.active.get(&tid.get_id()) {
+                Some(&start) => start,
+                None => return Ok(()),
+            };
+            let records = Self::read_records(&mut state.file, start)?;
+            Self::undo_images(&records, &[tid.get_id()].iter().copied().collect())
+        };
+        images.iter().try_for_each(install)
+    }
+
+    /**
+     * Recover the database after a crash. The after images of committed
+     * transactions are installed in log order (redo), then the before
+     * images of transactions that neither committed nor aborted, in
+     * reverse log order (undo). Those transactions are logged as aborted,
+     * and a checkpoint is written.
+     */
+    pub fn recover(&self, install: &mut dyn FnMut(&PageImage) -> DbResult<()>) -> DbResult<()> {
+        let (images, losers) = {
+            let mut state = self.state.lock().unwrap();
+            state.active.clear();
+            Self::plan_recovery(&mut state.file)?
+        };
+        images.iter().try_for_each(install)?;
+
+        let mut state = self.state.lock().unwrap();
+        for tid in losers {
+            Self::append(&mut state, tid, &Record::Abort)?;
+        }
+        drop(state);
+        self.log_checkpoint()
+    }
+
+    /// Returns the images to install to recover, and the transactions to abort.
+    fn plan_recovery(file: &mut File) -> DbResult<(Vec<PageImage>, Vec<u64>)> {
+        file.seek(SeekFrom::Start(0))?;
+        let checkpoint = read_i64(file)?;
+        let mut running = HashSet::new();
+        let (scan_start, redo_start) = if checkpoint == NO_CHECKPOINT {
+            (HEADER_SIZE, HEADER_SIZE)
+        } else {
+            let checkpoint = checkpoint as u64;
+            let mut start = checkpoint;
+            let records = Self::read_records(file, checkpoint)?;
+            if let Some(Record::Checkpoint(active)) = records.first().map(|r| &r.record) {
+                for &(tid, offset) in active {
+                    running.insert(tid);
+                    start = start.min(offset);
+                }
+            }
+            (start, checkpoint)
+        };
+
+        let records = Self::read_records(file, scan_start)?;
+        let mut committed = HashSet::new();
+        let mut finished = HashSet::new();
+        for r in records.iter() {
+            match r.record {
+                Record::Commit => {
+                    committed.insert(r.tid);
+                    finished.insert(r.tid);
+                }
+                Record::Abort => {
+                    finished.insert(r.tid);
+                }
+                Record::Begin | Record::Update { .. } => {
+                    running.insert(r.tid);
+                }
+                Record::Checkpoint(_) => {}
+            }
+        }
+        let losers: HashSet<u64> = running.difference(&finished).copied().collect();
+
+        let mut images: Vec<PageImage> = records
+            .iter()
+            .filter(|r| r.offset >= redo_start && committed.contains(&r.tid))
+            .filter_map(|r| match &r.record {
+                Record::Update { after, .. } => Some(after.clone()),
+                _ => None,
+            })
+            .collect();
+        images.extend(Self::undo_images(&records, &losers));
+        let mut losers: Vec<u64> = losers.into_iter().collect();
+        losers.sort_unstable();
+        Ok((images, losers))
+    }
+
+    /// The before images of the updates of `tids`, most recent first.
+    fn undo_images(records: &[LogRecord], tids: &HashSet<u64>) -> Vec<PageImage> {
+        records
+            .iter()
+            .rev()
+            .filter(|r| tids.contains(&r.tid))
+            .filter_map(|r| match &r.record {
+                Record::Update { before, .. } => Some(before.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn begin(state: &mut LogState, tid: u64) -> DbResult<()> {
+        if !state.active.contains_key(&tid) {
+            let offset = Self::append(state, tid, &Record::Begin)?;
+            state.active.insert(tid, offset);
+        }
+        Ok(())
+    }
+
+    fn log_end(&self, tid: u64, record: &Record) -> DbResult<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.active.remove(&tid).is_some() {
+            Self::append(&mut state, tid, record)?;
+            state.file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Appends a record to the log, returning the offset it starts at.
+    fn append(state: &mut LogState, tid: u64, record: &Record) -> DbResult<u64> {
+        let offset = state.file.seek(SeekFrom::End(0))?;
+        let mut data = Vec::new();
+        let record_type = match record {
+            Record::Begin => BEGIN_RECORD,
+            Record::Commit => COMMIT_RECORD,
+            Record::Abort => ABORT_RECORD,
+            Record::Update { .. } => UPDATE_RECORD,
+            Record::Checkpoint(_) => CHECKPOINT_RECORD,
+        };
+        data.extend_from_slice(&record_type.to_be_bytes());
+        data.extend_from_slice(&(tid as i64).to_be_bytes());
+        match record {
+            Record::Update { before, after } => {
+                write_image(&mut data, before);
+                write_image(&mut data, after);
+            }
+            Record::Checkpoint(active) => {
+                data.extend_from_slice(&(active.len() as i32).to_be_bytes());
+                for &(tid, first) in active {
+                    data.extend_from_slice(&(tid as i64).to_be_bytes());
+                    data.extend_from_slice(&(first as i64).to_be_bytes());
+                }
+            }
+            _ => {}
+        }
+        data.extend_from_slice(&(offset as i64).to_be_bytes());
+        state.file.write_all(&data)?;
+        Ok(offset)
+    }
+
+    /**
+     * Reads the records from `start` to the end of the log. A record cut
+     * short by a crash ends the log.
+     */
+    fn read_records(file: &mut File, start: u64) -> DbResult<Vec<LogRecord>> {
+        file.seek(SeekFrom::Start(start))?;
+        let mut reader = BufReader::new(file);
+        let mut records = Vec::new();
+        let mut offset = start;
+        loop {
+            match read_record(&mut reader, offset) {
+                Ok(record) => {
+                    offset = reader.stream_position()?;
+                    records.push(record);
+                }
+                Err(DbError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(records)
+    }
+}
+
+fn write_image(data: &mut Vec<u8>, image: &PageImage) {
+    data.extend_from_slice(&(image.table_id as i64).to_be_bytes());
+    data.extend_from_slice(&(image.pid.len() as i32).to_be_bytes());
+    for i in image.pid.iter() {
+        data.extend_from_slice(&i.to_be_bytes());
+    }
+    data.extend_from_slice(&(image.data.len() as i32).to_be_bytes());
+    data.extend_from_slice(&image.data);
+}
+
+fn read_i32(r: &mut dyn Read) -> DbResult<i32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(i32::from_be_bytes(buf))
+}
+
+fn read_i64(r: &mut dyn Read) -> DbResult<i64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(i64::from_be_bytes(buf))
+}
+
+/// Reads a page image, failing if its lengths are not those of a page.
+fn read_image(r: &mut dyn Read, offset: u64) -> DbResult<PageImage> {
+    let corrupt = || DbError::Db(format!("corrupt log record at offset {}", offset));
+    let table_id = read_i64(r)? as u64;
+    let pid_len = read_i32(r)?;
+    if !(1..=MAX_PID_LEN).contains(&pid_len) {
+        return Err(corrupt());
+    }
+    let pid = (0..pid_len).map(|_| read_i32(r)).collect::<DbResult<_>>()?;
+    if read_i32(r)? != PAGE_SIZE as i32 {
+        return Err(corrupt());
+    }
+    let mut data = vec![0; PAGE_SIZE];
+    r.read_exact(&mut data)?;
+    Ok(PageImage {
+        table_id,
+        pid,
+        data,
+    })
+}
+
+fn read_record(r: &mut dyn Read, offset: u64) -> DbResult<LogRecord> {
+    let record_type = read_i32(r)?;
+    let tid = read_i64(r)? as u64;
+    let record = match record_type {
+        BEGIN_RECORD => Record::Begin,
+        COMMIT_RECORD => Record::Commit,
+        ABORT_RECORD => Record::Abort,
+        UPDATE_RECORD => Record::Update {
+            before: read_image(r, offset)?,
+            after: read_image(r, offset)?,
+        },
+        CHECKPOINT_RECORD => {
+            let count = read_i32(r)?;
+            let mut active = Vec::new();
+            for _ in 0..count {
+                active.push((read_i64(r)? as u64, read_i64(r)? as u64));
+            }
+            Record::Checkpoint(active)
+        }
+        _ => {
+            return Err(DbError::Db(format!(
+                "corrupt log record at offset {}",
+                offset
+            )))
+        }
+    };
+    if read_i64(r)? as u64 != offset {
+        return Err(DbError::Db(format!(
+            "corrupt log record at offset {}",
+            offset
+        )));
+    }
+    Ok(LogRecord {
+        offset,
+        tid,
+        record,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::int_field::IntField;
+    use crate::common::page_id::PageId;
+    use crate::downcast;
+    use crate::storage::heap_page::HeapPage;
+    use crate::storage::heap_page_id::HeapPageId;
+    use crate::test_util::{int_tuple, int_tuple_desc, TempFile};
+
+    /// Returns a page of `table_id` holding one tuple with `value`.
+    fn get_page(table_id: u64, pgno: i32, value: i32) -> HeapPage {
+        let pid = HeapPageId::new(table_id, pgno);
+        let data = HeapPage::create_empty_page_data();
+        let mut page = HeapPage::new(pid, &data, int_tuple_desc(1)).unwrap();
+        page.insert_tuple(int_tuple(&[value])).unwrap();
+        page
+    }
+
+    /// Returns the value of the tuple held by each installed image.
+    fn install_all(
+        f: impl FnOnce(&mut dyn FnMut(&PageImage) -> DbResult<()>) -> DbResult<()>,
+    ) -> Vec<(i32, i32)> {
+        let mut installed = Vec::new();
+        f(&mut |image| {
+            let pid = HeapPageId::deserialize(&image.pid)?;
+            let page = HeapPage::new(pid, &image.data, int_tuple_desc(1))?;
+            let t = page.iterator().next().unwrap();
+            let value = downcast!(t.get_field(0).unwrap(), IntField).get_value();
+            installed.push((pid.get_page_number(), value));
+            Ok(())
+        })
+        .unwrap();
+        installed
+    }
+
+    #[test]
+    fn test_rollback() {
+        let tmp = TempFile::new("log_rollback");
+        let log = LogFile::new(tmp.path()).unwrap();
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        log.log_write(t1, &get_page(1, 0, 1), &get_page(1, 0, 2))
+            .unwrap();
+        log.log_write(t2, &get_page(1, 1, 10), &get_page(1, 1, 11))
+            .unwrap();
+        log.log_write(t1, &get_page(1, 0, 2), &get_page(1, 0, 3))
+            .unwrap();
+
+        let installed = install_all(|install| log.rollback(t1, install));
+        assert_eq!(vec![(0, 2), (0, 1)], installed);
+        log.log_abort(t1).unwrap();
+        assert!(install_all(|install| log.rollback(t1, install)).is_empty());
+    }
+
+    #[test]
+    fn test_recover() {
+        let tmp = TempFile::new("log_recover");
+        {
+            let log = LogFile::new(tmp.path()).unwrap();
+            let (t1, t2, t3) = (
+                TransactionId::new(),
+                TransactionId::new(),
+                TransactionId::new(),
+            );
+            log.log_write(t1, &get_page(1, 0, 1), &get_page(1, 0, 2))
+                .unwrap();
+            log.log_write(t2, &get_page(1, 1, 10), &get_page(1, 1, 11))
+                .unwrap();
+            log.log_write(t3, &get_page(1, 2, 20), &get_page(1, 2, 21))
+                .unwrap();
+            log.log_commit(t1).unwrap();
+            log.log_abort(t3).unwrap();
+            log.force().unwrap();
+        }
+
+        // t1 is redone and t2, which never finished, is undone
+        let log = LogFile::new(tmp.path()).unwrap();
+        let installed = install_all(|install| log.recover(install));
+        assert_eq!(vec![(0, 2), (1, 10)], installed);
+
+        // t2 was logged as aborted
+        let log = LogFile::new(tmp.path()).unwrap();
+        assert!(install_all(|install| log.recover(install)).is_empty());
+    }
+
+    #[test]
+    fn test_recover_from_checkpoint() {
+        let tmp = TempFile::new("log_checkpoint");
+        {
+            let log = LogFile::new(tmp.path()).unwrap();
+            let (t1, t2, t3) = (
+                TransactionId::new(),
+                TransactionId::new(),
+                TransactionId::new(),
+            );
+            log.log_write(t1, &get_page(1, 0, 1), &get_page(1, 0, 2))
+                .unwrap();
+            log.log_commit(t1).unwrap();
+            log.log_write(t2, &get_page(1, 1, 10), &get_page(1, 1, 11))
+                .unwrap();
+            log.log_checkpoint().unwrap();
+            log.log_write(t3, &get_page(1, 2, 20), &get_page(1, 2, 21))
+                .unwrap();
+            log.log_commit(t3).unwrap();
+        }
+
+        // t1 committed before the checkpoint, so only t3 is redone, while
+        // t2, running at the checkpoint, is still undone
+        let log = LogFile::new(tmp.path()).unwrap();
+        let installed = install_all(|install| log.recover(install));
+        assert_eq!(vec![(2, 21), (1, 10)], installed);
+    }
+
+    #[test]
+    fn test_torn_record() {
+        let tmp = TempFile::new("log_torn");
+        {
+            let log = LogFile::new(tmp.path()).unwrap();
+            let t1 = TransactionId::new();
+            log.log_write(t1, &get_page(1, 0, 1), &get_page(1, 0, 2))
+                .unwrap();
+            log.log_commit(t1).unwrap();
+        }
+        // a crash in the middle of writing the commit record
+        let file = OpenOptions::new().write(true).open(tmp.path()).unwrap();
+        file.set_len(file.metadata().unwrap().len() - 4).unwrap();
+
+        let log = LogFile::new(tmp.path()).unwrap();
+        let installed = install_all(|install| log.recover(install));
+        assert_eq!(vec![(0, 1)], installed);
+    }
+
+    #[test]
+    fn test_corrupt_image() {
+        // the header, then a begin record, then the update record, whose
+        // before image starts with the table id
+        let pid_len_offset = HEADER_SIZE + 20 + 12 + 8;
+        let data_len_offset = pid_len_offset + 4 + 3 * 4;
+        let recover = |offset: u64, value: i32| {
+            let tmp = TempFile::new("log_corrupt");
+            {
+                let log = LogFile::new(tmp.path()).unwrap();
+                let tid = TransactionId::new();
+                log.log_write(tid, &get_page(1, 0, 1), &get_page(1, 0, 2))
+                    .unwrap();
+            }
+            let mut file = OpenOptions::new().write(true).open(tmp.path()).unwrap();
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.write_all(&value.to_be_bytes()).unwrap();
+            drop(file);
+            let log = LogFile::new(tmp.path()).unwrap();
+            log.recover(&mut |_| Ok(()))
+        };
+
+        assert!(recover(data_len_offset, PAGE_SIZE as i32).is_ok());
+        assert!(recover(data_len_offset, i32::MAX).is_err());
+        assert!(recover(data_len_offset, -1).is_err());
+        assert!(recover(pid_len_offset, 3).is_ok());
+        assert!(recover(pid_len_offset, 1 << 30).is_err());
+        assert!(recover(pid_len_offset, -1).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+pub mod buffer_pool;
+pub mod db_file;
+pub mod eviction;
+pub mod free_space_map;
+pub mod heap_file;
+pub mod heap_file_encoder;
+pub mod heap_page;
+pub mod heap_page_id;
+pub mod log_file;
+pub mod page;
+pub mod page_file;
@@ -0,0 +1,42 @@
+use crate::common::page_id::PageId;
+use crate::downcast::Downcast;
+use crate::transaction::transaction_id::TransactionId;
+
+/// Size of a page in bytes.
+pub const PAGE_SIZE: usize = 4096;
+
+/**
+ * Page is the interface used to represent pages that are resident in the
+ * BufferPool. Typically, DbFiles will read and write pages from disk.
+ */
+pub trait Page: Downcast + Send + Sync {
+    /// Return the id of this page.
+    fn get_id(&self) -> Box<dyn PageId>;
+
+    /**
+     * Generates a byte array representing the contents of this page.
+     * Used to serialize this page to disk.
+     */
+    fn get_page_data(&self) -> Vec<u8>;
+
+    /**
+     * Get the id of the transaction that last dirtied this page, or None if
+     * the page is not dirty.
+     */
+    fn is_dirty(&self) -> Option<TransactionId>;
+
+    /// Set the dirty state of this page as dirtied by a particular transaction.
+    fn mark_dirty(&mut self, dirty: bool, tid: TransactionId);
+
+    /**
+     * Return a representation of this page before any modifications were
+     * made to it. Used by recovery.
+     */
+    fn get_before_image(&self) -> Box<dyn Page>;
+
+    /**
+     * Take a snapshot of the current page contents as the before image,
+     * once the transaction that modified it commits.
+     */
+    fn set_before_image(&mut self);
+}
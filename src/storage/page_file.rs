@@ -0,0 +1,133 @@
+use super::page::PAGE_SIZE;
+use crate::common::error::{DbError, DbResult};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/**
+ * PageFile is the OS file behind a DbFile: a sequence of PAGE_SIZE pages,
+ * read and written by page number.
+ */
+pub struct PageFile {
+    file: Mutex<File>,
+    path: PathBuf,
+    id: u64,
+}
+
+impl PageFile {
+    /// Opens the file at `path`, creating an empty one if it does not exist yet.
+    pub fn open<P: AsRef<Path>>(path: P) -> DbResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.as_ref())?;
+        let path = fs::canonicalize(path)?;
+        // The id is derived from the absolute path, so reopening the same
+        // file yields the same table id.
+        let mut s = DefaultHasher::new();
+        path.hash(&mut s);
+        Ok(Self {
+            file: Mutex::new(file),
+            path,
+            id: s.finish(),
+        })
+    }
+
+    /// Returns the id of the table stored in this file.
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the absolute path of the file.
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn num_pages(&self) -> usize {
+        let file = self.file.lock().unwrap();
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        len as usize / PAGE_SIZE
+    }
+
+    /// Reads the data of page `pgno`, which must exist.
+    pub fn read_page(&self, pgno: i32) -> DbResult<Vec<u8>> {
+        if pgno < 0 || pgno as usize >= self.num_pages() {
+            return Err(DbError::Db(format!(
+                "page {} does not exist in {}",
+                pgno,
+                self.path.display()
+            )));
+        }
+        let mut data = vec![0; PAGE_SIZE];
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start((pgno as usize * PAGE_SIZE) as u64))?;
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Writes the data of page `pgno`. Writing the page right after the last one appends it.
+    pub fn write_page(&self, pgno: i32, data: &[u8]) -> DbResult<()> {
+        if pgno < 0 || pgno as usize > self.num_pages() {
+            return Err(DbError::Db(format!("cannot write page {}", pgno)));
+        }
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start((pgno as usize * PAGE_SIZE) as u64))?;
+        file.write_all(data)?;
+        Ok(())
+    }
+
+    /**
+     * Appends a page holding `data` and returns its page number. The file
+     * stays locked in between, so concurrent appends get distinct pages.
+     */
+    pub fn append_page(&self, data: &[u8]) -> DbResult<i32> {
+        let mut file = self.file.lock().unwrap();
+        let len = file.seek(SeekFrom::End(0))?;
+        let pgno = (len as usize / PAGE_SIZE) as i32;
+        file.seek(SeekFrom::Start((pgno as usize * PAGE_SIZE) as u64))?;
+        file.write_all(data)?;
+        Ok(pgno)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempFile;
+
+    #[test]
+    fn test_id() {
+        let tmp = TempFile::new("page_file_id");
+        let f1 = PageFile::open(tmp.path()).unwrap();
+        let f2 = PageFile::open(tmp.path()).unwrap();
+        assert_eq!(f1.get_id(), f2.get_id());
+
+        let other = TempFile::new("page_file_id_other");
+        let f3 = PageFile::open(other.path()).unwrap();
+        assert_ne!(f1.get_id(), f3.get_id());
+    }
+
+    #[test]
+    fn test_pages() {
+        let tmp = TempFile::new("page_file_pages");
+        let f = PageFile::open(tmp.path()).unwrap();
+        assert_eq!(0, f.num_pages());
+        assert_eq!(0, f.append_page(&[1; PAGE_SIZE]).unwrap());
+        assert_eq!(1, f.append_page(&[2; PAGE_SIZE]).unwrap());
+        f.write_page(2, &[3; PAGE_SIZE]).unwrap();
+        f.write_page(0, &[4; PAGE_SIZE]).unwrap();
+        assert_eq!(3, f.num_pages());
+
+        assert_eq!(vec![4; PAGE_SIZE], f.read_page(0).unwrap());
+        assert_eq!(vec![2; PAGE_SIZE], f.read_page(1).unwrap());
+        assert_eq!(vec![3; PAGE_SIZE], f.read_page(2).unwrap());
+        assert!(f.read_page(3).is_err());
+        assert!(f.read_page(-1).is_err());
+        assert!(f.write_page(4, &[0; PAGE_SIZE]).is_err());
+    }
+}
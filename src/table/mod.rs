@@ -1,3 +1,3 @@
-mod record_id;
+pub mod record_id;
 pub mod tuple;
 pub mod tuple_desc;
@@ -1,9 +1,12 @@
 use crate::common::page_id::PageId;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
 /**
  * A RecordId is a reference to a specific tuple on a specific page of a
  * specific table.
  */
+#[derive(Clone)]
 pub struct RecordId {
     pid: Box<dyn PageId>,
     tupleno: i32,
@@ -18,11 +21,64 @@ impl RecordId {
         self.tupleno
     }
 
-    pub fn get_page_id(&self) -> &Box<dyn PageId> {
-        &self.pid
+    pub fn get_page_id(&self) -> &dyn PageId {
+        self.pid.as_ref()
     }
 
+    /// Two RecordIds are equal if they point at the same slot of the same page.
     pub fn equals(&self, other: &Self) -> bool {
-        false
+        self == other
+    }
+}
+
+impl PartialEq for RecordId {
+    fn eq(&self, other: &Self) -> bool {
+        self.pid.key() == other.pid.key() && self.tupleno == other.tupleno
+    }
+}
+
+impl Eq for RecordId {}
+
+impl Hash for RecordId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pid.key().hash(state);
+        self.tupleno.hash(state);
+    }
+}
+
+impl fmt::Debug for RecordId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (table_id, pgno) = self.pid.key();
+        write!(f, "RecordId({}, {}, {})", table_id, pgno, self.tupleno)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::heap_page_id::HeapPageId;
+    use std::collections::HashSet;
+
+    fn rid(table_id: u64, pgno: i32, tupleno: i32) -> RecordId {
+        RecordId::new(Box::new(HeapPageId::new(table_id, pgno)), tupleno)
+    }
+
+    #[test]
+    fn test_equals() {
+        assert_eq!(rid(1, 2, 3), rid(1, 2, 3));
+        assert!(rid(1, 2, 3).equals(&rid(1, 2, 3)));
+        assert_ne!(rid(1, 2, 3), rid(4, 2, 3));
+        assert_ne!(rid(1, 2, 3), rid(1, 4, 3));
+        assert_ne!(rid(1, 2, 3), rid(1, 2, 4));
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut set = HashSet::new();
+        set.insert(rid(1, 2, 3));
+        set.insert(rid(1, 2, 3).clone());
+        set.insert(rid(1, 2, 4));
+        assert_eq!(2, set.len());
+        assert!(set.contains(&rid(1, 2, 4)));
     }
 }
@@ -1,7 +1,6 @@
 use super::record_id::RecordId;
 use super::tuple_desc::TupleDesc;
 use crate::common::field::Field;
-use crate::downcast;
 use std::cmp::Ordering;
 use std::fmt;
 use std::slice::Iter;
@@ -11,10 +10,11 @@ use std::slice::Iter;
  * specified schema specified by a TupleDesc object and contain Field objects
  * with the data for each field.
  */
+#[derive(Clone)]
 pub struct Tuple {
     td: TupleDesc,
     fields: Vec<Box<dyn Field>>,
-    // record_id: RecordId,
+    record_id: Option<RecordId>,
 }
 
 impl Tuple {
@@ -22,7 +22,7 @@ impl Tuple {
         Self {
             td,
             fields: vec![],
-            // record_id,
+            record_id: None,
         }
     }
 
@@ -30,17 +30,18 @@ impl Tuple {
         &self.td
     }
 
-    // pub fn get_record_id(&self) -> &RecordId {
-    //     &self.record_id
-    // }
+    /// The RecordId representing the location of this tuple on disk, if any.
+    pub fn get_record_id(&self) -> Option<&RecordId> {
+        self.record_id.as_ref()
+    }
 
     pub fn set_tuple_desc(&mut self, td: TupleDesc) {
         self.td = td
     }
 
-    // pub fn set_record_id(&mut self, rid: RecordId) {
-    //     self.record_id = rid
-    // }
+    pub fn set_record_id(&mut self, rid: RecordId) {
+        self.record_id = Some(rid)
+    }
 
     pub fn set_field(&mut self, i: usize, field: Box<dyn Field>) {
         match i.cmp(&self.fields.len()) {
@@ -50,13 +51,25 @@ impl Tuple {
         }
     }
 
-    pub fn get_field(&self, i: usize) -> Option<&Box<dyn Field>> {
-        self.fields.get(i)
+    pub fn get_field(&self, i: usize) -> Option<&dyn Field> {
+        self.fields.get(i).map(|field| field.as_ref())
     }
 
     pub fn get_fields(&self) -> Iter<'_, Box<dyn Field>> {
         self.fields.iter()
     }
+
+    /**
+     * Concatenate the fields of t1 and t2 into a new tuple described by td,
+     * with the fields of t1 coming first.
+     */
+    pub fn merge(td: TupleDesc, t1: &Tuple, t2: &Tuple) -> Self {
+        Self {
+            td,
+            fields: t1.fields.iter().chain(t2.fields.iter()).cloned().collect(),
+            record_id: None,
+        }
+    }
 }
 
 impl fmt::Display for Tuple {
@@ -70,6 +83,8 @@ mod test {
     use super::*;
     use crate::common::int_field::IntField;
     use crate::common::ty::Type;
+    use crate::downcast;
+    use crate::storage::heap_page_id::HeapPageId;
 
     fn get_type(len: usize) -> Vec<Type> {
         let mut types = Vec::with_capacity(len);
@@ -79,15 +94,6 @@ mod test {
         types
     }
 
-    fn get_string(len: usize, val: &str) -> Vec<String> {
-        let mut strings = Vec::with_capacity(len);
-        let combine = |i: usize| val.to_string() + i.to_string().as_str();
-        for i in 0..len {
-            strings.push(combine(i));
-        }
-        strings
-    }
-
     #[test]
     fn test_modify_fields() {
         let td = TupleDesc::default_new(get_type(2));
@@ -123,4 +129,22 @@ mod test {
         let tup = Tuple::new(td);
         assert_eq!(TupleDesc::default_new(get_type(5)), *tup.get_tuple_desc());
     }
+
+    #[test]
+    fn test_record_id() {
+        let td = TupleDesc::default_new(get_type(1));
+        let mut tup = Tuple::new(td.clone());
+        assert!(tup.get_record_id().is_none());
+
+        tup.set_record_id(RecordId::new(Box::new(HeapPageId::new(1, 2)), 3));
+        let rid = tup.get_record_id().unwrap();
+        assert_eq!(3, rid.get_tuple_number());
+        assert_eq!(2, rid.get_page_id().get_page_number());
+        assert_eq!(1, rid.get_page_id().get_table_id());
+        assert!(tup.clone().get_record_id().is_some());
+
+        // a joined tuple is not stored anywhere
+        let merged = Tuple::merge(TupleDesc::merge(td.clone(), td), &tup, &tup);
+        assert!(merged.get_record_id().is_none());
+    }
 }
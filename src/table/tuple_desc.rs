@@ -19,11 +19,11 @@ impl TDItem {
 
 impl fmt::Display for TDItem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}({})", self.field_name, self.field_type.to_string())
+        write!(f, "{}({})", self.field_name, self.field_type)
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct TupleDesc {
     items: Vec<TDItem>,
 }
@@ -65,18 +65,12 @@ impl TupleDesc {
 
     /// Gets the (possibly null) field name of the ith field of this TupleDesc.
     pub fn get_field_name(&self, i: usize) -> Option<&str> {
-        match self.items.get(i) {
-            Some(item) => Some(&item.field_name),
-            None => None,
-        }
+        self.items.get(i).map(|item| item.field_name.as_str())
     }
 
     /// Gets the type of the ith field of this TupleDesc.
     pub fn get_field_type(&self, i: usize) -> Option<Type> {
-        match self.items.get(i) {
-            Some(item) => Some(item.field_type),
-            None => None,
-        }
+        self.items.get(i).map(|item| item.field_type)
     }
 
     /// Find the index of the field with a given name.
@@ -89,6 +83,16 @@ impl TupleDesc {
         None
     }
 
+    /// Returns true if both TupleDescs have the same field types, ignoring names.
+    pub fn types_match(&self, other: &TupleDesc) -> bool {
+        self.items.len() == other.items.len()
+            && self
+                .items
+                .iter()
+                .zip(other.items.iter())
+                .all(|(a, b)| a.field_type == b.field_type)
+    }
+
     pub fn get_size(&self) -> i32 {
         self.items.iter().map(|item| item.field_type.len()).sum()
     }
@@ -111,7 +115,8 @@ impl fmt::Display for TupleDesc {
      * the exact format does not matter.
      */
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "")
+        let items: Vec<String> = self.items.iter().map(|item| item.to_string()).collect();
+        write!(f, "{}", items.join(", "))
     }
 }
 
@@ -123,6 +128,8 @@ mod test {
     fn test_to_string() {
         let item = TDItem::new(Type::Int, "Age".to_string());
         assert_eq!(item.to_string(), "Age(Int)".to_string());
+        let td = TupleDesc::new(vec![Type::Int, Type::Str], vec!["a", "b"]);
+        assert_eq!("a(Int), b(Str)", td.to_string());
     }
 
     #[test]
@@ -203,7 +210,7 @@ mod test {
 
         let td1 = TupleDesc::new(get_type(1), str1.iter().map(|n| n.as_str()).collect());
         let td2 = TupleDesc::new(get_type(2), str2.iter().map(|n| n.as_str()).collect());
-        assert_eq!(combined_strings(&td1, &td2, td3), true);
+        assert!(combined_strings(&td1, &td2, td3));
 
         let td3 = TupleDesc::merge(td2, td1);
         assert_eq!(3, td3.num_fields());
@@ -215,7 +222,7 @@ mod test {
         let td1 = TupleDesc::new(get_type(1), str1.iter().map(|n| n.as_str()).collect());
         let td2 = TupleDesc::new(get_type(2), str2.iter().map(|n| n.as_str()).collect());
         let td2_clone = TupleDesc::new(get_type(2), str2.iter().map(|n| n.as_str()).collect());
-        assert_eq!(combined_strings(&td2, &td1, td3), true);
+        assert!(combined_strings(&td2, &td1, td3));
 
         let td3 = TupleDesc::merge(td2, td2_clone);
         assert_eq!(4, td3.num_fields());
@@ -224,14 +231,14 @@ mod test {
             assert_eq!(Some(Type::Int), td3.get_field_type(i));
         }
         let td2 = TupleDesc::new(get_type(2), str2.iter().map(|n| n.as_str()).collect());
-        assert_eq!(combined_strings(&td2, &td2, td3), true);
+        assert!(combined_strings(&td2, &td2, td3));
     }
 
     #[test]
     fn test_get_type() {
         let lengths = [1, 2, 1000];
         for len in lengths.iter() {
-            let td = TupleDesc::default_new(get_type(*len as usize));
+            let td = TupleDesc::default_new(get_type(*len));
             for i in 0..*len {
                 assert_eq!(Some(Type::Int), td.get_field_type(i));
             }
@@ -298,4 +305,13 @@ mod test {
         assert_ne!(int_string, single_int);
         assert_ne!(int_string, single_int2);
     }
+
+    #[test]
+    fn test_types_match() {
+        let named = TupleDesc::new(vec![Type::Int, Type::Str], vec!["a", "b"]);
+        let unnamed = TupleDesc::default_new(vec![Type::Int, Type::Str]);
+        assert!(named.types_match(&unnamed));
+        assert!(!named.types_match(&TupleDesc::default_new(vec![Type::Int])));
+        assert!(!named.types_match(&TupleDesc::default_new(vec![Type::Str, Type::Int])));
+    }
 }
@@ -0,0 +1,229 @@
+use crate::common::database::Database;
+use crate::common::error::DbResult;
+use crate::common::int_field::IntField;
+use crate::common::ty::Type;
+use crate::downcast;
+use crate::execution::op_iterator::OpIterator;
+use crate::execution::seq_scan::SeqScan;
+use crate::storage::db_file::DbFile;
+use crate::storage::heap_file::HeapFile;
+use crate::storage::heap_page::HeapPage;
+use crate::storage::heap_page_id::HeapPageId;
+use crate::table::tuple::Tuple;
+use crate::table::tuple_desc::TupleDesc;
+use crate::transaction::transaction_id::TransactionId;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/**
+ * A file under the system temp directory that is removed when dropped.
+ */
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("simple_db_{}_{}", process::id(), name));
+        let _ = fs::remove_file(&path);
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/**
+ * A directory under the system temp directory that is removed, with its
+ * contents, when dropped.
+ */
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("simple_db_{}_{}", process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Returns a TupleDesc of `columns` Int fields named `field0`, `field1`, ...
+pub fn int_tuple_desc(columns: usize) -> TupleDesc {
+    let names: Vec<String> = (0..columns).map(|i| format!("field{}", i)).collect();
+    TupleDesc::new(
+        vec![Type::Int; columns],
+        names.iter().map(|n| n.as_str()).collect(),
+    )
+}
+
+/// Returns a tuple of Int fields holding `values`.
+pub fn int_tuple(values: &[i32]) -> Tuple {
+    let mut t = Tuple::new(int_tuple_desc(values.len()));
+    for (i, v) in values.iter().enumerate() {
+        t.set_field(i, Box::new(IntField::new(*v)));
+    }
+    t
+}
+
+/**
+ * Creates a HeapFile at `path` with `columns` Int fields, holding `rows`
+ * packed into as few pages as possible.
+ */
+pub fn create_heap_file(path: &Path, columns: usize, rows: &[Vec<i32>]) -> HeapFile {
+    let tuples = rows.iter().map(|row| int_tuple(row)).collect();
+    write_heap_file(path, int_tuple_desc(columns), tuples)
+}
+
+/// Creates a HeapFile at `path` holding `tuples`, packed into as few pages as possible.
+pub fn write_heap_file(path: &Path, td: TupleDesc, tuples: Vec<Tuple>) -> HeapFile {
+    let f = HeapFile::new(path, td).unwrap();
+    let mut pgno = 0;
+    let mut page = empty_page(&f, pgno);
+    let empty = tuples.is_empty();
+    for t in tuples {
+        if page.get_num_empty_slots() == 0 {
+            f.write_page(&page).unwrap();
+            pgno += 1;
+            page = empty_page(&f, pgno);
+        }
+        page.insert_tuple(t).unwrap();
+    }
+    if !empty {
+        f.write_page(&page).unwrap();
+    }
+    f
+}
+
+fn empty_page(f: &HeapFile, pgno: i32) -> HeapPage {
+    let pid = HeapPageId::new(f.get_id(), pgno);
+    HeapPage::new(
+        pid,
+        &HeapPage::create_empty_page_data(),
+        f.get_tuple_desc().clone(),
+    )
+    .unwrap()
+}
+
+/**
+ * Creates a table named `name` of `columns` Int fields holding `rows` in
+ * `dir`, and adds it to the catalog of `db`. Returns the id of the table.
+ */
+pub fn create_table(
+    db: &Database,
+    dir: &TempDir,
+    name: &str,
+    columns: usize,
+    rows: &[Vec<i32>],
+) -> u64 {
+    let f = create_heap_file(&dir.path().join(format!("{}.dat", name)), columns, rows);
+    let id = f.get_id();
+    db.get_catalog().add_table(Arc::new(f), name, None);
+    id
+}
+
+/// Like create_table, but for a table of arbitrary schema holding `tuples`.
+pub fn create_table_with(
+    db: &Database,
+    dir: &TempDir,
+    name: &str,
+    td: TupleDesc,
+    tuples: Vec<Tuple>,
+) -> u64 {
+    let f = write_heap_file(&dir.path().join(format!("{}.dat", name)), td, tuples);
+    let id = f.get_id();
+    db.get_catalog().add_table(Arc::new(f), name, None);
+    id
+}
+
+/**
+ * An open SeqScan over a table created for a test. The directory holding
+ * the table is removed when the scan is dropped.
+ */
+struct TestScan {
+    scan: SeqScan,
+    _dir: TempDir,
+}
+
+impl OpIterator for TestScan {
+    fn open(&mut self) -> DbResult<()> {
+        self.scan.open()
+    }
+
+    fn has_next(&mut self) -> DbResult<bool> {
+        self.scan.has_next()
+    }
+
+    fn next(&mut self) -> DbResult<Tuple> {
+        self.scan.next()
+    }
+
+    fn rewind(&mut self) -> DbResult<()> {
+        self.scan.rewind()
+    }
+
+    fn close(&mut self) {
+        self.scan.close()
+    }
+
+    fn get_tuple_desc(&self) -> &TupleDesc {
+        self.scan.get_tuple_desc()
+    }
+}
+
+/**
+ * Returns an open scan with alias `alias` over a new table of `columns` Int
+ * fields holding `rows`, in a database of its own.
+ */
+pub fn scan_ints(alias: &str, columns: usize, rows: &[Vec<i32>]) -> Box<dyn OpIterator> {
+    let tuples = rows.iter().map(|row| int_tuple(row)).collect();
+    scan_tuples(alias, int_tuple_desc(columns), tuples)
+}
+
+/// Like scan_ints, but over a table of arbitrary schema holding `tuples`.
+pub fn scan_tuples(alias: &str, td: TupleDesc, tuples: Vec<Tuple>) -> Box<dyn OpIterator> {
+    static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+    let dir = TempDir::new(&format!("scan_{}", NEXT_DIR.fetch_add(1, Ordering::SeqCst)));
+    let db = Arc::new(Database::new(10));
+    let id = create_table_with(&db, &dir, alias, td, tuples);
+    let mut scan = SeqScan::new(db, TransactionId::new(), id, alias).unwrap();
+    scan.open().unwrap();
+    Box::new(TestScan { scan, _dir: dir })
+}
+
+/// Reads all remaining tuples of an open operator whose fields are all Ints.
+pub fn read_ints(op: &mut dyn OpIterator) -> Vec<Vec<i32>> {
+    let mut rows = Vec::new();
+    while op.has_next().unwrap() {
+        let t = op.next().unwrap();
+        rows.push(
+            t.get_fields()
+                .map(|f| downcast!(f, IntField).get_value())
+                .collect(),
+        );
+    }
+    rows
+}
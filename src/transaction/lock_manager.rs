@@ -0,0 +1,346 @@
+use super::transaction_id::TransactionId;
+use crate::common::error::{DbError, DbResult};
+use crate::common::page_id::PageKey;
+use crate::common::permissions::Permissions;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct PageLock {
+    shared: HashSet<TransactionId>,
+    exclusive: Option<TransactionId>,
+}
+
+impl PageLock {
+    fn is_free(&self) -> bool {
+        self.shared.is_empty() && self.exclusive.is_none()
+    }
+
+    /// Returns the transactions other than `tid` that keep it from taking the lock.
+    fn blockers(&self, tid: TransactionId, perm: Permissions) -> HashSet<TransactionId> {
+        let mut blockers = HashSet::new();
+        if let Some(holder) = self.exclusive {
+            if holder != tid {
+                blockers.insert(holder);
+            }
+        }
+        if perm == Permissions::ReadWrite {
+            blockers.extend(self.shared.iter().filter(|&&t| t != tid));
+        }
+        blockers
+    }
+}
+
+#[derive(Default)]
+struct LockTable {
+    locks: HashMap<PageKey, PageLock>,
+    held: HashMap<TransactionId, HashSet<PageKey>>,
+    waits_for: HashMap<TransactionId, HashSet<TransactionId>>,
+}
+
+impl LockTable {
+    /// Returns true if following the waits-for edges from `tid` leads back to it.
+    fn in_cycle(&self, tid: TransactionId) -> bool {
+        let mut stack: Vec<TransactionId> = self
+            .waits_for
+            .get(&tid)
+            .map(|ts| ts.iter().copied().collect())
+            .unwrap_or_default();
+        let mut seen = HashSet::new();
+        while let Some(t) = stack.pop() {
+            if t == tid {
+                return true;
+            }
+            if seen.insert(t) {
+                if let Some(next) = self.waits_for.get(&t) {
+                    stack.extend(next.iter().copied());
+                }
+            }
+        }
+        false
+    }
+
+    fn grant(&mut self, tid: TransactionId, key: PageKey, perm: Permissions) {
+        let lock = self.locks.entry(key).or_default();
+        match perm {
+            Permissions::ReadOnly => {
+                if lock.exclusive != Some(tid) {
+                    lock.shared.insert(tid);
+                }
+            }
+            Permissions::ReadWrite => {
+                lock.shared.remove(&tid);
+                lock.exclusive = Some(tid);
+            }
+        }
+        self.held.entry(tid).or_default().insert(key);
+    }
+
+    fn release(&mut self, tid: TransactionId, key: PageKey) {
+        if let Some(lock) = self.locks.get_mut(&key) {
+            lock.shared.remove(&tid);
+            if lock.exclusive == Some(tid) {
+                lock.exclusive = None;
+            }
+            if lock.is_free() {
+                self.locks.remove(&key);
+            }
+        }
+    }
+}
+
+/**
+ * LockManager grants shared and exclusive locks on pages to transactions,
+ * for strict two-phase locking: locks are only given back all at once, when
+ * the transaction commits or aborts.
+ *
+ * A transaction asking for a lock that conflicts with locks held by others
+ * waits until they are released. If waiting would close a cycle in the
+ * waits-for graph, or lasts longer than the optional timeout, the requesting
+ * transaction is chosen as the victim and gets a TransactionAborted error.
+ * It keeps the locks it already holds until it is aborted.
+ */
+#[derive(Default)]
+pub struct LockManager {
+    table: Mutex<LockTable>,
+    released: Condvar,
+    timeout: Option<Duration>,
+}
+
+impl LockManager {
+    /// Creates a LockManager that detects deadlocks with a waits-for graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Creates a LockManager that, on top of the waits-for graph, aborts a
+     * transaction which has waited longer than `timeout` for a lock.
+     */
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::default()
+        }
+    }
+
+    /**
+     * Blocks until `tid` holds a lock on the page suitable for `perm`. A
+     * transaction holding the only shared lock on a page upgrades it to an
+     * exclusive lock.
+     */
+    pub fn acquire(&self, tid: TransactionId, key: PageKey, perm: Permissions) -> DbResult<()> {
+        let deadline = self.timeout.map(|t| Instant::now() + t);
+        let mut table = self.table.lock().unwrap();
+        loop {
+            let blockers = table
+                .locks
+                .get(&key)
+                .map(|lock| lock.blockers(tid, perm))
+                .unwrap_or_default();
+            if blockers.is_empty() {
+                table.waits_for.remove(&tid);
+                table.grant(tid, key, perm);
+                return Ok(());
+            }
+
+            table.waits_for.insert(tid, blockers);
+            if table.in_cycle(tid) {
+                table.waits_for.remove(&tid);
+                return Err(DbError::TransactionAborted(format!(
+                    "deadlock detected while transaction {} waited for page {:?}",
+                    tid, key
+                )));
+            }
+            table = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        table.waits_for.remove(&tid);
+                        return Err(DbError::TransactionAborted(format!(
+                            "transaction {} timed out waiting for page {:?}",
+                            tid, key
+                        )));
+                    }
+                    self.released.wait_timeout(table, deadline - now).unwrap().0
+                }
+                None => self.released.wait(table).unwrap(),
+            };
+        }
+    }
+
+    /// Releases the lock `tid` holds on the page, if any.
+    pub fn release(&self, tid: TransactionId, key: PageKey) {
+        let mut table = self.table.lock().unwrap();
+        table.release(tid, key);
+        if let Some(keys) = table.held.get_mut(&tid) {
+            keys.remove(&key);
+        }
+        self.released.notify_all();
+    }
+
+    /// Releases all locks held by `tid`, when it commits or aborts.
+    pub fn release_all(&self, tid: TransactionId) {
+        let mut table = self.table.lock().unwrap();
+        for key in table.held.remove(&tid).unwrap_or_default() {
+            table.release(tid, key);
+        }
+        table.waits_for.remove(&tid);
+        self.released.notify_all();
+    }
+
+    /// Returns true if `tid` holds a lock of any kind on the page.
+    pub fn holds_lock(&self, tid: TransactionId, key: PageKey) -> bool {
+        let table = self.table.lock().unwrap();
+        table
+            .locks
+            .get(&key)
+            .map(|lock| lock.exclusive == Some(tid) || lock.shared.contains(&tid))
+            .unwrap_or(false)
+    }
+
+    /// Returns true if `tid` holds the exclusive lock on the page.
+    pub fn holds_exclusive(&self, tid: TransactionId, key: PageKey) -> bool {
+        let table = self.table.lock().unwrap();
+        table
+            .locks
+            .get(&key)
+            .map(|lock| lock.exclusive == Some(tid))
+            .unwrap_or(false)
+    }
+
+    /// Returns the pages on which `tid` holds a lock.
+    pub fn locked_pages(&self, tid: TransactionId) -> Vec<PageKey> {
+        let table = self.table.lock().unwrap();
+        table
+            .held
+            .get(&tid)
+            .map(|keys| keys.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread;
+
+    const P1: PageKey = (1, 0);
+    const P2: PageKey = (1, 1);
+
+    /// Asks for the lock in another thread; the receiver gets the outcome.
+    fn acquire_async(
+        lm: &Arc<LockManager>,
+        tid: TransactionId,
+        key: PageKey,
+        perm: Permissions,
+    ) -> mpsc::Receiver<DbResult<()>> {
+        let (tx, rx) = mpsc::channel();
+        let lm = lm.clone();
+        thread::spawn(move || tx.send(lm.acquire(tid, key, perm)).unwrap());
+        rx
+    }
+
+    fn is_blocked(rx: &mpsc::Receiver<DbResult<()>>) -> bool {
+        rx.recv_timeout(Duration::from_millis(100)).is_err()
+    }
+
+    #[test]
+    fn test_shared() {
+        let lm = LockManager::new();
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        lm.acquire(t1, P1, Permissions::ReadOnly).unwrap();
+        lm.acquire(t2, P1, Permissions::ReadOnly).unwrap();
+        assert!(lm.holds_lock(t1, P1));
+        assert!(lm.holds_lock(t2, P1));
+        assert!(!lm.holds_exclusive(t1, P1));
+    }
+
+    #[test]
+    fn test_exclusive_blocks() {
+        let lm = Arc::new(LockManager::new());
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        lm.acquire(t1, P1, Permissions::ReadWrite).unwrap();
+        // re-acquiring a held lock does not block
+        lm.acquire(t1, P1, Permissions::ReadOnly).unwrap();
+        assert!(lm.holds_exclusive(t1, P1));
+
+        let rx = acquire_async(&lm, t2, P1, Permissions::ReadOnly);
+        assert!(is_blocked(&rx));
+        lm.release_all(t1);
+        assert!(rx.recv().unwrap().is_ok());
+        assert!(lm.holds_lock(t2, P1));
+        assert!(!lm.holds_lock(t1, P1));
+        assert!(lm.locked_pages(t1).is_empty());
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let lm = Arc::new(LockManager::new());
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        lm.acquire(t1, P1, Permissions::ReadOnly).unwrap();
+        lm.acquire(t1, P1, Permissions::ReadWrite).unwrap();
+        assert!(lm.holds_exclusive(t1, P1));
+        lm.release(t1, P1);
+        assert!(!lm.holds_lock(t1, P1));
+
+        // a shared lock held by another transaction holds up the upgrade
+        lm.acquire(t1, P1, Permissions::ReadOnly).unwrap();
+        lm.acquire(t2, P1, Permissions::ReadOnly).unwrap();
+        let rx = acquire_async(&lm, t1, P1, Permissions::ReadWrite);
+        assert!(is_blocked(&rx));
+        lm.release(t2, P1);
+        assert!(rx.recv().unwrap().is_ok());
+        assert!(lm.holds_exclusive(t1, P1));
+    }
+
+    #[test]
+    fn test_deadlock() {
+        let lm = Arc::new(LockManager::new());
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        lm.acquire(t1, P1, Permissions::ReadWrite).unwrap();
+        lm.acquire(t2, P2, Permissions::ReadWrite).unwrap();
+
+        let rx = acquire_async(&lm, t1, P2, Permissions::ReadWrite);
+        assert!(is_blocked(&rx));
+        // t2 closes the cycle and is chosen as the victim
+        assert!(matches!(
+            lm.acquire(t2, P1, Permissions::ReadWrite),
+            Err(DbError::TransactionAborted(_))
+        ));
+        lm.release_all(t2);
+        assert!(rx.recv().unwrap().is_ok());
+        assert!(lm.holds_exclusive(t1, P2));
+    }
+
+    #[test]
+    fn test_upgrade_deadlock() {
+        let lm = Arc::new(LockManager::new());
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        lm.acquire(t1, P1, Permissions::ReadOnly).unwrap();
+        lm.acquire(t2, P1, Permissions::ReadOnly).unwrap();
+
+        let rx = acquire_async(&lm, t1, P1, Permissions::ReadWrite);
+        assert!(is_blocked(&rx));
+        assert!(lm.acquire(t2, P1, Permissions::ReadWrite).is_err());
+        lm.release_all(t2);
+        assert!(rx.recv().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let lm = LockManager::with_timeout(Duration::from_millis(50));
+        let (t1, t2) = (TransactionId::new(), TransactionId::new());
+        lm.acquire(t1, P1, Permissions::ReadOnly).unwrap();
+        let start = Instant::now();
+        assert!(matches!(
+            lm.acquire(t2, P1, Permissions::ReadWrite),
+            Err(DbError::TransactionAborted(_))
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(!lm.holds_lock(t2, P1));
+    }
+}
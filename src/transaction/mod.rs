@@ -0,0 +1,2 @@
+pub mod lock_manager;
+pub mod transaction_id;
@@ -0,0 +1,42 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/**
+ * TransactionId is a class that contains the identifier of a transaction.
+ * Every call to `new` returns a fresh id.
+ */
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub struct TransactionId(u64);
+
+impl TransactionId {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(NEXT_ID.fetch_add(1, Ordering::SeqCst))
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unique() {
+        let t1 = TransactionId::new();
+        let t2 = TransactionId::new();
+        assert_ne!(t1, t2);
+        assert!(t1 < t2);
+        assert_eq!(t1, t1.clone());
+    }
+}